@@ -4,35 +4,64 @@
 use anyhow::{Context, Result};
 use std::process::{Command, Stdio};
 use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+use crate::config::{AgentInvocation, PromptInput};
 
 /// Run agent in one-shot mode: pass full prompt on stdin, capture stdout.
 /// Used for optimization steps: we have the plan content + step prompt, we want the revised plan.
-pub fn run_agent_oneshot(
-    command: &str,
-    args: &[String],
-    prompt: &str,
-    plan_content: &str,
-) -> Result<String> {
+/// With `input = "arg"` the same text is passed as the last argument and stdin is closed.
+/// The agent is killed when its profile's timeout passes.
+pub fn run_agent_oneshot(agent: &AgentInvocation, prompt: &str, plan_content: &str) -> Result<String> {
     let full_input = format!(
         "{}\n\n---\n\nPlan to review/revise:\n\n{}",
         prompt, plan_content
     );
-    let mut child = Command::new(command)
-        .args(args)
-        .stdin(Stdio::piped())
+    let mut cmd = Command::new(&agent.command);
+    cmd.args(&agent.args).envs(agent.env.iter().map(|(k, v)| (k, v)));
+    match agent.input {
+        PromptInput::Stdin => cmd.stdin(Stdio::piped()),
+        PromptInput::Arg => cmd.arg(&full_input).stdin(Stdio::null()),
+    };
+    let mut child = cmd
         .stdout(Stdio::piped())
         .stderr(Stdio::inherit())
         .spawn()
-        .with_context(|| format!("spawn {} {}", command, args.join(" ")))?;
-    if let Some(mut stdin) = child.stdin.take() {
-        stdin.write_all(full_input.as_bytes()).context("write stdin")?;
-        stdin.flush().context("flush stdin")?;
-    }
-    let mut stdout = String::new();
-    if let Some(mut out) = child.stdout.take() {
-        out.read_to_string(&mut stdout).context("read stdout")?;
+        .with_context(|| format!("spawn {}", agent))?;
+    // Write stdin and read stdout on threads so a timeout can still kill an agent that
+    // stops reading its input or keeps its output open.
+    let writer = child.stdin.take().map(|mut stdin| {
+        std::thread::spawn(move || {
+            stdin.write_all(full_input.as_bytes())?;
+            stdin.flush()
+        })
+    });
+    let mut out = child.stdout.take().context("agent stdout")?;
+    let reader = std::thread::spawn(move || {
+        let mut stdout = String::new();
+        out.read_to_string(&mut stdout).map(|_| stdout)
+    });
+    let status = match agent.timeout {
+        None => child.wait().context("wait for agent")?,
+        Some(timeout) => {
+            let deadline = Instant::now() + timeout;
+            loop {
+                if let Some(status) = child.try_wait().context("wait for agent")? {
+                    break status;
+                }
+                if Instant::now() >= deadline {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    anyhow::bail!("agent timed out after {}s: {}", timeout.as_secs(), agent);
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            }
+        }
+    };
+    if let Some(writer) = writer {
+        writer.join().map_err(|_| anyhow::anyhow!("agent input writer panicked"))?.context("write stdin")?;
     }
-    let status = child.wait().context("wait for agent")?;
+    let stdout = reader.join().map_err(|_| anyhow::anyhow!("agent output reader panicked"))?.context("read stdout")?;
     if !status.success() {
         anyhow::bail!("agent exited with {}", status);
     }
@@ -41,9 +70,10 @@ pub fn run_agent_oneshot(
 
 /// Spawn agent interactively (no stdin pipe). User collaborates in their terminal.
 /// Used for `qp new` and `qp edit`. Caller should wait or detach as desired.
-pub fn run_agent_interactive(command: &str, args: &[String], initial_prompt: Option<&str>) -> Result<std::process::Child> {
-    let mut cmd = Command::new(command);
-    cmd.args(args);
+pub fn run_agent_interactive(agent: &AgentInvocation, initial_prompt: Option<&str>) -> Result<std::process::Child> {
+    let mut cmd = Command::new(&agent.command);
+    cmd.args(&agent.args);
+    cmd.envs(agent.env.iter().map(|(k, v)| (k, v)));
     cmd.stdin(Stdio::inherit());
     cmd.stdout(Stdio::inherit());
     cmd.stderr(Stdio::inherit());
@@ -52,6 +82,55 @@ pub fn run_agent_interactive(command: &str, args: &[String], initial_prompt: Opt
         // Common: `agent "prompt"` or `claude "prompt"`. We'll pass as first arg.
         cmd.arg(p);
     }
-    let child = cmd.spawn().with_context(|| format!("spawn {} {}", command, args.join(" ")))?;
+    let child = cmd.spawn().with_context(|| format!("spawn {}", agent))?;
     Ok(child)
 }
+
+/// Resolve a command the way the shell would: paths are checked directly, bare names
+/// are looked up in `PATH`. Returns the executable's path.
+pub fn find_command(command: &str) -> Option<std::path::PathBuf> {
+    let is_executable = |p: &std::path::Path| {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            p.metadata().map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+        }
+        #[cfg(not(unix))]
+        {
+            p.is_file()
+        }
+    };
+    if command.is_empty() {
+        return None;
+    }
+    if command.contains(std::path::MAIN_SEPARATOR) || command.contains('/') {
+        let p = std::path::PathBuf::from(command);
+        return is_executable(&p).then_some(p);
+    }
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).map(|dir| dir.join(command)).find(|p| is_executable(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_fires_when_agent_ignores_stdin() {
+        let agent = AgentInvocation {
+            command: "sleep".to_string(),
+            args: vec!["5".to_string()],
+            env: vec![],
+            timeout: Some(Duration::from_millis(300)),
+            input: PromptInput::Stdin,
+            profile: None,
+        };
+        // Far more than a pipe buffer, so a blocking write would never return.
+        let plan = "x".repeat(1 << 20);
+        let started = Instant::now();
+        let err = run_agent_oneshot(&agent, "Review.", &plan).unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{:#}", err);
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+}
@@ -176,72 +176,9 @@ fn main() -> Result<()> {
 }
 
 fn get_agent_prompt(agent_name: &str) -> Result<String> {
-    // Default prompts matching src/config.rs
-    let prompt = match agent_name {
-        "holes" => "Review this plan and identify gaps, risks, and issues. Output the complete plan with an updated Review Notes section (## Review Notes) containing these four subsections:\n\n\
-### Identified Weaknesses\n\
-List at least 5 specific weaknesses in the plan. Focus on missing security considerations, unclear requirements, underspecified behavior, missing error handling, and architectural gaps. Be concrete and actionable.\n\n\
-### Edge Cases\n\
-List at least 5 edge cases that the plan doesn't address. Think about boundary conditions, error states, concurrent access, invalid inputs, and failure scenarios.\n\n\
-### Assumptions to Validate\n\
-List at least 4 assumptions the plan makes that should be verified before implementation. These are things that could change the approach if they turn out to be false.\n\n\
-### Potential Failures\n\
-List at least 4 ways the implementation could fail in production. Consider infrastructure failures, data issues, scaling problems, and operational concerns.\n\n\
-Output the entire plan with the Review Notes section populated. Keep all other sections (Overview, Constraints, Implementation Notes, Tickets) unchanged.",
-        "details" => "Expand this plan with implementation details.\n\n\
-CRITICAL: Your output must be the COMPLETE, EXPANDED plan - not a summary or description of changes.\n\
-Do NOT ask for permission. Do NOT describe what you would add. Just output the full plan.\n\n\
-Add an ## Implementation Notes section (if not present, or expand existing) with:\n\n\
-### Technology Stack\n\
-Specify exact versions and libraries. Include language version, key dependencies with versions, build tools, and testing frameworks.\n\n\
-### Data Structures\n\
-Define the core data structures with actual code. Show structs/classes, interfaces/traits, and type definitions. Include field types and documentation.\n\n\
-### Algorithms & Logic\n\
-Document key algorithms with pseudocode or actual code. Explain the approach and any important implementation details.\n\n\
-### API Design\n\
-If applicable, show endpoint signatures, request/response schemas, and error formats.\n\n\
-Your response must START with the plan's YAML frontmatter (---) and include ALL sections: Overview, Constraints, Implementation Notes, Review Notes, and Tickets.\n\
-Do NOT write meta-commentary about the plan. Output ONLY the plan content.",
-        "breakdown" => "Break this plan into precise, atomic steps.\n\n\
-CRITICAL: Your output must be the COMPLETE plan with detailed steps added - not a summary or description.\n\
-Do NOT ask for permission. Do NOT describe what steps you would add. Output the full plan with steps.\n\n\
-For each ticket in the ## Tickets section, add a #### Steps subsection containing:\n\
-- Numbered steps (1, 2, 3, etc.) that are atomic and independently implementable\n\
-- Each step should have a clear action and a verification method\n\
-- Steps should be small enough to complete in under 2 hours\n\
-- Include specific commands, file names, and technical details\n\
-- End each step with 'Verify:' describing how to confirm completion\n\n\
-Example step format:\n\
-1. **Create user model file**\n\
-   - Create src/models/user.ts with User interface\n\
-   - Include id, email, name, createdAt fields\n\
-   - Verify: TypeScript compiles without errors\n\n\
-Your response must START with the plan's YAML frontmatter (---) and include ALL sections.\n\
-Output ONLY the plan content with steps added. No meta-commentary.",
-        "deliverables" => "Add clear acceptance criteria for each ticket in the plan.\n\n\
-CRITICAL: Your output must be the COMPLETE plan with acceptance criteria added - not a summary or description.\n\
-Do NOT ask for permission. Do NOT describe what criteria you would add. Output the full plan with criteria.\n\n\
-For each ticket in the ## Tickets section, add these subsections:\n\n\
-#### Acceptance Criteria\n\
-Numbered groups of specific, testable requirements. Use checkbox format for each item:\n\
-1. **Category Name**\n\
-   - [ ] Specific testable requirement\n\
-   - [ ] Another testable requirement\n\n\
-#### Demo Script\n\
-Concrete commands or code showing how to verify the feature works. Include expected output.\n\n\
-#### Test Requirements\n\
-Specific tests that must pass, with checkboxes:\n\
-- [ ] Test description\n\
-- [ ] Another test\n\n\
-Focus on:\n\
-- What specific behaviors must be verified?\n\
-- What can be demonstrated to stakeholders?\n\
-- How do we know the ticket is complete?\n\n\
-Your response must START with the plan's YAML frontmatter (---) and include ALL sections.\n\
-Output ONLY the plan content with acceptance criteria added. No meta-commentary.",
-        _ => anyhow::bail!("Unknown agent: {}", agent_name),
-    };
-    Ok(prompt.to_string())
+    // Same built-in prompts qp uses (prompts/*.md)
+    let prompt = qp::prompts::builtin(agent_name).with_context(|| format!("Unknown agent: {}", agent_name))?;
+    Ok(prompt.text().to_string())
 }
 
 fn load_test_cases(fixtures_dir: &PathBuf, only: Option<&str>) -> Result<Vec<TestCase>> {
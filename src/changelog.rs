@@ -0,0 +1,235 @@
+//! What each optimization step changed: sections and tickets added or removed, word-count
+//! and acceptance-criteria deltas.
+//!
+//! `qp optimize` writes one `history/vN.changes.toml` next to each `history/vN.md` it
+//! produces; `qp review` and `qp render` read them back as a changelog.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::plan;
+use crate::tickets;
+
+/// Structured diff between two versions of a plan body.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Changes {
+    /// Version this summarizes (`history/v{version}.md`).
+    pub version: u32,
+    /// Version it is compared with.
+    pub from_version: u32,
+    /// Optimization step that produced the version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+    /// Agent command that ran the step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+    /// `## Section` or `## Section › ### Subsection` outside the Tickets section.
+    #[serde(default)]
+    pub sections_added: Vec<String>,
+    #[serde(default)]
+    pub sections_removed: Vec<String>,
+    /// Ticket headings, e.g. "Ticket 4: Cache Layer".
+    #[serde(default)]
+    pub tickets_added: Vec<String>,
+    #[serde(default)]
+    pub tickets_removed: Vec<String>,
+    pub words_before: usize,
+    pub words_after: usize,
+    pub criteria_before: usize,
+    pub criteria_after: usize,
+}
+
+impl Changes {
+    pub fn words_delta(&self) -> i64 {
+        self.words_after as i64 - self.words_before as i64
+    }
+
+    pub fn criteria_delta(&self) -> i64 {
+        self.criteria_after as i64 - self.criteria_before as i64
+    }
+}
+
+fn signed(n: i64) -> String {
+    if n >= 0 {
+        format!("+{}", n)
+    } else {
+        n.to_string()
+    }
+}
+
+impl std::fmt::Display for Changes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = vec![];
+        if !self.sections_added.is_empty() {
+            parts.push(format!("+{} section(s)", self.sections_added.len()));
+        }
+        if !self.sections_removed.is_empty() {
+            parts.push(format!("-{} section(s)", self.sections_removed.len()));
+        }
+        if !self.tickets_added.is_empty() {
+            parts.push(format!("+{} ticket(s)", self.tickets_added.len()));
+        }
+        if !self.tickets_removed.is_empty() {
+            parts.push(format!("-{} ticket(s)", self.tickets_removed.len()));
+        }
+        parts.push(format!(
+            "{} words ({} → {})",
+            signed(self.words_delta()),
+            self.words_before,
+            self.words_after
+        ));
+        if self.criteria_delta() != 0 {
+            parts.push(format!("{} acceptance criteria", signed(self.criteria_delta())));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// `##`/`###` headings outside the Tickets section, as "Section" or "Section › Sub".
+fn section_headings(body: &str) -> Vec<String> {
+    let mut out = vec![];
+    let mut current: Option<String> = None;
+    let mut in_fence = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if let Some(h) = line.strip_prefix("## ") {
+            let h = h.trim().to_string();
+            out.push(h.clone());
+            current = Some(h);
+        } else if let Some(h) = line.strip_prefix("### ") {
+            match &current {
+                Some(c) if !c.eq_ignore_ascii_case("tickets") => out.push(format!("{} › {}", c, h.trim())),
+                _ => {}
+            }
+        }
+    }
+    out
+}
+
+fn ticket_headings(body: &str) -> Vec<String> {
+    tickets::parse_tickets(body)
+        .iter()
+        .map(|t| {
+            if t.id.chars().all(|c| c.is_ascii_digit()) {
+                format!("Ticket {}: {}", t.id, t.title)
+            } else {
+                t.title.clone()
+            }
+        })
+        .collect()
+}
+
+/// Acceptance criteria: checkbox items, or the list items of an "Acceptance Criteria"
+/// block that uses plain lists instead of checkboxes.
+pub fn count_criteria(body: &str) -> usize {
+    let mut total = 0;
+    let (mut checks, mut items, mut is_ac) = (0, 0, false);
+    let mut in_fence = false;
+    let flush = |checks: usize, items: usize, is_ac: bool| if is_ac && checks == 0 { items } else { checks };
+    for line in body.lines() {
+        let t = line.trim_start();
+        if t.starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if line.starts_with('#') {
+            total += flush(checks, items, is_ac);
+            (checks, items) = (0, 0);
+            is_ac = line.to_lowercase().contains("acceptance criteria");
+        } else if t.starts_with("- [ ]") || t.starts_with("- [x]") || t.starts_with("- [X]") {
+            checks += 1;
+        } else if t.starts_with("- ") || t.starts_with("* ") || t.split_once(". ").is_some_and(|(n, _)| n.parse::<u32>().is_ok()) {
+            items += 1;
+        }
+    }
+    total + flush(checks, items, is_ac)
+}
+
+pub fn word_count(body: &str) -> usize {
+    body.split_whitespace().count()
+}
+
+/// Compare two plan bodies.
+pub fn compute(before: &str, after: &str) -> Changes {
+    let diff = |a: &[String], b: &[String]| b.iter().filter(|x| !a.contains(x)).cloned().collect::<Vec<_>>();
+    let (sections_before, sections_after) = (section_headings(before), section_headings(after));
+    let (tickets_before, tickets_after) = (ticket_headings(before), ticket_headings(after));
+    Changes {
+        sections_added: diff(&sections_before, &sections_after),
+        sections_removed: diff(&sections_after, &sections_before),
+        tickets_added: diff(&tickets_before, &tickets_after),
+        tickets_removed: diff(&tickets_after, &tickets_before),
+        words_before: word_count(before),
+        words_after: word_count(after),
+        criteria_before: count_criteria(before),
+        criteria_after: count_criteria(after),
+        ..Default::default()
+    }
+}
+
+fn changes_path(qp_root: &Path, plan_id: &str, version: u32) -> std::path::PathBuf {
+    plan::plan_dir(qp_root, plan_id).join("history").join(format!("v{}.changes.toml", version))
+}
+
+/// Write `history/v{version}.changes.toml`.
+pub fn save(qp_root: &Path, plan_id: &str, changes: &Changes) -> Result<()> {
+    let path = changes_path(qp_root, plan_id, changes.version);
+    std::fs::write(&path, toml::to_string(changes)?).with_context(|| format!("write {}", path.display()))
+}
+
+pub fn load(qp_root: &Path, plan_id: &str, version: u32) -> Option<Changes> {
+    let s = std::fs::read_to_string(changes_path(qp_root, plan_id, version)).ok()?;
+    toml::from_str(&s).ok()
+}
+
+/// All recorded changes for a plan, oldest first.
+pub fn load_all(qp_root: &Path, plan_id: &str) -> Result<Vec<Changes>> {
+    let dir = plan::plan_dir(qp_root, plan_id).join("history");
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut out = vec![];
+    for entry in std::fs::read_dir(&dir)? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        if let Some(v) = name.strip_prefix('v').and_then(|n| n.strip_suffix(".changes.toml")) {
+            if let Some(changes) = v.parse().ok().and_then(|v| load(qp_root, plan_id, v)) {
+                out.push(changes);
+            }
+        }
+    }
+    out.sort_by_key(|c| c.version);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_changes() {
+        let before = "## Overview\n\nShip it.\n\n## Tickets\n\n### Ticket 1: Index\n\n- [ ] schema\n";
+        let after = "## Overview\n\nShip it fast.\n\n## Implementation Notes\n\n### Data Structures\n\nA map.\n\n\
+## Tickets\n\n### Ticket 1: Index\n\n- [ ] schema\n- [ ] writer\n\n### Ticket 2: API\n\n#### Acceptance Criteria\n\n1. Returns 200\n2. Paginates\n";
+        let c = compute(before, after);
+        assert_eq!(c.sections_added, vec!["Implementation Notes", "Implementation Notes › Data Structures"]);
+        assert!(c.sections_removed.is_empty());
+        assert_eq!(c.tickets_added, vec!["Ticket 2: API"]);
+        assert_eq!((c.criteria_before, c.criteria_after), (1, 4));
+        assert_eq!(c.words_delta(), word_count(after) as i64 - word_count(before) as i64);
+        assert!(c.to_string().starts_with("+2 section(s), +1 ticket(s), +"));
+        let back: Changes = toml::from_str(&toml::to_string(&c).unwrap()).unwrap();
+        assert_eq!(back, c);
+    }
+}
@@ -6,9 +6,18 @@ use colored::Colorize;
 use std::io::IsTerminal;
 use std::path::PathBuf;
 
-use crate::config::load_config;
-use crate::discovery::find_qp_root;
-use crate::plan::{self, PlanState};
+use crate::config::{self, load_config, ConfigScope};
+use crate::discovery::resolve_qp_root;
+use crate::error::QpError;
+use crate::export::{self, ExportTarget};
+use crate::import::{self, ImportFormat};
+use crate::graph;
+use crate::output::{self, OutputFormat};
+use crate::plan::{self, PlanDirNaming, PlanFilter, PlanLocation, PlanSort, PlanState};
+use crate::quarter::Quarter;
+use crate::report;
+use crate::schedule;
+use crate::tickets;
 use crate::optimize;
 
 #[derive(Parser)]
@@ -17,12 +26,39 @@ use crate::optimize;
 pub struct Cli {
     #[command(subcommand)]
     pub cmd: Option<Commands>,
+    /// Output format: text (default), json or yaml; `qp quarter` also takes markdown,
+    /// `qp graph` takes dot (default for text) or mermaid
+    #[arg(long, global = true, value_enum, value_name = "FORMAT", default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+    /// Override a config key for this run, e.g. `-c agent.command=codex` (repeatable;
+    /// wins over config files and QP_* environment variables)
+    #[arg(short = 'c', global = true, value_name = "KEY=VALUE", value_parser = parse_assignment)]
+    pub config: Vec<(String, String)>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// List plans in scope (default when no subcommand)
-    List,
+    List {
+        /// List archived plans instead of active ones
+        #[arg(long)]
+        archived: bool,
+        /// Only plans with this tag
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+        /// Only plans in this quarter (YYYY-Qn)
+        #[arg(long, value_name = "QUARTER")]
+        quarter: Option<Quarter>,
+        /// Only plans with this owner
+        #[arg(long, value_name = "OWNER")]
+        owner: Option<String>,
+        /// Only plans in this state
+        #[arg(long, value_name = "STATE")]
+        state: Option<PlanState>,
+        /// Sort by updated (default), created, title, priority, due or quarter
+        #[arg(long, value_name = "FIELD", default_value = "updated")]
+        sort: PlanSort,
+    },
     /// Create a new plan (spawns AI agent)
     New {
         #[arg(value_name = "NAME")]
@@ -32,6 +68,9 @@ pub enum Commands {
     Show {
         #[arg(value_name = "PLAN")]
         plan: String,
+        /// Print metadata, tickets and estimate totals instead of the full plan
+        #[arg(long)]
+        summary: bool,
     },
     /// Edit a plan (spawns AI agent)
     Edit {
@@ -43,13 +82,79 @@ pub enum Commands {
         #[arg(value_name = "PLAN")]
         plan: String,
     },
-    /// Delete a plan
+    /// Mark plan as in progress (warns when upstream plans are not completed)
+    Start {
+        #[arg(value_name = "PLAN")]
+        plan: String,
+        /// Reopen a completed plan
+        #[arg(long)]
+        force: bool,
+    },
+    /// Dependency graph of plans, or of one plan's tickets
+    Graph {
+        /// Show ticket dependencies for this plan instead of plan dependencies
+        #[arg(value_name = "PLAN")]
+        plan: Option<String>,
+    },
+    /// Set plan metadata: tags, owners, quarter, priority, due_date, team
+    Set {
+        #[arg(value_name = "PLAN")]
+        plan: String,
+        /// key=value pairs; lists are comma-separated, an empty value clears the field
+        #[arg(value_name = "KEY=VALUE", num_args = 1.., required = true, value_parser = parse_assignment)]
+        fields: Vec<(String, String)>,
+    },
+    /// Change a plan's title (updates slug and directory name)
+    Rename {
+        #[arg(value_name = "PLAN")]
+        plan: String,
+        #[arg(value_name = "TITLE", num_args = 1.., required = true)]
+        title: Vec<String>,
+    },
+    /// Copy a plan into a new draft
+    Clone {
+        #[arg(value_name = "PLAN")]
+        plan: String,
+        /// Title for the new plan (default: "<title> (copy)", numbered when taken)
+        #[arg(long)]
+        title: Option<String>,
+    },
+    /// Create a plan from a Markdown document, GitHub issue JSON or text file (`-` reads stdin)
+    Import {
+        #[arg(value_name = "FILE")]
+        file: String,
+        /// Input format
+        #[arg(long, value_enum, default_value_t = ImportFormat::Auto)]
+        from: ImportFormat,
+        /// Plan title (default: the document's title or file name)
+        #[arg(long)]
+        title: Option<String>,
+        /// Have the agent rewrite the imported document into qp's plan format
+        #[arg(long)]
+        normalize: bool,
+    },
+    /// Move a plan to the trash (recover with `qp trash restore`)
     Delete {
         #[arg(value_name = "PLAN")]
         plan: String,
         #[arg(long)]
         yes: bool,
     },
+    /// Move a plan to .qp/archive (hidden from list, still viewable)
+    Archive {
+        #[arg(value_name = "PLAN")]
+        plan: String,
+    },
+    /// Move an archived plan back to .qp/plans
+    Unarchive {
+        #[arg(value_name = "PLAN")]
+        plan: String,
+    },
+    /// Manage deleted plans
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
     /// Run optimization steps
     Optimize {
         #[arg(value_name = "PLAN")]
@@ -58,6 +163,9 @@ pub enum Commands {
         step: Option<String>,
         #[arg(long)]
         force: bool,
+        /// Run every step with this [profiles.<name>] instead of the configured agents
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
     },
     /// Show optimization history and diffs
     Review {
@@ -66,50 +174,351 @@ pub enum Commands {
     },
     /// Overview of all plans
     Status,
-    /// Show statistics
-    Stats,
+    /// Show statistics (also written to .qp/stats.toml)
+    Stats {
+        /// Only plans updated on or after this date (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE")]
+        since: Option<chrono::NaiveDate>,
+    },
+    /// Roll-up of all plans assigned to a quarter
+    Quarter {
+        /// Quarter as YYYY-Qn (default: current quarter)
+        #[arg(value_name = "QUARTER")]
+        quarter: Option<Quarter>,
+        /// Evaluate "late in the quarter" as of this date (YYYY-MM-DD; default: today)
+        #[arg(long, value_name = "DATE")]
+        as_of: Option<chrono::NaiveDate>,
+        /// Include archived plans
+        #[arg(long)]
+        include_archived: bool,
+    },
+    /// Critical path and capacity schedule for a plan's tickets or a whole quarter
+    Schedule {
+        #[arg(value_name = "PLAN", required_unless_present = "quarter")]
+        plan: Option<String>,
+        /// Schedule every plan assigned to this quarter (YYYY-Qn)
+        #[arg(long, value_name = "QUARTER", conflicts_with = "plan")]
+        quarter: Option<Quarter>,
+    },
+    /// Export a plan's tickets as issue-tracker payloads, or create the issues with --apply
+    Export {
+        #[arg(value_name = "PLAN")]
+        plan: String,
+        /// Payload shape: github-issues, gitlab-issues, jira-csv or linear-json
+        #[arg(long = "to", value_enum, value_name = "TARGET")]
+        to: ExportTarget,
+        /// Write the payload to a file instead of stdout
+        #[arg(long, short, value_name = "FILE", conflicts_with = "apply")]
+        output: Option<PathBuf>,
+        /// Create the issues through the tracker API (github-issues, gitlab-issues)
+        #[arg(long)]
+        apply: bool,
+        /// Tracker API base URL (default: [tracker] base_url, then the public API)
+        #[arg(long, value_name = "URL")]
+        base_url: Option<String>,
+        /// GitHub owner/name or GitLab project (default: [tracker] repo)
+        #[arg(long, value_name = "REPO")]
+        repo: Option<String>,
+    },
+    /// Render a plan (or all plans) as self-contained HTML for sharing and printing
+    Render {
+        #[arg(value_name = "PLAN", required_unless_present = "all")]
+        plan: Option<String>,
+        /// HTML output (the default and currently only format)
+        #[arg(long)]
+        html: bool,
+        /// Render every plan plus an index page as a static site
+        #[arg(long, conflicts_with = "plan")]
+        all: bool,
+        /// Output file (single plan; default stdout) or directory (--all; default .qp/site)
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Pull issue state from the tracker into ticket statuses (after `qp export --apply`)
+    Sync {
+        #[arg(value_name = "PLAN")]
+        plan: String,
+        /// Tracker API base URL (default: the URL used at export time)
+        #[arg(long, value_name = "URL")]
+        base_url: Option<String>,
+        /// Accept both sides for conflicting tickets and stop reporting them
+        #[arg(long)]
+        force: bool,
+    },
     /// Show version history for a plan
     History {
         #[arg(value_name = "PLAN")]
         plan: String,
     },
-    /// Show current configuration
+    /// Built-in review-agent prompts
+    Prompts {
+        #[command(subcommand)]
+        action: PromptsAction,
+    },
+    /// List, install and enable plugin packs of extra review steps
+    Plugin {
+        #[command(subcommand)]
+        action: PluginAction,
+        /// Use ~/.config/qp (plugins and config)
+        #[arg(long, global = true, conflicts_with = "local")]
+        global: bool,
+        /// Use .qp (plugins and config); default inside a project
+        #[arg(long, global = true)]
+        local: bool,
+    },
+    /// Show current configuration, or get/set/unset one key
     Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+        /// Same as `qp config set KEY VALUE...`
         #[arg(long, value_name = "KEY")]
         set: Option<String>,
-        #[arg(value_name = "VALUE", num_args = 0..)]
+        #[arg(value_name = "VALUE", num_args = 0.., requires = "set", allow_hyphen_values = true)]
         value: Vec<String>,
+        /// Use the global config (~/.config/qp/config.toml)
+        #[arg(long, global = true, conflicts_with = "local")]
+        global: bool,
+        /// Use the project config (.qp/config.toml); default for set/unset inside a project
+        #[arg(long, global = true)]
+        local: bool,
+        /// Show every effective value with the file and line it came from
+        #[arg(long, conflicts_with_all = ["set", "global", "local"])]
+        explain: bool,
+    },
+    /// Rename plan directories to the configured (or given) naming scheme
+    MigrateLayout {
+        /// Naming scheme: uuid, date-slug or seq-slug (default: [plans] naming from config)
+        #[arg(long, value_name = "NAMING")]
+        naming: Option<PlanDirNaming>,
     },
     /// Initialize .qp directory and walk through agent, skills, and plugins config
     Init {
         /// Skip interactive wizard; create .qp with default config only
         #[arg(long)]
         no_interactive: bool,
+        /// Wizard answers: agent profile (cursor, claude, aider or custom)
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+        /// Agent command (required with --profile custom)
+        #[arg(long, value_name = "CMD")]
+        agent_command: Option<String>,
+        /// Extra agent args, space-separated (e.g. --agent-args="-p --verbose")
+        #[arg(long, value_name = "ARGS", allow_hyphen_values = true)]
+        agent_args: Option<String>,
+        /// Optimization steps, comma-separated
+        #[arg(long, value_name = "STEPS", value_delimiter = ',')]
+        steps: Option<Vec<String>>,
+        /// Built-in plugin steps to add, comma-separated (or none)
+        #[arg(long, value_name = "STEPS", value_delimiter = ',')]
+        plugins: Option<Vec<String>>,
+        /// TOML file with wizard answers (profile, command, agent_args, steps, plugins); flags win
+        #[arg(long, value_name = "FILE")]
+        answers: Option<PathBuf>,
+        /// Upgrade an existing .qp to the current layout: migrate config and plan
+        /// frontmatter, refresh plan-format.md. Shows a diff and asks before writing
+        #[arg(long, conflicts_with_all = ["no_interactive", "profile", "agent_command", "agent_args", "steps", "plugins", "answers"])]
+        upgrade: bool,
+        /// Apply the upgrade without asking
+        #[arg(long, requires = "upgrade")]
+        yes: bool,
     },
 }
 
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print a value (merged config unless --global/--local)
+    Get {
+        #[arg(value_name = "KEY")]
+        key: String,
+    },
+    /// Set a dotted key, e.g. `agent.command`, `optimization.steps`, `review_agents.holes.prompt`
+    Set {
+        #[arg(value_name = "KEY")]
+        key: String,
+        /// Value; lists take several words, a comma-separated word or a TOML array
+        #[arg(value_name = "VALUE", num_args = 1.., allow_hyphen_values = true)]
+        value: Vec<String>,
+    },
+    /// Remove a key from the config file
+    Unset {
+        #[arg(value_name = "KEY")]
+        key: String,
+    },
+    /// Check the config: unknown keys, steps without a review agent, empty prompts,
+    /// commands missing from PATH. Exits non-zero on errors
+    Validate,
+    /// Print a JSON Schema for config.toml (for editor completion)
+    Schema,
+}
+
+#[derive(Subcommand)]
+pub enum PromptsAction {
+    /// Write the built-in prompts to .qp/prompts/<step>.md for customization
+    Export {
+        /// Output directory (default: .qp/prompts)
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+        /// Overwrite files that already exist
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PluginAction {
+    /// List installed and built-in packs with their steps
+    List,
+    /// Install a pack from a directory, a .toml file or a git URL
+    Add {
+        #[arg(value_name = "SOURCE")]
+        source: String,
+        /// Replace an installed pack with the same name
+        #[arg(long)]
+        force: bool,
+    },
+    /// Disable a pack's steps and delete the installed pack
+    Remove {
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+    /// Add a pack's steps to review_agents and optimization.steps
+    Enable {
+        #[arg(value_name = "NAME")]
+        name: String,
+        /// Enable only this step of the pack
+        #[arg(long, value_name = "STEP")]
+        step: Option<String>,
+    },
+    /// Remove a pack's steps from review_agents and optimization.steps
+    Disable {
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TrashAction {
+    /// List plans in the trash
+    List,
+    /// Move a plan from the trash back to .qp/plans
+    Restore {
+        #[arg(value_name = "PLAN")]
+        plan: String,
+    },
+    /// Permanently delete everything in the trash
+    Empty {
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+fn parse_assignment(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.trim().to_string(), v.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got \"{}\"", s))
+}
+
 pub fn run() -> Result<()> {
     let cli = Cli::parse();
     let cwd = std::env::current_dir().context("current dir")?;
-    let qp_root = find_qp_root(&cwd);
+    let qp_root = resolve_qp_root(&cwd)?;
+    config::set_cli_overrides(cli.config.clone())?;
+
+    let fmt = cli.format;
 
     match &cli.cmd {
-        None => cmd_list(qp_root.as_deref())?,
-        Some(Commands::List) => cmd_list(qp_root.as_deref())?,
-        Some(Commands::New { name }) => cmd_new(qp_root.as_deref(), name.as_deref())?,
-        Some(Commands::Show { plan }) => cmd_show(qp_root.as_deref(), plan)?,
-        Some(Commands::Edit { plan }) => cmd_edit(qp_root.as_deref(), plan)?,
-        Some(Commands::Approve { plan }) => cmd_approve(qp_root.as_deref(), plan)?,
-        Some(Commands::Delete { plan, yes }) => cmd_delete(qp_root.as_deref(), plan, *yes)?,
-        Some(Commands::Optimize { plan, step, force }) => {
-            cmd_optimize(qp_root.as_deref(), plan, step.as_deref(), *force)?
+        None => cmd_list(qp_root.as_deref(), false, &PlanFilter::default(), PlanSort::default(), fmt)?,
+        Some(Commands::List { archived, tag, quarter, owner, state, sort }) => {
+            let filter = PlanFilter {
+                tag: tag.clone(),
+                quarter: *quarter,
+                owner: owner.clone(),
+                state: *state,
+            };
+            cmd_list(qp_root.as_deref(), *archived, &filter, *sort, fmt)?
+        }
+        Some(Commands::Set { plan, fields }) => cmd_set(qp_root.as_deref(), plan, fields, fmt)?,
+        Some(Commands::New { name }) => cmd_new(qp_root.as_deref(), name.as_deref(), fmt)?,
+        Some(Commands::Show { plan, summary }) => cmd_show(qp_root.as_deref(), plan, *summary, fmt)?,
+        Some(Commands::Edit { plan }) => cmd_edit(qp_root.as_deref(), plan, fmt)?,
+        Some(Commands::Approve { plan }) => cmd_approve(qp_root.as_deref(), plan, fmt)?,
+        Some(Commands::Start { plan, force }) => cmd_start(qp_root.as_deref(), plan, *force, fmt)?,
+        Some(Commands::Graph { plan }) => cmd_graph(qp_root.as_deref(), plan.as_deref(), fmt)?,
+        Some(Commands::Rename { plan, title }) => cmd_rename(qp_root.as_deref(), plan, &title.join(" "), fmt)?,
+        Some(Commands::Clone { plan, title }) => cmd_clone(qp_root.as_deref(), plan, title.as_deref(), fmt)?,
+        Some(Commands::Import { file, from, title, normalize }) => {
+            cmd_import(qp_root.as_deref(), file, *from, title.as_deref(), *normalize, fmt)?
+        }
+        Some(Commands::Delete { plan, yes }) => cmd_delete(qp_root.as_deref(), plan, *yes, fmt)?,
+        Some(Commands::Archive { plan }) => cmd_archive(qp_root.as_deref(), plan, fmt)?,
+        Some(Commands::Unarchive { plan }) => cmd_unarchive(qp_root.as_deref(), plan, fmt)?,
+        Some(Commands::Trash { action }) => cmd_trash(qp_root.as_deref(), action, fmt)?,
+        Some(Commands::Optimize { plan, step, force, profile }) => {
+            cmd_optimize(qp_root.as_deref(), plan, step.as_deref(), *force, profile.as_deref(), fmt)?
+        }
+        Some(Commands::Review { plan }) => cmd_review(qp_root.as_deref(), plan, fmt)?,
+        Some(Commands::Status) => cmd_status(qp_root.as_deref(), fmt)?,
+        Some(Commands::Stats { since }) => cmd_stats(qp_root.as_deref(), *since, fmt)?,
+        Some(Commands::Quarter { quarter, as_of, include_archived }) => {
+            cmd_quarter(qp_root.as_deref(), *quarter, *as_of, *include_archived, fmt)?
+        }
+        Some(Commands::Schedule { plan, quarter }) => {
+            cmd_schedule(qp_root.as_deref(), plan.as_deref(), *quarter, fmt)?
+        }
+        Some(Commands::Export { plan, to, output, apply, base_url, repo }) => cmd_export(
+            qp_root.as_deref(),
+            plan,
+            *to,
+            output.as_deref(),
+            *apply,
+            base_url.as_deref(),
+            repo.as_deref(),
+            fmt,
+        )?,
+        Some(Commands::Render { plan, html: _, all, output }) => {
+            cmd_render(qp_root.as_deref(), plan.as_deref(), *all, output.as_deref(), fmt)?
+        }
+        Some(Commands::Sync { plan, base_url, force }) => {
+            cmd_sync(qp_root.as_deref(), plan, base_url.as_deref(), *force, fmt)?
+        }
+        Some(Commands::History { plan }) => cmd_history(qp_root.as_deref(), plan, fmt)?,
+        Some(Commands::Config { action, set, value, global, local, explain }) => {
+            let scope = match (*global, *local) {
+                (true, _) => Some(ConfigScope::Global),
+                (_, true) => Some(ConfigScope::Local),
+                _ => None,
+            };
+            if *explain {
+                cmd_config_explain(qp_root.as_deref(), fmt)?
+            } else {
+                cmd_config(qp_root.as_deref(), action.as_ref(), set, value, scope, fmt)?
+            }
+        }
+        Some(Commands::Prompts { action }) => cmd_prompts(qp_root.as_deref(), action, fmt)?,
+        Some(Commands::Plugin { action, global, local }) => {
+            let scope = match (*global, *local) {
+                (true, _) => Some(ConfigScope::Global),
+                (_, true) => Some(ConfigScope::Local),
+                _ => None,
+            };
+            cmd_plugin(qp_root.as_deref(), action, scope, fmt)?
+        }
+        Some(Commands::MigrateLayout { naming }) => cmd_migrate_layout(qp_root.as_deref(), *naming, fmt)?,
+        Some(Commands::Init { upgrade: true, yes, .. }) => cmd_init_upgrade(qp_root.as_deref(), *yes, fmt)?,
+        Some(Commands::Init { no_interactive, profile, agent_command, agent_args, steps, plugins, answers, .. }) => {
+            let flags = crate::init_wizard::WizardAnswers {
+                profile: profile.clone(),
+                command: agent_command.clone(),
+                agent_args: agent_args.as_ref().map(|a| a.split_whitespace().map(String::from).collect()),
+                steps: steps.clone(),
+                plugins: plugins.clone(),
+            };
+            let answers = match answers {
+                Some(path) => flags.or(crate::init_wizard::WizardAnswers::load(path)?),
+                None => flags,
+            };
+            cmd_init(&cwd, *no_interactive, &answers, fmt)?
         }
-        Some(Commands::Review { plan }) => cmd_review(qp_root.as_deref(), plan)?,
-        Some(Commands::Status) => cmd_status(qp_root.as_deref())?,
-        Some(Commands::Stats) => cmd_stats(qp_root.as_deref())?,
-        Some(Commands::History { plan }) => cmd_history(qp_root.as_deref(), plan)?,
-        Some(Commands::Config { set, value }) => cmd_config(qp_root.as_deref(), set, value)?,
-        Some(Commands::Init { no_interactive }) => cmd_init(&cwd, *no_interactive)?,
     }
     Ok(())
 }
@@ -117,21 +526,81 @@ pub fn run() -> Result<()> {
 fn require_qp_root(qp_root: Option<&std::path::Path>) -> Result<PathBuf> {
     match qp_root {
         Some(p) => Ok(p.to_path_buf()),
-        None => {
-            eprintln!("{} No .qp directory found. Run from a project with .qp or run `qp init` first.", "error:".red());
-            std::process::exit(1);
-        }
+        None => Err(QpError::NoQpRoot.into()),
     }
 }
 
-fn cmd_list(qp_root: Option<&std::path::Path>) -> Result<()> {
+fn cmd_list(
+    qp_root: Option<&std::path::Path>,
+    archived: bool,
+    filter: &PlanFilter,
+    sort: PlanSort,
+    format: OutputFormat,
+) -> Result<()> {
     let root = require_qp_root(qp_root)?;
-    let plans = plan::list_plans(&root)?;
+    let location = if archived { PlanLocation::Archived } else { PlanLocation::Active };
+    let all = plan::load_plans_in(&root, location)?;
+    let total = all.len();
+    let config = load_config(Some(&root))?;
+    let mut estimates = std::collections::HashMap::new();
+    let mut plans = vec![];
+    for p in all.into_iter().filter(|p| filter.matches(&p.meta)) {
+        let totals = tickets::estimate_totals(&tickets::parse_tickets(&p.body), &config.capacity);
+        if totals.estimated > 0 {
+            estimates.insert(p.meta.id.clone(), totals);
+        }
+        plans.push(p.meta);
+    }
+    plan::sort_plans(&mut plans, sort);
+    let entries: Vec<output::PlanEntry> = plans
+        .iter()
+        .map(|m| output::PlanEntry { meta: m.clone(), estimate: estimates.get(&m.id).cloned() })
+        .collect();
+    if output::emit(format, &entries)? {
+        return Ok(());
+    }
     if plans.is_empty() {
-        println!("No plans. Create one with: qp new [name]");
+        if total > 0 {
+            println!("No plans match the given filters.");
+        } else if archived {
+            println!("No archived plans.");
+        } else {
+            println!("No plans. Create one with: qp new [name]");
+        }
         return Ok(());
     }
-    println!("{}", "Plans:".bold());
+    let labels = estimates
+        .iter()
+        .map(|(id, t)| (id.clone(), format!("~{}h", (t.hours * 10.0).round() / 10.0)))
+        .collect();
+    let heading = if archived { "Archived plans:" } else { "Plans:" };
+    println!("{}", heading.bold());
+    print_plan_lines(&plans, &labels);
+    Ok(())
+}
+
+/// Compact metadata suffix for list output, e.g. "[high] 2026-Q4 @alice #backend".
+fn plan_meta_summary(m: &plan::PlanMeta) -> String {
+    let mut parts = vec![];
+    if let Some(p) = m.priority {
+        parts.push(format!("[{}]", p));
+    }
+    if let Some(q) = m.quarter {
+        parts.push(q.to_string());
+    }
+    if let Some(d) = m.due_date {
+        parts.push(format!("due {}", d));
+    }
+    if let Some(t) = &m.team {
+        parts.push(format!("team:{}", t));
+    }
+    parts.extend(m.owners.iter().map(|o| format!("@{}", o)));
+    parts.extend(m.tags.iter().map(|t| format!("#{}", t)));
+    parts.join(" ")
+}
+
+/// One line per plan; `estimates` maps plan id to an estimate label shown after the title.
+fn print_plan_lines(plans: &[plan::PlanMeta], estimates: &std::collections::HashMap<String, String>) {
     for m in plans {
         let state_color = match m.state {
             PlanState::Draft => "yellow",
@@ -150,7 +619,45 @@ fn cmd_list(qp_root: Option<&std::path::Path>) -> Result<()> {
             "magenta" => state_str.magenta(),
             _ => state_str.normal(),
         };
-        println!("  {}  {}  {}", m.id, state_display, m.title);
+        let mut summary = plan_meta_summary(m);
+        if let Some(e) = estimates.get(&m.id) {
+            summary = if summary.is_empty() { e.clone() } else { format!("{} {}", e, summary) };
+        }
+        if summary.is_empty() {
+            println!("  {}  {}  {}", m.id, state_display, m.title);
+        } else {
+            println!("  {}  {}  {}  {}", m.id, state_display, m.title, summary.dimmed());
+        }
+    }
+}
+
+/// Print the result of a command that changed one plan: JSON/YAML, or nothing for text.
+fn emit_change(format: OutputFormat, action: &'static str, plan: &plan::Plan, path: Option<&std::path::Path>) -> Result<bool> {
+    output::emit(
+        format,
+        &output::PlanChange {
+            action,
+            plan: plan.meta.clone(),
+            path: path.map(|p| p.display().to_string()),
+        },
+    )
+}
+
+fn cmd_set(
+    qp_root: Option<&std::path::Path>,
+    plan_ref: &str,
+    fields: &[(String, String)],
+    format: OutputFormat,
+) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let plan = plan::set_plan_fields(&root, plan_ref, fields)?;
+    if emit_change(format, "set", &plan, None)? {
+        return Ok(());
+    }
+    println!("Updated: {} ({})", plan.meta.title, plan.meta.id);
+    let summary = plan_meta_summary(&plan.meta);
+    if !summary.is_empty() {
+        println!("  {}", summary);
     }
     Ok(())
 }
@@ -159,32 +666,74 @@ fn cmd_list(qp_root: Option<&std::path::Path>) -> Result<()> {
 /// When the user is ready, they ask the agent to write the plan. Combined with plan_format_instructions() so the agent knows path and structure.
 const PLAN_MODE_INTRO: &str = "You are in plan mode. Ask the user short questions to help them articulate goals, scope, and key outcomes. Do not output a full plan structure, frontmatter, or section outline yet. Only help them get their ideas out. When they are ready, they will ask you to write the plan; then output the full plan using the path and format below.";
 
-fn cmd_new(qp_root: Option<&std::path::Path>, name: Option<&str>) -> Result<()> {
+fn cmd_new(qp_root: Option<&std::path::Path>, name: Option<&str>, format: OutputFormat) -> Result<()> {
     let root = require_qp_root(qp_root)?;
     let config = load_config(Some(&root))?;
-    let plan = plan::create_plan(&root, name)?;
+    let plan = plan::create_plan(&root, name, config.plans.naming)?;
     let path = plan::plan_md_path(&root, &plan.meta.id);
     let instructions = plan::plan_format_instructions(&path, &plan.meta.id, &plan.meta.title);
     let prompt = format!("{}\n\n{}", PLAN_MODE_INTRO, instructions);
-    println!("Created plan: {} ({})", plan.meta.title, plan.meta.id);
-    println!("Spawning agent for editing: {} {}", config.agent.command, config.agent.args.join(" "));
-    println!("Plan file: {}", path.display());
-    let mut child = crate::agent::run_agent_interactive(
-        &config.agent.command,
-        &config.agent.args,
-        Some(&prompt),
-    )?;
+    output::note(format, &format!("Created plan: {} ({})", plan.meta.title, plan.meta.id));
+    let agent = config.main_agent()?;
+    output::note(format, &format!("Spawning agent for editing: {}", agent));
+    output::note(format, &format!("Plan file: {}", path.display()));
+    let mut child = crate::agent::run_agent_interactive(&agent, Some(&prompt))?;
     let _ = child.wait();
+    let plan = plan::get_plan(&root, &plan.meta.id)?;
+    if emit_change(format, "new", &plan, Some(&path))? {
+        return Ok(());
+    }
     println!("Save the full plan to: {}", path.display());
     println!("Then run `qp approve {}` and `qp optimize {}` for analysis.", plan.meta.id, plan.meta.id);
     Ok(())
 }
 
-fn cmd_show(qp_root: Option<&std::path::Path>, plan_ref: &str) -> Result<()> {
+fn cmd_show(qp_root: Option<&std::path::Path>, plan_ref: &str, summary: bool, format: OutputFormat) -> Result<()> {
     let root = require_qp_root(qp_root)?;
     let plan = plan::get_plan(&root, plan_ref)?;
-    let out = plan::serialize_plan(&plan)?;
-    print!("{}", out);
+    if !summary {
+        let doc = output::PlanDocument { meta: plan.meta.clone(), body: plan.body.clone() };
+        if output::emit(format, &doc)? {
+            return Ok(());
+        }
+        let out = plan::serialize_plan(&plan)?;
+        print!("{}", out);
+        return Ok(());
+    }
+    let config = load_config(Some(&root))?;
+    let tickets = tickets::parse_tickets(&plan.body);
+    let doc = output::PlanSummary {
+        meta: plan.meta.clone(),
+        tickets: tickets.iter().map(|t| output::TicketSummary::new(t, &config.capacity)).collect(),
+        estimate: tickets::estimate_totals(&tickets, &config.capacity),
+    };
+    if output::emit(format, &doc)? {
+        return Ok(());
+    }
+    let m = &plan.meta;
+    println!("{}  {}", m.title.bold(), m.state);
+    println!("  id: {}", m.id);
+    let meta = plan_meta_summary(m);
+    if !meta.is_empty() {
+        println!("  {}", meta.dimmed());
+    }
+    if tickets.is_empty() {
+        println!("\nNo tickets.");
+        return Ok(());
+    }
+    println!("\n{}", "Tickets:".bold());
+    let width = tickets.iter().map(|t| t.title.chars().count()).max().unwrap_or(0);
+    for t in &tickets {
+        let estimate = match (t.estimate(), t.estimate_hours(&config.capacity)) {
+            (Some(e), _) => e.to_string(),
+            (None, Some(h)) => format!("{}h (steps)", h),
+            (None, None) => "-".to_string(),
+        };
+        let steps = if t.steps.is_empty() { String::new() } else { format!("{} steps", t.steps.len()) };
+        let line = format!("  {:<4} {:<width$}  {:<12} {}", t.id, t.title, estimate, steps, width = width);
+        println!("{}", line.trim_end());
+    }
+    println!("\nEstimate: {}", doc.estimate);
     Ok(())
 }
 
@@ -196,25 +745,25 @@ fn use_plan_mode_for_edit(plan: &plan::Plan) -> bool {
     !plan.body.contains("## Overview") || plan.body.len() < 200
 }
 
-fn cmd_edit(qp_root: Option<&std::path::Path>, plan_ref: &str) -> Result<()> {
+fn cmd_edit(qp_root: Option<&std::path::Path>, plan_ref: &str, format: OutputFormat) -> Result<()> {
     let root = require_qp_root(qp_root)?;
     let config = load_config(Some(&root))?;
     let plan = plan::get_plan(&root, plan_ref)?;
     let path = plan::plan_md_path(&root, &plan.meta.id);
     let instructions = plan::plan_format_instructions(&path, &plan.meta.id, &plan.meta.title);
-    println!("Spawning agent to edit: {} {}", config.agent.command, config.agent.args.join(" "));
-    println!("Plan file: {}", path.display());
+    let agent = config.main_agent()?;
+    output::note(format, &format!("Spawning agent to edit: {}", agent));
+    output::note(format, &format!("Plan file: {}", path.display()));
     let prompt = if use_plan_mode_for_edit(&plan) {
         format!("{}\n\n{}", PLAN_MODE_INTRO, instructions)
     } else {
         format!("Edit this plan. Preserve id and title. Write changes to: {}\n\n{}", path.display(), instructions)
     };
-    let mut child = crate::agent::run_agent_interactive(
-        &config.agent.command,
-        &config.agent.args,
-        Some(&prompt),
-    )?;
+    let mut child = crate::agent::run_agent_interactive(&agent, Some(&prompt))?;
     let _ = child.wait();
+    if emit_change(format, "edit", &plan::get_plan(&root, &plan.meta.id)?, Some(&path))? {
+        return Ok(());
+    }
     println!("Save the full plan to: {}", path.display());
     if plan.meta.state == PlanState::Draft {
         println!("Then run `qp approve {}` and `qp optimize {}` for analysis.", plan.meta.id, plan.meta.id);
@@ -222,22 +771,236 @@ fn cmd_edit(qp_root: Option<&std::path::Path>, plan_ref: &str) -> Result<()> {
     Ok(())
 }
 
-fn cmd_approve(qp_root: Option<&std::path::Path>, plan_ref: &str) -> Result<()> {
+fn cmd_approve(qp_root: Option<&std::path::Path>, plan_ref: &str, format: OutputFormat) -> Result<()> {
     let root = require_qp_root(qp_root)?;
     let plan = plan::approve_plan(&root, plan_ref)?;
+    if emit_change(format, "approve", &plan, None)? {
+        return Ok(());
+    }
     println!("Approved: {} ({})", plan.meta.title, plan.meta.id);
     Ok(())
 }
 
-fn cmd_delete(qp_root: Option<&std::path::Path>, plan_ref: &str, yes: bool) -> Result<()> {
+fn cmd_start(qp_root: Option<&std::path::Path>, plan_ref: &str, force: bool, format: OutputFormat) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let plan = plan::get_plan(&root, plan_ref)?;
+    let (blocked_by, unresolved) = graph::upstream_blockers(&root, &plan.meta.id)?;
+    for reference in &unresolved {
+        eprintln!("{} {}: unknown dependency \"{}\"", "warning:".yellow(), plan.meta.title, reference);
+    }
+    for up in &blocked_by {
+        eprintln!(
+            "{} depends on \"{}\" ({}), which is {}",
+            "warning:".yellow(),
+            up.title,
+            up.id,
+            up.state
+        );
+    }
+    let plan = plan::start_plan(&root, plan_ref, force)?;
+    if emit_change(format, "start", &plan, None)? {
+        return Ok(());
+    }
+    println!("Started: {} ({})", plan.meta.title, plan.meta.id);
+    Ok(())
+}
+
+fn cmd_graph(qp_root: Option<&std::path::Path>, plan_ref: Option<&str>, format: OutputFormat) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let (name, g) = match plan_ref {
+        Some(p) => {
+            let plan = plan::get_plan(&root, p)?;
+            (plan.meta.title.clone(), graph::plan_ticket_graph(&plan))
+        }
+        None => ("plans".to_string(), graph::load_plan_graph(&root)?),
+    };
+    for (node, reference) in &g.unresolved {
+        let label = g.nodes.iter().find(|n| &n.id == node).map(|n| n.label.as_str()).unwrap_or(node);
+        eprintln!("{} {}: unknown dependency \"{}\"", "warning:".yellow(), label, reference);
+    }
+    if let Some(cycle) = g.find_cycle() {
+        let labels: Vec<&str> = cycle
+            .iter()
+            .map(|id| g.nodes.iter().find(|n| &n.id == id).map(|n| n.label.as_str()).unwrap_or(id))
+            .collect();
+        eprintln!("{} dependency cycle: {}", "warning:".yellow(), labels.join(" -> "));
+    }
+    match format {
+        OutputFormat::Text | OutputFormat::Dot => print!("{}", g.to_dot(&name)),
+        OutputFormat::Mermaid => print!("{}", g.to_mermaid()),
+        _ => {
+            let edge = |(from, to): &(String, String)| output::GraphEdge { from: from.clone(), to: to.clone() };
+            let doc = output::Graph {
+                name,
+                nodes: g.nodes.clone(),
+                edges: g.edges.iter().map(edge).collect(),
+                unresolved: g.unresolved.iter().map(edge).collect(),
+                cycle: g.find_cycle(),
+            };
+            print!("{}", output::to_string(format, &doc)?);
+        }
+    }
+    Ok(())
+}
+
+fn cmd_rename(qp_root: Option<&std::path::Path>, plan_ref: &str, title: &str, format: OutputFormat) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let config = load_config(Some(&root))?;
+    let old = plan::get_plan(&root, plan_ref)?;
+    let plan = plan::rename_plan(&root, plan_ref, title, config.plans.naming)?;
+    if emit_change(format, "rename", &plan, Some(&plan::plan_md_path(&root, &plan.meta.id)))? {
+        return Ok(());
+    }
+    println!("Renamed: {} -> {} ({})", old.meta.title, plan.meta.title, plan.meta.id);
+    println!("Plan file: {}", plan::plan_md_path(&root, &plan.meta.id).display());
+    Ok(())
+}
+
+fn cmd_clone(qp_root: Option<&std::path::Path>, plan_ref: &str, title: Option<&str>, format: OutputFormat) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let config = load_config(Some(&root))?;
+    let plan = plan::clone_plan(&root, plan_ref, title, config.plans.naming)?;
+    if emit_change(format, "clone", &plan, Some(&plan::plan_md_path(&root, &plan.meta.id)))? {
+        return Ok(());
+    }
+    println!("Cloned into draft: {} ({})", plan.meta.title, plan.meta.id);
+    println!("Plan file: {}", plan::plan_md_path(&root, &plan.meta.id).display());
+    Ok(())
+}
+
+fn cmd_import(
+    qp_root: Option<&std::path::Path>,
+    file: &str,
+    from: ImportFormat,
+    title: Option<&str>,
+    normalize: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let config = load_config(Some(&root))?;
+    let (name, content) = if file == "-" {
+        let mut s = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut s).context("read stdin")?;
+        (None, s)
+    } else {
+        let s = std::fs::read_to_string(file).with_context(|| format!("read {}", file))?;
+        (Some(file), s)
+    };
+    let imported = import::convert(from, name, &content)?;
+    let stem = name.and_then(|n| std::path::Path::new(n).file_stem()).map(|s| s.to_string_lossy().to_string());
+    let title = title
+        .map(str::to_string)
+        .or(imported.title.clone())
+        .or(stem)
+        .unwrap_or_else(|| "Imported Plan".to_string());
+    let mut plan = plan::create_plan_with_body(&root, Some(&title), &imported.body, config.plans.naming)?;
+    let path = plan::plan_md_path(&root, &plan.meta.id);
+    if !imported.tags.is_empty() {
+        plan.meta.tags = imported.tags.clone();
+        plan::save_plan(&root, &plan)?;
+    }
+    if normalize {
+        output::note(format, &format!("Normalizing with {}", config.main_agent()?));
+        let prompt = import::normalize_prompt(&path, &plan.meta.id, &plan.meta.title);
+        let body = import::normalize(&config, &prompt, &plan::serialize_plan(&plan)?).with_context(|| {
+            format!("--normalize failed; the heuristic import was kept as {} ({})", plan.meta.id, path.display())
+        })?;
+        plan = plan::update_plan_body(&root, &plan.meta.id, &body, None)?;
+    }
+    if emit_change(format, "import", &plan, Some(&path))? {
+        return Ok(());
+    }
+    println!("Imported plan: {} ({})", plan.meta.title, plan.meta.id);
+    if !imported.unmapped.is_empty() && !normalize {
+        println!(
+            "Kept under Implementation Notes (no matching section): {}",
+            imported.unmapped.join(", ")
+        );
+    }
+    println!("Plan file: {}", path.display());
+    if !normalize {
+        println!("Review the mapping, or re-import with --normalize to have the agent rewrite it.");
+    }
+    Ok(())
+}
+
+fn cmd_delete(qp_root: Option<&std::path::Path>, plan_ref: &str, yes: bool, format: OutputFormat) -> Result<()> {
     let root = require_qp_root(qp_root)?;
     let plan = plan::get_plan(&root, plan_ref)?;
     if !yes {
-        eprintln!("Delete plan \"{}\" ({})? Use --yes to confirm.", plan.meta.title, plan.meta.id);
-        std::process::exit(1);
+        let question = format!("Delete plan \"{}\" ({})?", plan.meta.title, plan.meta.id);
+        return Err(QpError::ConfirmationRequired(question).into());
+    }
+    let plan = plan::delete_plan(&root, plan_ref)?;
+    if emit_change(format, "delete", &plan, None)? {
+        return Ok(());
+    }
+    println!("Moved to trash: {}", plan.meta.title);
+    println!("Restore with `qp trash restore {}`.", plan.meta.id);
+    Ok(())
+}
+
+fn cmd_archive(qp_root: Option<&std::path::Path>, plan_ref: &str, format: OutputFormat) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let plan = plan::archive_plan(&root, plan_ref)?;
+    if emit_change(format, "archive", &plan, None)? {
+        return Ok(());
+    }
+    println!("Archived: {} ({})", plan.meta.title, plan.meta.id);
+    Ok(())
+}
+
+fn cmd_unarchive(qp_root: Option<&std::path::Path>, plan_ref: &str, format: OutputFormat) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let plan = plan::unarchive_plan(&root, plan_ref)?;
+    if emit_change(format, "unarchive", &plan, None)? {
+        return Ok(());
+    }
+    println!("Unarchived: {} ({})", plan.meta.title, plan.meta.id);
+    Ok(())
+}
+
+fn cmd_trash(qp_root: Option<&std::path::Path>, action: &TrashAction, format: OutputFormat) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    match action {
+        TrashAction::List => {
+            let plans = plan::list_plans_in(&root, PlanLocation::Trashed)?;
+            let entries: Vec<output::PlanEntry> = plans
+                .iter()
+                .map(|m| output::PlanEntry { meta: m.clone(), estimate: None })
+                .collect();
+            if output::emit(format, &entries)? {
+                return Ok(());
+            }
+            if plans.is_empty() {
+                println!("Trash is empty.");
+                return Ok(());
+            }
+            println!("{}", "Trash:".bold());
+            for m in plans {
+                println!("  {}  {}  (deleted {})", m.id, m.title, m.deleted_at.as_deref().unwrap_or("unknown"));
+            }
+        }
+        TrashAction::Restore { plan } => {
+            let plan = plan::restore_plan(&root, plan)?;
+            if emit_change(format, "restore", &plan, None)? {
+                return Ok(());
+            }
+            println!("Restored: {} ({})", plan.meta.title, plan.meta.id);
+        }
+        TrashAction::Empty { yes } => {
+            let count = plan::list_plans_in(&root, PlanLocation::Trashed)?.len();
+            if !yes {
+                let question = format!("Permanently delete {} plan(s) in the trash?", count);
+                return Err(QpError::ConfirmationRequired(question).into());
+            }
+            let removed = plan::empty_trash(&root)?;
+            if output::emit(format, &serde_json::json!({ "removed": removed }))? {
+                return Ok(());
+            }
+            println!("Permanently deleted {} plan(s).", removed);
+        }
     }
-    plan::delete_plan(&root, plan_ref)?;
-    println!("Deleted: {}", plan.meta.title);
     Ok(())
 }
 
@@ -246,23 +1009,44 @@ fn cmd_optimize(
     plan_ref: &str,
     step: Option<&str>,
     force: bool,
+    profile: Option<&str>,
+    format: OutputFormat,
 ) -> Result<()> {
     let root = require_qp_root(qp_root)?;
-    let config = load_config(Some(&root))?;
-    if let Some(s) = step {
-        let _ = optimize::run_step(&root, plan_ref, s, &config)?;
-        println!("Step {} completed.", s);
+    let mut config = load_config(Some(&root))?;
+    if let Some(name) = profile {
+        config.use_profile(name)?;
+    }
+    let message = if let Some(s) = step {
+        optimize::run_step(&root, plan_ref, s, &config)?;
+        format!("Step {} completed.", s)
     } else {
         let results = optimize::run_all_steps(&root, plan_ref, &config, force)?;
-        println!("Ran {} optimization step(s).", results.len());
+        format!("Ran {} optimization step(s).", results.len())
+    };
+    let plan = plan::get_plan(&root, plan_ref)?;
+    if emit_change(format, "optimize", &plan, None)? {
+        return Ok(());
     }
+    println!("{}", message);
     Ok(())
 }
 
-fn cmd_review(qp_root: Option<&std::path::Path>, plan_ref: &str) -> Result<()> {
+fn cmd_review(qp_root: Option<&std::path::Path>, plan_ref: &str, format: OutputFormat) -> Result<()> {
     let root = require_qp_root(qp_root)?;
     let plan = plan::get_plan(&root, plan_ref)?;
     let history_dir = plan::plan_dir(&root, &plan.meta.id).join("history");
+    let doc = output::Review {
+        id: plan.meta.id.clone(),
+        title: plan.meta.title.clone(),
+        review_cycles: plan.meta.review_cycles,
+        review_steps: plan.meta.review_steps.clone(),
+        history_dir: history_dir.exists().then(|| history_dir.display().to_string()),
+        changes: crate::changelog::load_all(&root, &plan.meta.id)?,
+    };
+    if output::emit(format, &doc)? {
+        return Ok(());
+    }
     if !history_dir.exists() {
         println!("No optimization history yet.");
         return Ok(());
@@ -271,79 +1055,706 @@ fn cmd_review(qp_root: Option<&std::path::Path>, plan_ref: &str) -> Result<()> {
     for rs in &plan.meta.review_steps {
         println!("  {}: {}", rs.step, rs.status);
     }
+    if !doc.changes.is_empty() {
+        println!("\nChangelog:");
+        for c in &doc.changes {
+            let step = c.step.as_deref().map(|s| format!(" ({})", s)).unwrap_or_default();
+            println!("  v{}{}: {}", c.version, step, c);
+            for s in &c.sections_added {
+                println!("      + section {}", s);
+            }
+            for s in &c.sections_removed {
+                println!("      - section {}", s);
+            }
+            for t in &c.tickets_added {
+                println!("      + {}", t);
+            }
+            for t in &c.tickets_removed {
+                println!("      - {}", t);
+            }
+        }
+        let (first, last) = (&doc.changes[0], &doc.changes[doc.changes.len() - 1]);
+        println!(
+            "  Words: {} → {} since v{}",
+            first.words_before, last.words_after, first.from_version
+        );
+    }
     println!("\nVersion snapshots in: {}", history_dir.display());
     Ok(())
 }
 
-fn cmd_status(qp_root: Option<&std::path::Path>) -> Result<()> {
-    cmd_list(qp_root)
+fn cmd_status(qp_root: Option<&std::path::Path>, format: OutputFormat) -> Result<()> {
+    cmd_list(qp_root, false, &PlanFilter::default(), PlanSort::default(), format)
+}
+
+fn cmd_stats(qp_root: Option<&std::path::Path>, since: Option<chrono::NaiveDate>, format: OutputFormat) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let config = load_config(Some(&root))?;
+    let stats = crate::stats::compute(&root, &config, since)?;
+    if since.is_none() {
+        crate::stats::save(&root, &stats)?;
+    }
+    if output::emit(format, &stats)? {
+        return Ok(());
+    }
+    let join = |items: Vec<String>| if items.is_empty() { "-".to_string() } else { items.join(", ") };
+    match &stats.since {
+        Some(d) => println!("Plans updated since {}: {} total, {} completed, {} with optimization", d, stats.total, stats.completed, stats.with_optimization),
+        None => println!(
+            "Plans: {} total, {} completed, {} with optimization",
+            stats.total, stats.completed, stats.with_optimization
+        ),
+    }
+    if let Some(rate) = stats.success_rate {
+        println!("Success rate: {:.0}% (completed / plans past draft)", rate * 100.0);
+    }
+    println!("Optimizations per plan: {:.1}", stats.avg_optimizations);
+    if stats.plans_with_history > 0 {
+        let growth = (stats.words_final as f64 / stats.words_draft.max(1) as f64 - 1.0) * 100.0;
+        println!(
+            "Words draft → current: {} → {} ({:+.0}%, {} plan(s) with history)",
+            stats.words_draft, stats.words_final, growth, stats.plans_with_history
+        );
+    }
+    println!("\n{}", "By state".bold());
+    println!("  {}", join(stats.by_state.iter().map(|(s, n)| format!("{} {}", s, n)).collect()));
+    if !stats.avg_days_in_state.is_empty() {
+        println!("{}", "Average days in state".bold());
+        println!("  {}", join(stats.avg_days_in_state.iter().map(|(s, d)| format!("{} {:.1}", s, d)).collect()));
+    }
+    if !stats.steps.is_empty() {
+        let mut steps: Vec<_> = stats.steps.iter().collect();
+        steps.sort_by(|a, b| b.1.runs.cmp(&a.1.runs).then(a.0.cmp(b.0)));
+        println!("{}", "Steps (most run first)".bold());
+        for (name, s) in steps {
+            let failed = if s.failed > 0 { format!(", {} failed", s.failed) } else { String::new() };
+            println!("  {:<14} {} run(s){}, {:+} words", name, s.runs, failed, s.words_added);
+        }
+    }
+    if !stats.agents.is_empty() {
+        println!("{}", "Step runs by agent".bold());
+        println!("  {}", join(stats.agents.iter().map(|(a, n)| format!("{} {}", a, n)).collect()));
+    }
+    if !stats.quarters.is_empty() {
+        println!("{}", "By quarter".bold());
+        for (q, s) in &stats.quarters {
+            println!("  {}  {} plan(s), {} completed", q, s.plans, s.completed);
+        }
+    }
+    Ok(())
+}
+
+fn cmd_quarter(
+    qp_root: Option<&std::path::Path>,
+    quarter: Option<Quarter>,
+    as_of: Option<chrono::NaiveDate>,
+    include_archived: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let config = load_config(Some(&root))?;
+    let quarter = quarter.unwrap_or_else(Quarter::current);
+    let as_of = as_of.unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let report = report::quarter_report(&root, quarter, &config, as_of, include_archived)?;
+    match format {
+        OutputFormat::Text => print!("{}", report::render_table(&report)),
+        OutputFormat::Markdown => print!("{}", report::render_markdown(&report)),
+        _ => print!("{}", output::to_string(format, &report)?),
+    }
+    Ok(())
+}
+
+fn cmd_schedule(
+    qp_root: Option<&std::path::Path>,
+    plan_ref: Option<&str>,
+    quarter: Option<Quarter>,
+    format: OutputFormat,
+) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let config = load_config(Some(&root))?;
+    let s = match (plan_ref, quarter) {
+        (_, Some(q)) => schedule::schedule_quarter(&root, q, &config.capacity)?,
+        (Some(p), None) => schedule::schedule_plan(&root, p, &config.capacity)?,
+        (None, None) => anyhow::bail!("give a plan or --quarter"),
+    };
+    if !output::emit(format, &s)? {
+        print!("{}", schedule::render_schedule(&s, quarter.is_some()));
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_export(
+    qp_root: Option<&std::path::Path>,
+    plan_ref: &str,
+    target: ExportTarget,
+    output_file: Option<&std::path::Path>,
+    apply: bool,
+    base_url: Option<&str>,
+    repo: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let config = load_config(Some(&root))?;
+    if !apply {
+        let plan = plan::get_plan(&root, plan_ref)?;
+        let payload = export::render(&plan, target, &config.capacity)?;
+        match output_file {
+            Some(path) => {
+                std::fs::write(path, payload).with_context(|| format!("write {}", path.display()))?;
+                output::note(format, &format!("Wrote {}", path.display()));
+            }
+            None => print!("{}", payload),
+        }
+        return Ok(());
+    }
+    let Some(kind) = target.tracker() else {
+        anyhow::bail!("--apply supports github-issues and gitlab-issues; import the exported file into your tracker instead");
+    };
+    let client = crate::tracker::TrackerClient::new(kind, &config.tracker, base_url, repo)?;
+    let results = export::apply(&root, plan_ref, &client, &config)?;
+    if output::emit(format, &results)? {
+        return Ok(());
+    }
+    for r in &results {
+        let status = if r.created { "created" } else { "exists " };
+        let url = r.url.as_deref().map(|u| format!("  {}", u)).unwrap_or_default();
+        println!("  {} #{}  Ticket {}: {}{}", status, r.number, r.ticket, r.title, url.dimmed());
+    }
+    let created = results.iter().filter(|r| r.created).count();
+    println!("Created {} issue(s) in {} ({} already exported).", created, client.repo, results.len() - created);
+    Ok(())
+}
+
+fn cmd_render(
+    qp_root: Option<&std::path::Path>,
+    plan_ref: Option<&str>,
+    all: bool,
+    out: Option<&std::path::Path>,
+    format: OutputFormat,
+) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let config = load_config(Some(&root))?;
+    if all {
+        let dir = out.map(|p| p.to_path_buf()).unwrap_or_else(|| root.join("site"));
+        let files = crate::render::render_site(&root, &dir, &config.capacity)?;
+        let paths: Vec<String> = files.iter().map(|p| p.display().to_string()).collect();
+        if output::emit(format, &paths)? {
+            return Ok(());
+        }
+        println!("Wrote {} page(s) to {}", files.len(), dir.display());
+        println!("Open {}", dir.join("index.html").display());
+        return Ok(());
+    }
+    let plan = plan::get_plan(&root, plan_ref.unwrap_or_default())?;
+    let html = crate::render::render_plan(&root, &plan, &config.capacity, None)?;
+    match out {
+        Some(path) => {
+            std::fs::write(path, html).with_context(|| format!("write {}", path.display()))?;
+            output::note(format, &format!("Wrote {}", path.display()));
+        }
+        None => print!("{}", html),
+    }
+    Ok(())
 }
 
-fn cmd_stats(qp_root: Option<&std::path::Path>) -> Result<()> {
+fn cmd_sync(
+    qp_root: Option<&std::path::Path>,
+    plan_ref: &str,
+    base_url: Option<&str>,
+    force: bool,
+    format: OutputFormat,
+) -> Result<()> {
     let root = require_qp_root(qp_root)?;
-    let plans = plan::list_plans(&root)?;
-    let total = plans.len();
-    let completed = plans.iter().filter(|p| matches!(p.state, PlanState::Completed)).count();
-    let with_reviews = plans.iter().filter(|p| p.review_cycles > 0).count();
-    println!("Plans: {} total, {} completed, {} with optimization", total, completed, with_reviews);
+    let config = load_config(Some(&root))?;
+    let report = crate::sync::sync_plan(&root, plan_ref, &config, base_url, force)?;
+    if output::emit(format, &report)? {
+        return Ok(());
+    }
+    println!("{} ({} {})", report.plan.bold(), report.tracker, report.repo);
+    for t in &report.tickets {
+        let change = match &t.status_changed_to {
+            Some(s) => format!("-> {}", s),
+            None => "unchanged".to_string(),
+        };
+        let line = format!("  #{:<5} {:<7} Ticket {}: {}  {}", t.issue, t.remote_state, t.ticket, t.title, change);
+        if t.conflict {
+            println!("{}", line.yellow());
+        } else {
+            println!("{}", line);
+        }
+    }
+    if !report.unmapped.is_empty() {
+        println!("Not exported: ticket {}", report.unmapped.join(", "));
+    }
+    let conflicts: Vec<_> = report.conflicts().collect();
+    if !conflicts.is_empty() {
+        println!("\n{}", "Conflicts (edited in plan.md and in the tracker since the last sync):".yellow().bold());
+        for t in conflicts {
+            if t.missing_locally {
+                println!("  Ticket {} ({}): removed from plan.md, issue #{} changed", t.ticket, t.title, t.issue);
+            } else {
+                println!("  Ticket {} ({}): compare with issue #{}", t.ticket, t.title, t.issue);
+            }
+        }
+        println!("Reconcile by hand, then run `qp sync {} --force` to accept both sides.", plan_ref);
+    }
+    if report.completed {
+        println!("{}", "All tickets are done; plan marked completed.".green());
+    }
     Ok(())
 }
 
-fn cmd_history(qp_root: Option<&std::path::Path>, plan_ref: &str) -> Result<()> {
+fn cmd_history(qp_root: Option<&std::path::Path>, plan_ref: &str, format: OutputFormat) -> Result<()> {
     let root = require_qp_root(qp_root)?;
     let plan = plan::get_plan(&root, plan_ref)?;
     let history_dir = plan::plan_dir(&root, &plan.meta.id).join("history");
+    let mut versions = vec![];
+    if history_dir.exists() {
+        let mut entries: Vec<_> = std::fs::read_dir(&history_dir)?.filter_map(|e| e.ok()).collect();
+        entries.sort_by_key(|e| e.path());
+        for e in entries {
+            let name = e.file_name().to_string_lossy().into_owned();
+            if name.ends_with(".md") && !name.ends_with(".review.md") {
+                let modified_at = e
+                    .metadata()
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339());
+                versions.push(output::HistoryEntry {
+                    version: name.strip_prefix('v').and_then(|v| v.strip_suffix(".md")).and_then(|v| v.parse().ok()),
+                    file: name,
+                    modified_at,
+                });
+            }
+        }
+    }
+    versions.sort_by_key(|v| v.version);
+    let doc = output::History { id: plan.meta.id.clone(), title: plan.meta.title.clone(), versions };
+    if output::emit(format, &doc)? {
+        return Ok(());
+    }
     if !history_dir.exists() {
         println!("No version history.");
         return Ok(());
     }
-    let mut entries: Vec<_> = std::fs::read_dir(&history_dir)?.filter_map(|e| e.ok()).collect();
-    entries.sort_by_key(|e| e.path());
-    for e in entries {
-        let name = e.file_name().to_string_lossy().into_owned();
-        if name.ends_with(".md") && !name.ends_with(".review.md") {
-            println!("  {}", name);
-        }
+    for v in &doc.versions {
+        println!("  {}", v.file);
     }
     Ok(())
 }
 
 fn cmd_config(
     qp_root: Option<&std::path::Path>,
+    action: Option<&ConfigAction>,
     set: &Option<String>,
-    _value: &[String],
+    value: &[String],
+    scope: Option<ConfigScope>,
+    format: OutputFormat,
 ) -> Result<()> {
-    if set.is_some() {
-        eprintln!("qp config set is not implemented yet. Edit config manually: .qp/config.toml or ~/.config/qp/config.toml");
-        return Ok(());
+    let legacy_set = set.as_ref().map(|key| ConfigAction::Set { key: key.clone(), value: value.to_vec() });
+    match action.or(legacy_set.as_ref()) {
+        Some(ConfigAction::Get { key }) => {
+            let value = match scope {
+                Some(scope) => config::get_config_value_in(&scope.path(qp_root)?, key)?,
+                None => config::get_config_value(&load_config(qp_root)?, key)?,
+            };
+            let doc = output::ConfigValue {
+                key: key.clone(),
+                value: value.clone(),
+                scope: scope.map(|s| s.to_string()),
+                path: None,
+            };
+            if output::emit(format, &doc)? {
+                return Ok(());
+            }
+            match value {
+                Some(v) => println!("{}", v),
+                None => anyhow::bail!("{} is not set{}", key, scope.map(|s| format!(" in {} config", s)).unwrap_or_default()),
+            }
+            return Ok(());
+        }
+        Some(ConfigAction::Set { key, value }) => {
+            if value.is_empty() {
+                anyhow::bail!("missing value: qp config set {} <VALUE>", key);
+            }
+            let scope = default_scope(scope, qp_root);
+            let path = scope.path(qp_root)?;
+            let stored = config::set_config_value(&path, key, value)?;
+            let doc = output::ConfigValue {
+                key: key.clone(),
+                value: Some(stored.clone()),
+                scope: Some(scope.to_string()),
+                path: Some(path.display().to_string()),
+            };
+            if output::emit(format, &doc)? {
+                return Ok(());
+            }
+            println!("{} = {}  ({})", key, stored, path.display());
+            return Ok(());
+        }
+        Some(ConfigAction::Unset { key }) => {
+            let scope = default_scope(scope, qp_root);
+            let path = scope.path(qp_root)?;
+            let removed = config::unset_config_value(&path, key)?;
+            let doc = output::ConfigValue {
+                key: key.clone(),
+                value: None,
+                scope: Some(scope.to_string()),
+                path: Some(path.display().to_string()),
+            };
+            if output::emit(format, &doc)? {
+                return Ok(());
+            }
+            if removed {
+                println!("Removed {} from {}", key, path.display());
+            } else {
+                println!("{} was not set in {}", key, path.display());
+            }
+            return Ok(());
+        }
+        Some(ConfigAction::Validate) => return cmd_config_validate(qp_root, format),
+        Some(ConfigAction::Schema) => {
+            let schema = crate::config_schema::schema();
+            if !output::emit(format, &schema)? {
+                println!("{}", serde_json::to_string_pretty(&schema)?);
+            }
+            return Ok(());
+        }
+        None => {}
     }
     let root = qp_root;
     let config = load_config(root)?;
+    let global = crate::config::global_config_path().filter(|p| p.exists());
+    let local = root.map(|r| r.join("config.toml")).filter(|p| p.exists());
+    let doc = output::ResolvedConfig {
+        config,
+        global_path: global.map(|p| p.display().to_string()),
+        local_path: local.map(|p| p.display().to_string()),
+    };
+    if output::emit(format, &doc)? {
+        return Ok(());
+    }
+    let config = doc.config;
     println!("agent.command = \"{}\"", config.agent.command);
     println!("agent.args = {:?}", config.agent.args);
+    if let Some(p) = &config.agent.profile {
+        println!("agent.profile = \"{}\"", p);
+    }
     println!("optimization.steps = {:?}", config.optimization.steps);
     for (name, ra) in &config.review_agents {
-        println!("review_agents.{} command = \"{}\"", name, ra.command);
+        match &ra.profile {
+            Some(p) => println!("review_agents.{} profile = \"{}\"", name, p),
+            None => println!("review_agents.{} command = \"{}\"", name, ra.command),
+        }
         println!("review_agents.{} prompt = \"{}\"", name, ra.prompt);
     }
+    let mut profiles: Vec<_> = config.profiles.iter().collect();
+    profiles.sort_by_key(|(name, _)| name.as_str());
+    for (name, p) in profiles {
+        println!("profiles.{} command = \"{}\" args = {:?}", name, p.command, p.args);
+    }
     if let Some(p) = crate::config::global_config_path() {
         println!("(global config: {})", p.display());
     }
     Ok(())
 }
 
-fn cmd_init(cwd: &std::path::Path, no_interactive: bool) -> Result<()> {
-    let qp_dir = cwd.join(".qp");
-    let config_toml: Option<String> = if no_interactive || !std::io::stdin().is_terminal() {
+fn cmd_config_validate(qp_root: Option<&std::path::Path>, format: OutputFormat) -> Result<()> {
+    use crate::config_schema::Severity;
+    let issues = crate::config_schema::validate(qp_root);
+    let errors = issues.iter().filter(|i| i.severity == Severity::Error).count();
+    if !output::emit(format, &issues)? {
+        for issue in &issues {
+            let label = match issue.severity {
+                Severity::Error => "error:".red(),
+                Severity::Warning => "warning:".yellow(),
+            };
+            let mut location: Vec<String> = issue.source.iter().map(|s| s.to_string()).collect();
+            let named = |k: &String| issue.message.contains(k.as_str()) || location.iter().any(|l| l.ends_with(k.as_str()));
+            if let Some(key) = issue.key.as_ref().filter(|k| !named(k)) {
+                location.push(key.clone());
+            }
+            location.push(issue.message.clone());
+            println!("{} {}", label, location.join(": "));
+        }
+        if issues.is_empty() {
+            println!("Config is valid.");
+        }
+    }
+    if errors > 0 {
+        anyhow::bail!("config has {} error(s)", errors);
+    }
+    Ok(())
+}
+
+fn cmd_config_explain(qp_root: Option<&std::path::Path>, format: OutputFormat) -> Result<()> {
+    let entries: Vec<output::ExplainedValue> = config::explain_config(qp_root)?
+        .into_iter()
+        .map(|(key, value, source)| output::ExplainedValue { key, value, source })
+        .collect();
+    if output::emit(format, &entries)? {
+        return Ok(());
+    }
+    let width = entries.iter().map(|e| e.key.len()).max().unwrap_or(0);
+    for e in &entries {
+        let first = e.value.lines().next().unwrap_or("");
+        let value = if first.chars().count() > 60 || e.value.contains('\n') {
+            format!("{}…", first.chars().take(57).collect::<String>())
+        } else {
+            first.to_string()
+        };
+        println!("{:width$} = {}  ({})", e.key, value, e.source, width = width);
+    }
+    Ok(())
+}
+
+/// `--global`/`--local`, else the project config when inside a project.
+fn default_scope(scope: Option<ConfigScope>, qp_root: Option<&std::path::Path>) -> ConfigScope {
+    scope.unwrap_or(if qp_root.is_some() { ConfigScope::Local } else { ConfigScope::Global })
+}
+
+fn cmd_prompts(qp_root: Option<&std::path::Path>, action: &PromptsAction, format: OutputFormat) -> Result<()> {
+    match action {
+        PromptsAction::Export { dir, force } => {
+            let dir = match dir {
+                Some(d) => d.clone(),
+                None => require_qp_root(qp_root)?.join("prompts"),
+            };
+            let files = crate::prompts::export(&dir, *force)?;
+            let doc: Vec<_> = files
+                .iter()
+                .map(|(path, written)| serde_json::json!({ "path": path.display().to_string(), "written": written }))
+                .collect();
+            if output::emit(format, &doc)? {
+                return Ok(());
+            }
+            for (path, written) in &files {
+                if *written {
+                    println!("Wrote {}", path.display());
+                } else {
+                    println!("Kept {} (exists; --force to overwrite)", path.display());
+                }
+            }
+            println!("\nTo use one, set e.g.: qp config set review_agents.holes.prompt_file prompts/holes.md");
+        }
+    }
+    Ok(())
+}
+
+fn cmd_plugin(
+    qp_root: Option<&std::path::Path>,
+    action: &PluginAction,
+    scope: Option<ConfigScope>,
+    format: OutputFormat,
+) -> Result<()> {
+    use crate::plugins;
+    let print_steps = |steps: &[String]| println!("optimization.steps = {}", steps.join(", "));
+    match action {
+        PluginAction::List => {
+            let mut list = plugins::list(qp_root)?;
+            if let Some(scope) = scope {
+                list.retain(|p| p.source == plugins::PluginSource::from(scope));
+            }
+            if output::emit(format, &list)? {
+                return Ok(());
+            }
+            let config = load_config(qp_root)?;
+            let mut seen = std::collections::HashSet::new();
+            for p in &list {
+                let shadowed = !seen.insert(p.manifest.name.clone());
+                let version = p.manifest.version.as_deref().map(|v| format!(" {}", v)).unwrap_or_default();
+                let note = if shadowed { ", shadowed" } else { "" };
+                println!("{}{} ({}{})  {}", p.manifest.name.bold(), version, p.source, note, p.manifest.description);
+                for step in &p.manifest.steps {
+                    let mark = if config.optimization.steps.contains(&step.name) { "*" } else { " " };
+                    println!("  {} {:20} {}", mark, step.name, step.description);
+                }
+            }
+            println!("\n* = in optimization.steps");
+        }
+        PluginAction::Add { source, force } => {
+            let scope = default_scope(scope, qp_root);
+            let plugin = plugins::install(source, scope, qp_root, *force)?;
+            if output::emit(format, &plugin)? {
+                return Ok(());
+            }
+            let path = plugin.path.as_deref().map(|p| p.display().to_string()).unwrap_or_default();
+            println!("Installed {} ({} steps) to {}", plugin.manifest.name, plugin.manifest.steps.len(), path);
+            println!("Enable it with: qp plugin enable {}", plugin.manifest.name);
+        }
+        PluginAction::Remove { name } => {
+            let scope = match scope {
+                Some(scope) => scope,
+                None => match plugins::find(qp_root, name)?.source {
+                    plugins::PluginSource::Local => ConfigScope::Local,
+                    plugins::PluginSource::Global => ConfigScope::Global,
+                    plugins::PluginSource::Builtin => anyhow::bail!("{} is built in; use `qp plugin disable {}`", name, name),
+                },
+            };
+            let plugin = plugins::uninstall(name, scope, qp_root)?;
+            let steps = plugins::disable(&plugin, &load_config(qp_root)?, &scope.path(qp_root)?)?;
+            if output::emit(format, &plugin)? {
+                return Ok(());
+            }
+            println!("Removed {}", name);
+            print_steps(&steps);
+        }
+        PluginAction::Enable { name, step } => {
+            let plugin = plugins::find(qp_root, name)?;
+            let path = default_scope(scope, qp_root).path(qp_root)?;
+            let steps = plugins::enable(&plugin, step.as_deref(), &load_config(qp_root)?, &path)?;
+            if output::emit(format, &steps)? {
+                return Ok(());
+            }
+            println!("Enabled {} in {}", step.as_deref().unwrap_or(name), path.display());
+            print_steps(&steps);
+        }
+        PluginAction::Disable { name } => {
+            let plugin = plugins::find(qp_root, name)?;
+            let path = default_scope(scope, qp_root).path(qp_root)?;
+            let steps = plugins::disable(&plugin, &load_config(qp_root)?, &path)?;
+            if output::emit(format, &steps)? {
+                return Ok(());
+            }
+            println!("Disabled {} in {}", name, path.display());
+            print_steps(&steps);
+        }
+    }
+    Ok(())
+}
+
+fn cmd_migrate_layout(
+    qp_root: Option<&std::path::Path>,
+    naming: Option<PlanDirNaming>,
+    format: OutputFormat,
+) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let config = load_config(Some(&root))?;
+    let naming = naming.unwrap_or(config.plans.naming);
+    let moved = plan::migrate_layout(&root, naming)?;
+    let doc: Vec<output::LayoutMove> = moved
+        .iter()
+        .map(|(title, from, to)| output::LayoutMove {
+            title: title.clone(),
+            from: from.display().to_string(),
+            to: to.display().to_string(),
+        })
+        .collect();
+    if output::emit(format, &doc)? {
+        return Ok(());
+    }
+    if moved.is_empty() {
+        println!("All plans already use the {} layout.", naming);
+    } else {
+        for (title, from, to) in &moved {
+            let name = |p: &std::path::Path| p.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            println!("  {}  {} -> {}", title, name(from), name(to));
+        }
+        println!("Moved {} plan(s) to the {} layout.", moved.len(), naming);
+    }
+    if naming != config.plans.naming {
+        println!("Set `naming = \"{}\"` under [plans] in .qp/config.toml so new plans use it too.", naming);
+    }
+    Ok(())
+}
+
+fn cmd_init(
+    cwd: &std::path::Path,
+    no_interactive: bool,
+    answers: &crate::init_wizard::WizardAnswers,
+    format: OutputFormat,
+) -> Result<()> {
+    let qp_dir = crate::discovery::init_dir(cwd);
+    let interactive = !no_interactive && format.is_text() && std::io::stdin().is_terminal();
+    // Without a terminal the wizard still runs when answers were given; unanswered
+    // questions then take their defaults.
+    let config_toml: Option<String> = if !interactive && answers.is_empty() {
         None
     } else {
-        let (profile_name, command) = crate::init_wizard::choose_agent_profile()?;
-        let config = crate::init_wizard::run_wizard(&command)?;
-        let toml = crate::config::config_to_toml(&config)?;
-        println!("\n  Writing config for agent: {} ({})", profile_name, command);
+        let stdin = std::io::stdin();
+        let mut wizard = crate::init_wizard::Wizard::new(stdin.lock(), std::io::stdout(), interactive);
+        let result = wizard.run(answers)?;
+        let toml = crate::config::config_to_toml(&result.config)?;
+        if format.is_text() {
+            println!("\n  Writing config for agent: {} ({})", result.profile, result.config.agent.command);
+        }
+        if crate::agent::find_command(&result.config.agent.command).is_none() {
+            eprintln!("{} `{}` is not on PATH; install it before `qp new` or `qp optimize`", "warning:".yellow(), result.config.agent.command);
+        }
         Some(toml)
     };
     plan::init_qp(&qp_dir, config_toml.as_deref())?;
+    if output::emit(format, &serde_json::json!({ "path": qp_dir.display().to_string() }))? {
+        return Ok(());
+    }
     println!("Initialized {}", qp_dir.display());
     Ok(())
 }
+
+fn cmd_init_upgrade(qp_root: Option<&std::path::Path>, yes: bool, format: OutputFormat) -> Result<()> {
+    let root = require_qp_root(qp_root)?;
+    let upgrade = crate::upgrade::plan_upgrade(&root)?;
+    let base = root.parent().unwrap_or(&root);
+    let doc = |applied: bool| {
+        let changes: Vec<_> = upgrade
+            .changes
+            .iter()
+            .map(|c| serde_json::json!({ "path": c.path.display().to_string(), "reasons": c.reasons, "diff": c.diff(base) }))
+            .collect();
+        serde_json::json!({
+            "from_version": upgrade.from_version,
+            "to_version": upgrade.to_version,
+            "changes": changes,
+            "applied": applied,
+        })
+    };
+    if upgrade.changes.is_empty() {
+        if output::emit(format, &doc(false))? {
+            return Ok(());
+        }
+        println!("{} is up to date (layout version {}).", root.display(), upgrade.to_version);
+        return Ok(());
+    }
+    let question = format!("Apply {} change(s) to {}?", upgrade.changes.len(), root.display());
+    if !format.is_text() {
+        if !yes {
+            output::emit(format, &doc(false))?;
+            return Err(QpError::ConfirmationRequired(question).into());
+        }
+        crate::upgrade::apply(&upgrade)?;
+        output::emit(format, &doc(true))?;
+        return Ok(());
+    }
+    println!("Upgrading {} from layout version {} to {}\n", root.display(), upgrade.from_version, upgrade.to_version);
+    for change in &upgrade.changes {
+        for reason in &change.reasons {
+            println!("{} {}", "#".dimmed(), reason.dimmed());
+        }
+        for line in change.diff(base).lines() {
+            match line.chars().next() {
+                Some('@') => println!("{}", line.cyan()),
+                Some('+') => println!("{}", line.green()),
+                Some('-') => println!("{}", line.red()),
+                _ => println!("{}", line),
+            }
+        }
+        println!();
+    }
+    if !yes {
+        if !std::io::stdin().is_terminal() {
+            return Err(QpError::ConfirmationRequired(question).into());
+        }
+        print!("{} [y/N] ", question);
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("No changes written.");
+            return Ok(());
+        }
+    }
+    crate::upgrade::apply(&upgrade)?;
+    println!("Upgraded {} to layout version {}.", root.display(), upgrade.to_version);
+    Ok(())
+}
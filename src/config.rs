@@ -1,11 +1,17 @@
-//! Configuration loading: global (~/.config/qp) and local (.qp/config.toml).
-//! Local overrides global.
+//! Configuration loading: built-in defaults, then global (~/.config/qp), then local
+//! (.qp/config.toml), then the `QP_CONFIG` file, `QP_*` environment variables and `-c`
+//! flags. Later layers override earlier ones key by key; `qp config --explain` shows which
+//! layer each value came from.
 
 use anyhow::{Context, Result};
+use colored::Colorize;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
+use crate::plan::PlanDirNaming;
+use crate::prompts;
+
 const DEFAULT_AGENT_COMMAND: &str = "claude";
 const DEFAULT_OPTIMIZATION_STEPS: &[&str] = &["holes", "details", "breakdown", "deliverables"];
 
@@ -23,6 +29,53 @@ pub struct AgentConfig {
     pub command: String,
     #[serde(default)]
     pub args: Vec<String>,
+    /// Named `[profiles.<name>]` to use instead of `command` and `args`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+/// A named agent setup (`[profiles.<name>]`) that `[agent]` and review agents can
+/// reference with `profile = "<name>"`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProfileConfig {
+    #[serde(default = "default_agent_command")]
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables for the agent process.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Seconds before a one-shot run (optimize, import --normalize) is killed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    #[serde(default, skip_serializing_if = "PromptInput::is_stdin")]
+    pub input: PromptInput,
+}
+
+/// What to run for one agent call, after resolving profiles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentInvocation {
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub timeout: Option<std::time::Duration>,
+    /// How a one-shot run passes the prompt and plan.
+    pub input: PromptInput,
+    /// Profile the settings came from, if any.
+    pub profile: Option<String>,
+}
+
+impl std::fmt::Display for AgentInvocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.command)?;
+        if !self.args.is_empty() {
+            write!(f, " {}", self.args.join(" "))?;
+        }
+        if let Some(p) = &self.profile {
+            write!(f, " (profile {})", p)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -31,7 +84,65 @@ pub struct ReviewAgentConfig {
     pub command: String,
     #[serde(default)]
     pub args: Vec<String>,
+    /// Inline prompt; required unless `prompt_file` is set or the step has a built-in
+    /// prompt (see [`crate::prompts`]).
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub prompt: String,
+    /// Markdown file with the prompt, relative to the directory of the config file that
+    /// sets it (`.qp/` for the project config). Replaces `prompt` when loading.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_file: Option<String>,
+    /// Named `[profiles.<name>]` to use instead of `command` and `args`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// How the agent's output is merged into the plan.
+    #[serde(default, skip_serializing_if = "StepOutput::is_auto")]
+    pub output: StepOutput,
+    /// `## Section` the output goes into when `output = "section"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    #[serde(default, skip_serializing_if = "PromptInput::is_stdin")]
+    pub input: PromptInput,
+    /// Why `prompt_file` could not be read, when loading.
+    #[serde(skip)]
+    pub prompt_error: Option<String>,
+}
+
+/// How a one-shot agent run receives the prompt and plan.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptInput {
+    /// Written to the agent's stdin (e.g. `claude -p`).
+    #[default]
+    Stdin,
+    /// Passed as the last argument (e.g. `cursor-agent -p <prompt>`, `aider --message <prompt>`).
+    Arg,
+}
+
+impl PromptInput {
+    fn is_stdin(&self) -> bool {
+        *self == PromptInput::Stdin
+    }
+}
+
+/// Output parser for a review step.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepOutput {
+    /// A full plan (with frontmatter) replaces the body; anything else is added to
+    /// `## Review Notes`.
+    #[default]
+    Auto,
+    /// Always add the output to `## Review Notes`.
+    Notes,
+    /// Replace (or add) the `## <section>` section with the output.
+    Section,
+}
+
+impl StepOutput {
+    fn is_auto(&self) -> bool {
+        *self == StepOutput::Auto
+    }
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -40,6 +151,71 @@ pub struct OptimizationConfig {
     pub steps: Vec<String>,
 }
 
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PlansConfig {
+    /// Directory naming for new plans: "uuid" (default), "date-slug" or "seq-slug".
+    #[serde(default)]
+    pub naming: PlanDirNaming,
+}
+
+fn default_engineers() -> u32 {
+    1
+}
+
+fn default_capacity_days() -> u32 {
+    60
+}
+
+fn default_hours_per_day() -> f64 {
+    6.0
+}
+
+fn default_hours_per_point() -> f64 {
+    4.0
+}
+
+/// Team capacity used by `qp schedule`: engineers × working days per quarter.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CapacityConfig {
+    #[serde(default = "default_engineers")]
+    pub engineers: u32,
+    /// Working days per quarter.
+    #[serde(default = "default_capacity_days")]
+    pub days: u32,
+    /// Focused hours per engineer per day; converts day estimates to hours.
+    #[serde(default = "default_hours_per_day")]
+    pub hours_per_day: f64,
+    /// Converts point estimates (`3pt`) to hours.
+    #[serde(default = "default_hours_per_point")]
+    pub hours_per_point: f64,
+}
+
+impl Default for CapacityConfig {
+    fn default() -> Self {
+        Self {
+            engineers: default_engineers(),
+            days: default_capacity_days(),
+            hours_per_day: default_hours_per_day(),
+            hours_per_point: default_hours_per_point(),
+        }
+    }
+}
+
+/// Issue tracker used by `qp export --apply` and `qp sync`. Unset fields fall back to the
+/// target's defaults (e.g. `https://api.github.com` and `GITHUB_TOKEN` for GitHub).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct TrackerConfig {
+    /// API base URL; point it at a local mock server for testing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// GitHub `owner/name` or GitLab project path or id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    /// Environment variable holding the API token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ConfigFile {
     #[serde(default)]
@@ -48,6 +224,65 @@ pub struct ConfigFile {
     pub review_agents: HashMap<String, ReviewAgentConfig>,
     #[serde(default)]
     pub optimization: OptimizationConfig,
+    #[serde(default)]
+    pub plans: PlansConfig,
+    #[serde(default)]
+    pub capacity: CapacityConfig,
+    #[serde(default)]
+    pub tracker: TrackerConfig,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: HashMap<String, ProfileConfig>,
+}
+
+impl ConfigFile {
+    fn invocation(&self, profile: Option<&str>, command: &str, args: &[String], input: PromptInput) -> Result<AgentInvocation> {
+        let Some(name) = profile else {
+            return Ok(AgentInvocation {
+                command: command.to_string(),
+                args: args.to_vec(),
+                env: vec![],
+                timeout: None,
+                input,
+                profile: None,
+            });
+        };
+        let p = self.profiles.get(name).with_context(|| {
+            let mut names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            names.sort();
+            format!("unknown profile: {} (defined: {})", name, if names.is_empty() { "none".to_string() } else { names.join(", ") })
+        })?;
+        let mut env: Vec<(String, String)> = p.env.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        env.sort();
+        Ok(AgentInvocation {
+            command: p.command.clone(),
+            args: p.args.clone(),
+            env,
+            timeout: p.timeout.map(std::time::Duration::from_secs),
+            input: p.input,
+            profile: Some(name.to_string()),
+        })
+    }
+
+    /// The `[agent]` used by qp new/edit/import.
+    pub fn main_agent(&self) -> Result<AgentInvocation> {
+        self.invocation(self.agent.profile.as_deref(), &self.agent.command, &self.agent.args, PromptInput::Stdin)
+    }
+
+    /// The agent for an optimization step: its profile if set, else its command and args.
+    pub fn step_agent(&self, step: &str) -> Result<AgentInvocation> {
+        let ra = self.review_agents.get(step).with_context(|| format!("unknown step: {}", step))?;
+        self.invocation(ra.profile.as_deref(), &ra.command, &ra.args, ra.input)
+    }
+
+    /// Point `[agent]` and every review agent at one profile, e.g. for `qp optimize --profile`.
+    pub fn use_profile(&mut self, name: &str) -> Result<()> {
+        self.invocation(Some(name), "", &[], PromptInput::Stdin)?;
+        self.agent.profile = Some(name.to_string());
+        for ra in self.review_agents.values_mut() {
+            ra.profile = Some(name.to_string());
+        }
+        Ok(())
+    }
 }
 
 impl Default for ConfigFile {
@@ -56,150 +291,428 @@ impl Default for ConfigFile {
             agent: AgentConfig {
                 command: default_agent_command(),
                 args: vec![],
+                profile: None,
             },
             review_agents: default_review_agents(),
             optimization: OptimizationConfig {
                 steps: default_optimization_steps(),
             },
+            plans: PlansConfig::default(),
+            capacity: CapacityConfig::default(),
+            tracker: TrackerConfig::default(),
+            profiles: HashMap::new(),
         }
     }
 }
 
 fn default_review_agents() -> HashMap<String, ReviewAgentConfig> {
-    let mut m = HashMap::new();
-    m.insert(
-        "holes".to_string(),
-        ReviewAgentConfig {
-            command: DEFAULT_AGENT_COMMAND.to_string(),
-            args: vec![],
-            prompt: "Review this plan and identify gaps, risks, and issues. Output the complete plan with an updated Review Notes section (## Review Notes) containing these four subsections:\n\n\
-### Identified Weaknesses\n\
-List at least 5 specific weaknesses in the plan. Focus on missing security considerations, unclear requirements, underspecified behavior, missing error handling, and architectural gaps. Be concrete and actionable.\n\n\
-### Edge Cases\n\
-List at least 5 edge cases that the plan doesn't address. Think about boundary conditions, error states, concurrent access, invalid inputs, and failure scenarios.\n\n\
-### Assumptions to Validate\n\
-List at least 4 assumptions the plan makes that should be verified before implementation. These are things that could change the approach if they turn out to be false.\n\n\
-### Potential Failures\n\
-List at least 4 ways the implementation could fail in production. Consider infrastructure failures, data issues, scaling problems, and operational concerns.\n\n\
-Output the entire plan with the Review Notes section populated. Keep all other sections (Overview, Constraints, Implementation Notes, Tickets) unchanged.".to_string(),
-        },
-    );
-    m.insert(
-        "details".to_string(),
-        ReviewAgentConfig {
-            command: DEFAULT_AGENT_COMMAND.to_string(),
-            args: vec![],
-            prompt: "Expand this plan with implementation details.\n\n\
-CRITICAL: Your output must be the COMPLETE, EXPANDED plan - not a summary or description of changes.\n\
-Do NOT ask for permission. Do NOT describe what you would add. Just output the full plan.\n\n\
-Add an ## Implementation Notes section (if not present, or expand existing) with:\n\n\
-### Technology Stack\n\
-Specify exact versions and libraries. Include language version, key dependencies with versions, build tools, and testing frameworks.\n\n\
-### Data Structures\n\
-Define the core data structures with actual code. Show structs/classes, interfaces/traits, and type definitions. Include field types and documentation.\n\n\
-### Algorithms & Logic\n\
-Document key algorithms with pseudocode or actual code. Explain the approach and any important implementation details.\n\n\
-### API Design\n\
-If applicable, show endpoint signatures, request/response schemas, and error formats.\n\n\
-Your response must START with the plan's YAML frontmatter (---) and include ALL sections: Overview, Constraints, Implementation Notes, Review Notes, and Tickets.\n\
-Do NOT write meta-commentary about the plan. Output ONLY the plan content.".to_string(),
-        },
-    );
-    m.insert(
-        "breakdown".to_string(),
-        ReviewAgentConfig {
-            command: DEFAULT_AGENT_COMMAND.to_string(),
-            args: vec![],
-            prompt: "Break this plan into precise, atomic steps.\n\n\
-CRITICAL: Your output must be the COMPLETE plan with detailed steps added - not a summary or description.\n\
-Do NOT ask for permission. Do NOT describe what steps you would add. Output the full plan with steps.\n\n\
-For each ticket in the ## Tickets section, add a #### Steps subsection containing:\n\
-- Numbered steps (1, 2, 3, etc.) that are atomic and independently implementable\n\
-- Each step should have a clear action and a verification method\n\
-- Steps should be small enough to complete in under 2 hours\n\
-- Include specific commands, file names, and technical details\n\
-- End each step with 'Verify:' describing how to confirm completion\n\n\
-Example step format:\n\
-1. **Create user model file**\n\
-   - Create src/models/user.ts with User interface\n\
-   - Include id, email, name, createdAt fields\n\
-   - Verify: TypeScript compiles without errors\n\n\
-Your response must START with the plan's YAML frontmatter (---) and include ALL sections.\n\
-Output ONLY the plan content with steps added. No meta-commentary.".to_string(),
-        },
-    );
-    m.insert(
-        "deliverables".to_string(),
-        ReviewAgentConfig {
-            command: DEFAULT_AGENT_COMMAND.to_string(),
-            args: vec![],
-            prompt: "Add clear acceptance criteria for each ticket in the plan.\n\n\
-CRITICAL: Your output must be the COMPLETE plan with acceptance criteria added - not a summary or description.\n\
-Do NOT ask for permission. Do NOT describe what criteria you would add. Output the full plan with criteria.\n\n\
-For each ticket in the ## Tickets section, add these subsections:\n\n\
-#### Acceptance Criteria\n\
-Numbered groups of specific, testable requirements. Use checkbox format for each item:\n\
-1. **Category Name**\n\
-   - [ ] Specific testable requirement\n\
-   - [ ] Another testable requirement\n\n\
-#### Demo Script\n\
-Concrete commands or code showing how to verify the feature works. Include expected output.\n\n\
-#### Test Requirements\n\
-Specific tests that must pass, with checkboxes:\n\
-- [ ] Test description\n\
-- [ ] Another test\n\n\
-Focus on:\n\
-- What specific behaviors must be verified?\n\
-- What can be demonstrated to stakeholders?\n\
-- How do we know the ticket is complete?\n\n\
-Your response must START with the plan's YAML frontmatter (---) and include ALL sections.\n\
-Output ONLY the plan content with acceptance criteria added. No meta-commentary.".to_string(),
-        },
-    );
-    m
-}
-
-/// Load config: merge global (if present) with local (if present). Local wins.
+    prompts::BUILTIN
+        .iter()
+        .map(|p| {
+            let agent = ReviewAgentConfig {
+                command: DEFAULT_AGENT_COMMAND.to_string(),
+                args: vec![],
+                prompt: p.text().to_string(),
+                prompt_file: None,
+                profile: None,
+                output: StepOutput::Auto,
+                section: None,
+                input: PromptInput::Stdin,
+                prompt_error: None,
+            };
+            (p.name.to_string(), agent)
+        })
+        .collect()
+}
+
+/// Load config: built-in defaults, then global (if present), then local (if present).
+/// See [`load_config_explained`] for the merge rules. Unknown keys are reported on stderr.
 pub fn load_config(qp_root: Option<&std::path::Path>) -> Result<ConfigFile> {
-    let mut config = ConfigFile::default();
+    let layers = load_layers(qp_root)?;
+    for layer in &layers {
+        for key in &layer.unknown {
+            eprintln!("{} {}: {}", "warning:".yellow(), layer.source(key), unknown_key_message(key));
+        }
+    }
+    Ok(resolve_layers(&layers, qp_root)?.0)
+}
 
-    // Global: ~/.config/qp/config.toml
-    if let Some(config_dir) = dirs::config_dir() {
-        let global = config_dir.join("qp").join("config.toml");
-        if global.exists() {
-            let s = std::fs::read_to_string(&global)
-                .with_context(|| format!("read {}", global.display()))?;
-            let global_cfg: ConfigFile = toml::from_str(&s)
-                .with_context(|| format!("parse {}", global.display()))?;
-            merge_config(&mut config, &global_cfg);
+/// "unknown config key `x`", with the closest known key when there is one.
+pub fn unknown_key_message(key: &str) -> String {
+    match suggest_key(key) {
+        Some(known) => format!("unknown config key `{}` (did you mean `{}`?)", key, known),
+        None => format!("unknown config key `{}`", key),
+    }
+}
+
+/// Known key within a small edit distance of `key`.
+fn suggest_key(key: &str) -> Option<String> {
+    let parts: Vec<&str> = key.split('.').collect();
+    CONFIG_KEYS
+        .iter()
+        .filter_map(|(pattern, _, _)| {
+            let p: Vec<&str> = pattern.split('.').collect();
+            if p.len() != parts.len() {
+                return None;
+            }
+            let known: Vec<&str> = p.iter().zip(&parts).map(|(p, k)| if *p == "*" { *k } else { *p }).collect();
+            let known = known.join(".");
+            let distance = edit_distance(key, &known);
+            (distance <= 3).then_some((distance, known))
+        })
+        .min()
+        .map(|(_, known)| known)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = (prev + (ca != *cb) as usize).min(row[j] + 1).min(cur + 1);
+            prev = cur;
         }
     }
+    row[b.len()]
+}
 
-    // Local: .qp/config.toml (takes precedence)
-    if let Some(root) = qp_root {
-        let local = root.join("config.toml");
-        if local.exists() {
-            let s = std::fs::read_to_string(&local)
-                .with_context(|| format!("read {}", local.display()))?;
-            let local_cfg: ConfigFile = toml::from_str(&s)
-                .with_context(|| format!("parse {}", local.display()))?;
-            merge_config(&mut config, &local_cfg);
+/// How a layer combines its `review_agents` or `optimization.steps` with the layers below,
+/// set per file in a `[merge]` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeMode {
+    /// Add to what lower layers define (review agents: by name, field by field).
+    Extend,
+    /// Discard what lower layers define.
+    Replace,
+}
+
+fn default_agents_merge() -> MergeMode {
+    MergeMode::Extend
+}
+
+fn default_steps_merge() -> MergeMode {
+    MergeMode::Replace
+}
+
+/// `[merge]` table of one config file. It applies to that file only and is not part of
+/// the merged config.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct MergeRules {
+    #[serde(default = "default_agents_merge")]
+    pub review_agents: MergeMode,
+    #[serde(default = "default_steps_merge")]
+    pub steps: MergeMode,
+}
+
+impl Default for MergeRules {
+    fn default() -> Self {
+        Self { review_agents: default_agents_merge(), steps: default_steps_merge() }
+    }
+}
+
+/// Where an effective config value came from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigSource {
+    /// "default", "global", "local", "QP_CONFIG", "env" or "cli".
+    pub layer: String,
+    /// Config file, or the `$VARIABLE` / `-c key` that set the value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.path, self.line) {
+            (Some(p), Some(l)) => write!(f, "{}:{}", p, l),
+            (Some(p), None) => write!(f, "{}", p),
+            _ => write!(f, "{}", self.layer),
+        }
+    }
+}
+
+/// One config file (or the built-in defaults), ready to merge.
+pub(crate) struct ConfigLayer {
+    pub(crate) name: &'static str,
+    pub(crate) path: Option<PathBuf>,
+    table: toml::Table,
+    /// Dotted key → 1-based line in the file.
+    lines: HashMap<String, usize>,
+    rules: MergeRules,
+    /// Keys not in [`CONFIG_KEYS`]; they are ignored when merging.
+    pub(crate) unknown: Vec<String>,
+}
+
+impl ConfigLayer {
+    pub(crate) fn defaults() -> Result<Self> {
+        let table = match toml::Value::try_from(ConfigFile::default()).context("serialize default config")? {
+            toml::Value::Table(t) => t,
+            _ => unreachable!("config serializes to a table"),
+        };
+        Ok(Self { name: "default", path: None, table, lines: HashMap::new(), rules: MergeRules::default(), unknown: vec![] })
+    }
+
+    pub(crate) fn read(name: &'static str, path: PathBuf) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = std::fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+        let layer = Self::parse(name, Some(path.clone()), &text).with_context(|| format!("parse {}", path.display()))?;
+        Ok(Some(layer))
+    }
+
+    /// Parse and check one file: on top of the built-in defaults it must form a valid
+    /// config, so a file may override single fields of a built-in review agent.
+    fn parse(name: &'static str, path: Option<PathBuf>, text: &str) -> Result<Self> {
+        let mut table: toml::Table = toml::from_str(text)?;
+        let mut unknown = vec![];
+        collect_unknown(&table, "", &mut unknown);
+        let rules = match table.remove("merge") {
+            Some(v) => v.try_into().context("invalid [merge] table")?,
+            None => MergeRules::default(),
+        };
+        let mut lines = HashMap::new();
+        if let Ok(doc) = toml_edit::ImDocument::parse(text) {
+            collect_lines(doc.as_table(), "", text, &mut lines);
+        }
+        let layer = Self { name, path, table, lines, rules, unknown };
+        let defaults = Self::defaults()?;
+        let layers = [defaults, layer];
+        merge_layers(&layers)?;
+        let [_, layer] = layers;
+        Ok(layer)
+    }
+
+    /// A layer that sets one key, from an environment variable or `-c` flag (`origin`).
+    fn single(name: &'static str, origin: String, key: &str, words: &[String]) -> Result<Self> {
+        let value = parse_config_value(key_kind(key)?, words)?;
+        let mut doc = toml_edit::DocumentMut::new();
+        insert_value(doc.as_table_mut(), key, value)?;
+        let mut layer = Self::parse(name, Some(PathBuf::from(origin)), &doc.to_string())?;
+        layer.lines.clear();
+        Ok(layer)
+    }
+
+    pub(crate) fn source(&self, key: &str) -> ConfigSource {
+        // Values inside arrays of tables or multi-line arrays fall back to the nearest key with a line.
+        let mut k = key;
+        let line = loop {
+            if let Some(l) = self.lines.get(k) {
+                break Some(*l);
+            }
+            match k.rsplit_once('.') {
+                Some((parent, _)) => k = parent,
+                None => break None,
+            }
+        };
+        ConfigSource {
+            layer: self.name.to_string(),
+            path: self.path.as_ref().map(|p| p.display().to_string()),
+            line,
+        }
+    }
+}
+
+fn collect_unknown(table: &toml::Table, prefix: &str, out: &mut Vec<String>) {
+    for (k, v) in table {
+        let path = if prefix.is_empty() { k.to_string() } else { format!("{}.{}", prefix, k) };
+        match v {
+            toml::Value::Table(t) if key_kind(&path).is_err() => collect_unknown(t, &path, out),
+            _ if key_kind(&path).is_err() => out.push(path),
+            _ => {}
+        }
+    }
+}
+
+fn collect_lines(table: &dyn toml_edit::TableLike, prefix: &str, text: &str, out: &mut HashMap<String, usize>) {
+    for (k, item) in table.iter() {
+        let path = if prefix.is_empty() { k.to_string() } else { format!("{}.{}", prefix, k) };
+        if let Some(span) = table.key(k).and_then(|key| key.span()) {
+            out.insert(path.clone(), text[..span.start].matches('\n').count() + 1);
+        }
+        if let Some(t) = item.as_table_like() {
+            collect_lines(t, &path, text, out);
         }
     }
+}
 
-    Ok(config)
+/// Merge `src` into `dst`, recording the source of every leaf value.
+fn merge_table(
+    dst: &mut toml::Table,
+    src: &toml::Table,
+    prefix: &str,
+    layer: &ConfigLayer,
+    sources: &mut BTreeMap<String, ConfigSource>,
+) {
+    for (k, v) in src {
+        let path = if prefix.is_empty() { k.clone() } else { format!("{}.{}", prefix, k) };
+        if path == "review_agents" && layer.rules.review_agents == MergeMode::Replace {
+            dst.remove(k);
+            sources.retain(|key, _| !key.starts_with("review_agents."));
+        }
+        match (dst.get_mut(k), v) {
+            (Some(toml::Value::Table(d)), toml::Value::Table(s)) => merge_table(d, s, &path, layer, sources),
+            (Some(toml::Value::Array(d)), toml::Value::Array(s))
+                if path == "optimization.steps" && layer.rules.steps == MergeMode::Extend =>
+            {
+                for item in s {
+                    if !d.contains(item) {
+                        d.push(item.clone());
+                    }
+                }
+                sources.insert(path.clone(), layer.source(&path));
+            }
+            _ => {
+                dst.insert(k.clone(), v.clone());
+                sources.retain(|key, _| !key.starts_with(&format!("{}.", path)));
+                record_sources(v, &path, layer, sources);
+            }
+        }
+    }
+}
+
+fn record_sources(value: &toml::Value, path: &str, layer: &ConfigLayer, sources: &mut BTreeMap<String, ConfigSource>) {
+    match value {
+        toml::Value::Table(t) => {
+            for (k, v) in t {
+                record_sources(v, &format!("{}.{}", path, k), layer, sources);
+            }
+        }
+        _ => {
+            sources.insert(path.to_string(), layer.source(path));
+        }
+    }
+}
+
+/// Load config and, for every effective leaf value (dotted key), the layer, file and line
+/// it came from.
+///
+/// Layers apply in order: built-in defaults, global, local. Within a layer, every key
+/// that is present overrides the same key below it; keys a file does not mention are
+/// inherited. Review agents merge by name and field (`[merge] review_agents = "replace"`
+/// drops lower layers' agents); `optimization.steps` replaces the list unless the file
+/// sets `[merge] steps = "extend"`, which appends its steps.
+pub fn load_config_explained(
+    qp_root: Option<&std::path::Path>,
+) -> Result<(ConfigFile, BTreeMap<String, ConfigSource>)> {
+    resolve_layers(&load_layers(qp_root)?, qp_root)
+}
+
+/// All layers that apply here, lowest first.
+pub(crate) fn load_layers(qp_root: Option<&std::path::Path>) -> Result<Vec<ConfigLayer>> {
+    let mut layers = vec![ConfigLayer::defaults()?];
+    if let Some(layer) = global_config_path().map(|p| ConfigLayer::read("global", p)).transpose()?.flatten() {
+        layers.push(layer);
+    }
+    if let Some(root) = qp_root {
+        if let Some(layer) = ConfigLayer::read("local", root.join("config.toml"))? {
+            layers.push(layer);
+        }
+    }
+    if let Some(path) = std::env::var_os("QP_CONFIG").filter(|p| !p.is_empty()) {
+        let path = PathBuf::from(path);
+        let layer = ConfigLayer::read("QP_CONFIG", path.clone())?
+            .with_context(|| format!("QP_CONFIG: {} does not exist", path.display()))?;
+        layers.push(layer);
+    }
+    for (var, key) in ENV_OVERRIDES {
+        let Some(value) = std::env::var(var).ok().filter(|v| !v.trim().is_empty()) else { continue };
+        let words: Vec<String> = match key_kind(key)? {
+            KeyKind::StringList => value.split_whitespace().map(String::from).collect(),
+            _ => vec![value],
+        };
+        layers.push(ConfigLayer::single("env", format!("${}", var), key, &words).with_context(|| format!("invalid {}", var))?);
+    }
+    for (key, value) in CLI_OVERRIDES.get().into_iter().flatten() {
+        let layer = ConfigLayer::single("cli", format!("-c {}", key), key, std::slice::from_ref(value));
+        layers.push(layer.with_context(|| format!("invalid -c {}={}", key, value))?);
+    }
+    Ok(layers)
 }
 
-fn merge_config(base: &mut ConfigFile, override_with: &ConfigFile) {
-    if override_with.agent.command != default_agent_command() || !override_with.agent.args.is_empty() {
-        base.agent = override_with.agent.clone();
+/// Environment variables that override one key each, applied after the `QP_CONFIG` file.
+/// List values split on whitespace (or take a TOML array).
+pub const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("QP_AGENT_COMMAND", "agent.command"),
+    ("QP_AGENT_ARGS", "agent.args"),
+    ("QP_OPTIMIZATION_STEPS", "optimization.steps"),
+];
+
+static CLI_OVERRIDES: std::sync::OnceLock<Vec<(String, String)>> = std::sync::OnceLock::new();
+
+/// Register `-c key=value` flags; they override every other layer. Keys are checked here
+/// so a typo fails before any command runs. Only the first call has an effect.
+pub fn set_cli_overrides(overrides: Vec<(String, String)>) -> Result<()> {
+    for (key, value) in &overrides {
+        if key.starts_with("merge.") {
+            anyhow::bail!("-c {}: merge modes apply to config files only", key);
+        }
+        parse_config_value(key_kind(key)?, std::slice::from_ref(value)).with_context(|| format!("invalid -c {}={}", key, value))?;
     }
-    for (k, v) in &override_with.review_agents {
-        base.review_agents.insert(k.clone(), v.clone());
+    let _ = CLI_OVERRIDES.set(overrides);
+    Ok(())
+}
+
+pub(crate) fn merge_layers(layers: &[ConfigLayer]) -> Result<(ConfigFile, BTreeMap<String, ConfigSource>)> {
+    let mut merged = toml::Table::new();
+    let mut sources = BTreeMap::new();
+    for layer in layers {
+        merge_table(&mut merged, &layer.table, "", layer, &mut sources);
     }
-    if !override_with.optimization.steps.is_empty() {
-        base.optimization.steps = override_with.optimization.steps.clone();
+    let config: ConfigFile = toml::Value::Table(merged).try_into().context("merge config layers")?;
+    Ok((config, sources))
+}
+
+/// Merge layers, then read each review agent's `prompt_file` into its `prompt`. Paths
+/// are relative to the config file that set them; from `QP_*` variables or `-c` they are
+/// relative to `.qp/`.
+pub(crate) fn resolve_layers(
+    layers: &[ConfigLayer],
+    qp_root: Option<&std::path::Path>,
+) -> Result<(ConfigFile, BTreeMap<String, ConfigSource>)> {
+    let (mut config, mut sources) = merge_layers(layers)?;
+    let rank = |sources: &BTreeMap<String, ConfigSource>, key: &str| {
+        sources.get(key).and_then(|s| layers.iter().rposition(|l| l.name == s.layer))
+    };
+    for (name, agent) in config.review_agents.iter_mut() {
+        let Some(file) = agent.prompt_file.as_deref().filter(|f| !f.trim().is_empty()) else { continue };
+        let key = format!("review_agents.{}.prompt_file", name);
+        // An inline prompt from a higher layer wins over a lower layer's prompt_file.
+        if rank(&sources, &format!("review_agents.{}.prompt", name)) > rank(&sources, &key) {
+            continue;
+        }
+        let base = sources
+            .get(&key)
+            .filter(|s| s.line.is_some())
+            .and_then(|s| s.path.as_deref())
+            .and_then(|p| std::path::Path::new(p).parent().map(|d| d.to_path_buf()))
+            .or_else(|| qp_root.map(|r| r.to_path_buf()))
+            .unwrap_or_default();
+        let path = base.join(file);
+        // A missing file only fails the step that needs it (and `qp config validate`).
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                agent.prompt_error = Some(format!("{}: read {}: {}", key, path.display(), e));
+                continue;
+            }
+        };
+        agent.prompt = text.trim_end().to_string();
+        let layer = sources.get(&key).map(|s| s.layer.clone()).unwrap_or_default();
+        let source = ConfigSource { layer, path: Some(path.display().to_string()), line: None };
+        sources.insert(format!("review_agents.{}.prompt", name), source);
     }
+    Ok((config, sources))
+}
+
+/// Check that `text` is a valid config file on its own (including its `[merge]` table).
+pub fn validate_config_text(text: &str) -> Result<()> {
+    ConfigLayer::parse("check", None, text).map(|_| ())
 }
 
 /// Resolve path to global config file (for display).
@@ -211,3 +724,384 @@ pub fn global_config_path() -> Option<PathBuf> {
 pub fn config_to_toml(config: &ConfigFile) -> Result<String> {
     toml::to_string_pretty(config).context("serialize config to TOML")
 }
+
+/// Which config file `qp config set/get/unset` works on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigScope {
+    /// `~/.config/qp/config.toml`
+    Global,
+    /// `.qp/config.toml`
+    Local,
+}
+
+impl ConfigScope {
+    pub fn path(self, qp_root: Option<&std::path::Path>) -> Result<PathBuf> {
+        match self {
+            ConfigScope::Global => global_config_path().context("no user config directory on this system"),
+            ConfigScope::Local => qp_root
+                .map(|r| r.join("config.toml"))
+                .context("no .qp directory here; run `qp init` or use --global"),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigScope::Global => write!(f, "global"),
+            ConfigScope::Local => write!(f, "local"),
+        }
+    }
+}
+
+/// Value type of a settable key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyKind {
+    String,
+    StringList,
+    Integer,
+    Float,
+    /// A string from a fixed set.
+    Choice(&'static [&'static str]),
+}
+
+/// Every config key as `(dotted key, kind, description)`; `*` matches one review agent
+/// name. Drives `qp config set/get/unset`, unknown-key warnings and `qp config schema`.
+pub const CONFIG_KEYS: &[(&str, KeyKind, &str)] = &[
+    ("agent.command", KeyKind::String, "Agent CLI for qp new/edit/import and the default for review agents"),
+    ("agent.args", KeyKind::StringList, "Extra arguments passed to the agent CLI"),
+    ("agent.profile", KeyKind::String, "Profile to use instead of agent.command and agent.args"),
+    ("review_agents.*.command", KeyKind::String, "Agent CLI that runs this optimization step"),
+    ("review_agents.*.args", KeyKind::StringList, "Extra arguments for this step's agent CLI"),
+    ("review_agents.*.prompt", KeyKind::String, "Instructions sent with the plan for this step"),
+    ("review_agents.*.prompt_file", KeyKind::String, "Markdown file with the prompt, relative to this config file's directory; replaces prompt"),
+    ("review_agents.*.profile", KeyKind::String, "Profile to use instead of this step's command and args"),
+    ("review_agents.*.output", KeyKind::Choice(&["auto", "notes", "section"]), "How the step's output is merged: full plan or review notes (auto), review notes, or one section"),
+    ("review_agents.*.section", KeyKind::String, "Section the output replaces when output = \"section\""),
+    ("review_agents.*.input", KeyKind::Choice(&["stdin", "arg"]), "How the prompt and plan reach the agent: on stdin, or as the last argument"),
+    ("optimization.steps", KeyKind::StringList, "Steps qp optimize runs, in order; each needs a review_agents entry"),
+    ("profiles.*.command", KeyKind::String, "Agent CLI for this profile"),
+    ("profiles.*.args", KeyKind::StringList, "Arguments for this profile's agent CLI, e.g. the model"),
+    ("profiles.*.env.*", KeyKind::String, "Environment variable set for this profile's agent process"),
+    ("profiles.*.timeout", KeyKind::Integer, "Seconds before a one-shot agent run is killed"),
+    ("profiles.*.input", KeyKind::Choice(&["stdin", "arg"]), "How the prompt and plan reach this profile's agent in one-shot runs"),
+    ("plans.naming", KeyKind::Choice(&["uuid", "date-slug", "seq-slug"]), "Directory naming for new plans"),
+    ("capacity.engineers", KeyKind::Integer, "Engineers available per quarter (qp schedule)"),
+    ("capacity.days", KeyKind::Integer, "Working days per quarter"),
+    ("capacity.hours_per_day", KeyKind::Float, "Focused hours per engineer per day"),
+    ("capacity.hours_per_point", KeyKind::Float, "Hours per story point in estimates"),
+    ("tracker.base_url", KeyKind::String, "Issue tracker API base URL"),
+    ("tracker.repo", KeyKind::String, "GitHub owner/name or GitLab project"),
+    ("tracker.token_env", KeyKind::String, "Environment variable holding the tracker API token"),
+    ("merge.review_agents", KeyKind::Choice(&["extend", "replace"]), "How this file's review agents combine with lower layers"),
+    ("merge.steps", KeyKind::Choice(&["replace", "extend"]), "How this file's optimization.steps combine with lower layers"),
+];
+
+/// Kind of a dotted key, or an error listing the valid keys.
+pub fn key_kind(key: &str) -> Result<KeyKind> {
+    let parts: Vec<&str> = key.split('.').collect();
+    CONFIG_KEYS
+        .iter()
+        .find(|(pattern, _, _)| {
+            let p: Vec<&str> = pattern.split('.').collect();
+            p.len() == parts.len() && p.iter().zip(&parts).all(|(p, k)| *p == "*" || p == k)
+        })
+        .map(|(_, kind, _)| *kind)
+        .with_context(|| {
+            let keys: Vec<&str> = CONFIG_KEYS.iter().map(|(k, _, _)| *k).collect();
+            format!("unknown config key: {} (valid keys: {})", key, keys.join(", "))
+        })
+}
+
+/// Parse command-line words into a TOML value of the key's kind. A single argument that
+/// is a TOML literal (`["a", "b"]`, `"quoted"`) is taken as such.
+fn parse_config_value(kind: KeyKind, words: &[String]) -> Result<toml_edit::Value> {
+    let raw = words.join(" ");
+    if let Ok(v) = raw.trim().parse::<toml_edit::Value>() {
+        let ok = matches!(
+            (kind, &v),
+            (KeyKind::String | KeyKind::Choice(_), toml_edit::Value::String(_))
+                | (KeyKind::StringList, toml_edit::Value::Array(_))
+                | (KeyKind::Integer, toml_edit::Value::Integer(_))
+                | (KeyKind::Float, toml_edit::Value::Float(_) | toml_edit::Value::Integer(_))
+        );
+        if ok {
+            if let (KeyKind::Choice(choices), Some(s)) = (kind, v.as_str()) {
+                check_choice(choices, s)?;
+            }
+            return Ok(match (kind, v) {
+                (KeyKind::Float, toml_edit::Value::Integer(i)) => (*i.value() as f64).into(),
+                (_, v) => v,
+            });
+        }
+    }
+    Ok(match kind {
+        KeyKind::String => raw.into(),
+        KeyKind::Choice(choices) => {
+            check_choice(choices, raw.trim())?;
+            raw.trim().into()
+        }
+        KeyKind::StringList => {
+            let items: Vec<String> = if words.len() == 1 {
+                words[0].split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+            } else {
+                words.to_vec()
+            };
+            toml_edit::Value::Array(items.iter().collect())
+        }
+        KeyKind::Integer => raw.trim().parse::<i64>().with_context(|| format!("expected an integer, got \"{}\"", raw))?.into(),
+        KeyKind::Float => raw.trim().parse::<f64>().with_context(|| format!("expected a number, got \"{}\"", raw))?.into(),
+    })
+}
+
+fn check_choice(choices: &[&str], value: &str) -> Result<()> {
+    if !choices.contains(&value) {
+        anyhow::bail!("expected one of {}, got \"{}\"", choices.join(", "), value);
+    }
+    Ok(())
+}
+
+fn read_document(path: &std::path::Path) -> Result<toml_edit::DocumentMut> {
+    if !path.exists() {
+        return Ok(toml_edit::DocumentMut::new());
+    }
+    let s = std::fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    s.parse().with_context(|| format!("parse {}", path.display()))
+}
+
+/// Insert a dotted key, creating implicit parent tables as needed.
+fn insert_value(root: &mut toml_edit::Table, key: &str, value: toml_edit::Value) -> Result<()> {
+    let parts: Vec<&str> = key.split('.').collect();
+    let (last, parents) = parts.split_last().unwrap();
+    let mut table = root as &mut dyn toml_edit::TableLike;
+    for part in parents {
+        let item = table.entry(part).or_insert_with(|| {
+            let mut t = toml_edit::Table::new();
+            t.set_implicit(true);
+            toml_edit::Item::Table(t)
+        });
+        table = item.as_table_like_mut().with_context(|| format!("{} is not a table", part))?;
+    }
+    table.insert(last, toml_edit::Item::Value(value));
+    Ok(())
+}
+
+/// Set `key` in the config file at `path`, keeping its comments and layout. The result
+/// must still load as a config, otherwise nothing is written. Returns the stored value.
+pub fn set_config_value(path: &std::path::Path, key: &str, words: &[String]) -> Result<String> {
+    let kind = key_kind(key)?;
+    let value = parse_config_value(kind, words)?;
+    let display = display_value(&value);
+    set_config_values(path, vec![(key.to_string(), value)])?;
+    Ok(display)
+}
+
+/// Set several keys in one write, with the same checks as [`set_config_value`].
+pub fn set_config_values(path: &std::path::Path, values: Vec<(String, toml_edit::Value)>) -> Result<()> {
+    let mut doc = read_document(path)?;
+    let keys: Vec<String> = values.iter().map(|(k, _)| k.clone()).collect();
+    for (key, value) in values {
+        key_kind(&key)?;
+        insert_value(doc.as_table_mut(), &key, value).with_context(|| format!("in {}", path.display()))?;
+    }
+    let text = doc.to_string();
+    validate_config_text(&text).with_context(|| format!("invalid value for {}", keys.join(", ")))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    std::fs::write(path, text).with_context(|| format!("write {}", path.display()))
+}
+
+/// Remove `key` from the config file at `path` (and tables it leaves empty). Returns
+/// false when the key was not set there.
+pub fn unset_config_value(path: &std::path::Path, key: &str) -> Result<bool> {
+    key_kind(key)?;
+    let mut doc = read_document(path)?;
+    let parts: Vec<&str> = key.split('.').collect();
+    fn remove(table: &mut dyn toml_edit::TableLike, parts: &[&str]) -> bool {
+        match parts {
+            [] => false,
+            [last] => table.remove(last).is_some(),
+            [first, rest @ ..] => {
+                let Some(child) = table.get_mut(first).and_then(|i| i.as_table_like_mut()) else {
+                    return false;
+                };
+                let removed = remove(child, rest);
+                if removed && child.is_empty() {
+                    table.remove(first);
+                }
+                removed
+            }
+        }
+    }
+    if !remove(doc.as_table_mut(), &parts) {
+        return Ok(false);
+    }
+    std::fs::write(path, doc.to_string()).with_context(|| format!("write {}", path.display()))?;
+    Ok(true)
+}
+
+/// Value of `key` in one config file, if set there.
+pub fn get_config_value_in(path: &std::path::Path, key: &str) -> Result<Option<String>> {
+    key_kind(key)?;
+    let doc = read_document(path)?;
+    let mut item = doc.as_item();
+    for part in key.split('.') {
+        match item.get(part) {
+            Some(i) => item = i,
+            None => return Ok(None),
+        }
+    }
+    Ok(item.as_value().map(display_value))
+}
+
+/// Effective value of `key` after merging defaults, global and local config.
+pub fn get_config_value(config: &ConfigFile, key: &str) -> Result<Option<String>> {
+    key_kind(key)?;
+    let mut value = toml::Value::try_from(config).context("serialize config")?;
+    for part in key.split('.') {
+        match value.get(part) {
+            Some(v) => value = v.clone(),
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(match value {
+        toml::Value::String(s) => s,
+        other => other.to_string(),
+    }))
+}
+
+/// Every effective leaf value (key, value, source), sorted by key. Values print as in
+/// [`get_config_value`].
+pub fn explain_config(qp_root: Option<&std::path::Path>) -> Result<Vec<(String, String, ConfigSource)>> {
+    let (config, sources) = load_config_explained(qp_root)?;
+    let merged = toml::Value::try_from(&config).context("serialize config")?;
+    let mut out = vec![];
+    for (key, source) in sources {
+        let value = key.split('.').try_fold(&merged, |v, part| v.get(part));
+        // Keys that deserialize away (unknown fields) have no effective value.
+        let Some(value) = value else { continue };
+        let value = match value {
+            toml::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        out.push((key, value, source));
+    }
+    Ok(out)
+}
+
+/// Strings print raw (prompts stay readable); everything else as TOML.
+fn display_value(value: &toml_edit::Value) -> String {
+    match value.as_str() {
+        Some(s) => s.to_string(),
+        None => value.to_string().trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_get_unset_keeps_comments() {
+        let dir = std::env::temp_dir().join("qp_test_config_edit");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        std::fs::write(&path, "# my agent\n[agent]\ncommand = \"claude\" # default\n").unwrap();
+
+        set_config_value(&path, "agent.args", &["--model,opus".to_string()]).unwrap();
+        set_config_value(&path, "review_agents.holes.prompt", &["Find".to_string(), "gaps".to_string()]).unwrap();
+        set_config_value(&path, "capacity.hours_per_day", &["5".to_string()]).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("# my agent\n[agent]\ncommand = \"claude\" # default\nargs = [\"--model\", \"opus\"]\n"));
+        assert!(text.contains("[review_agents.holes]\nprompt = \"Find gaps\"") && !text.contains("[review_agents]\n"));
+        assert_eq!(get_config_value_in(&path, "capacity.hours_per_day").unwrap().as_deref(), Some("5.0"));
+
+        assert!(set_config_value(&path, "plans.naming", &["bogus".to_string()]).is_err());
+        assert!(set_config_value(&path, "capacity.engineers", &["-1".to_string()]).is_err());
+        assert!(set_config_value(&path, "agent.colour", &["red".to_string()]).is_err());
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("bogus"));
+
+        assert!(unset_config_value(&path, "review_agents.holes.prompt").unwrap());
+        assert!(!unset_config_value(&path, "review_agents.holes.prompt").unwrap());
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("review_agents"));
+        let merged = ConfigFile::default();
+        assert_eq!(get_config_value(&merged, "optimization.steps").unwrap().unwrap(), r#"["holes", "details", "breakdown", "deliverables"]"#);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_layers_and_provenance() {
+        let global = "[agent]\ncommand = \"codex\"\nargs = [\"exec\"]\n\n[review_agents.style]\ncommand = \"codex\"\nprompt = \"Check style\"\n";
+        let local = "[agent]\nargs = [\"-q\"]\n\n[review_agents.holes]\nprompt = \"Find gaps\"\n\n\
+[optimization]\nsteps = [\"style\"]\n\n[merge]\nsteps = \"extend\"\n";
+        let layers = vec![
+            ConfigLayer::defaults().unwrap(),
+            ConfigLayer::parse("global", Some(PathBuf::from("g.toml")), global).unwrap(),
+            ConfigLayer::parse("local", Some(PathBuf::from("l.toml")), local).unwrap(),
+        ];
+        let (config, sources) = merge_layers(&layers).unwrap();
+        assert_eq!((config.agent.command.as_str(), config.agent.args.clone()), ("codex", vec!["-q".to_string()]));
+        assert_eq!(sources["agent.command"].to_string(), "g.toml:2");
+        assert_eq!(sources["agent.args"].to_string(), "l.toml:2");
+        // Agents merge by name and field; steps extend the defaults.
+        assert_eq!(config.review_agents["holes"].prompt, "Find gaps");
+        assert_eq!(sources["review_agents.holes.prompt"].to_string(), "l.toml:5");
+        assert_eq!(sources["review_agents.holes.command"].to_string(), "default");
+        assert_eq!(config.optimization.steps.last().map(String::as_str), Some("style"));
+        assert_eq!(config.optimization.steps.len(), 5);
+
+        let mut layers = layers;
+        let words = ["holes".to_string(), "style".to_string()];
+        layers.push(ConfigLayer::single("env", "$QP_OPTIMIZATION_STEPS".into(), "optimization.steps", &words).unwrap());
+        layers.push(ConfigLayer::single("cli", "-c agent.command".into(), "agent.command", &["aider".to_string()]).unwrap());
+        let (config, sources) = merge_layers(&layers).unwrap();
+        assert_eq!((config.agent.command.as_str(), config.optimization.steps), ("aider", words.to_vec()));
+        assert_eq!(sources["agent.command"].to_string(), "-c agent.command");
+        assert_eq!(sources["agent.args"].to_string(), "l.toml:2");
+        assert!(ConfigLayer::single("cli", "-c capacity.days".into(), "capacity.days", &["soon".to_string()]).is_err());
+
+        let replace = "[merge]\nreview_agents = \"replace\"\n\n[review_agents.solo]\nprompt = \"Solo\"\n";
+        let layers = vec![ConfigLayer::defaults().unwrap(), ConfigLayer::parse("local", None, replace).unwrap()];
+        let (config, sources) = merge_layers(&layers).unwrap();
+        assert_eq!(config.review_agents.keys().collect::<Vec<_>>(), vec!["solo"]);
+        assert!(!sources.keys().any(|k| k.starts_with("review_agents.holes")));
+        assert!(validate_config_text("[merge]\nsteps = \"append\"\n").is_err());
+
+        let dir = std::env::temp_dir().join("qp_test_prompt_file");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("prompts")).unwrap();
+        std::fs::write(dir.join("prompts/holes.md"), "Find gaps.\n").unwrap();
+        let text = "[review_agents.holes]\nprompt_file = \"prompts/holes.md\"\n";
+        let layers = vec![ConfigLayer::defaults().unwrap(), ConfigLayer::parse("local", Some(dir.join("config.toml")), text).unwrap()];
+        let (config, sources) = resolve_layers(&layers, None).unwrap();
+        assert_eq!(config.review_agents["holes"].prompt, "Find gaps.");
+        assert_eq!(sources["review_agents.holes.prompt"].path, Some(dir.join("prompts/holes.md").display().to_string()));
+
+        // A local inline prompt beats a global prompt_file; a missing file fails only its step.
+        let global = ConfigLayer::parse("global", Some(dir.join("config.toml")), text).unwrap();
+        let local = "[review_agents.holes]\nprompt = \"Mine.\"\n\n[review_agents.details]\nprompt_file = \"nope.md\"\n";
+        let layers = vec![ConfigLayer::defaults().unwrap(), global, ConfigLayer::parse("local", None, local).unwrap()];
+        let (config, _) = resolve_layers(&layers, None).unwrap();
+        assert_eq!(config.review_agents["holes"].prompt, "Mine.");
+        assert!(config.review_agents["details"].prompt_error.as_deref().is_some_and(|e| e.contains("nope.md")));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_profiles() {
+        let text = "[profiles.deep]\ncommand = \"claude\"\nargs = [\"--model\", \"opus\"]\ntimeout = 600\nenv = { A = \"1\" }\n\n\
+[profiles.fast]\ncommand = \"codex\"\n\n[review_agents.holes]\nprofile = \"deep\"\n";
+        let layers = vec![ConfigLayer::defaults().unwrap(), ConfigLayer::parse("local", None, text).unwrap()];
+        let (mut config, _) = merge_layers(&layers).unwrap();
+        let holes = config.step_agent("holes").unwrap();
+        assert_eq!((holes.args.join(" "), holes.env.clone()), ("--model opus".to_string(), vec![("A".into(), "1".into())]));
+        assert_eq!(holes.timeout, Some(std::time::Duration::from_secs(600)));
+        assert_eq!(config.step_agent("details").unwrap().profile, None);
+        assert!(config.use_profile("nope").is_err());
+        config.use_profile("fast").unwrap();
+        assert_eq!(config.step_agent("holes").unwrap().command, "codex");
+        assert_eq!(config.main_agent().unwrap().to_string(), "codex (profile fast)");
+    }
+}
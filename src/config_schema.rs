@@ -0,0 +1,204 @@
+//! Config checks for `qp config validate` and the JSON Schema printed by `qp config schema`.
+//!
+//! Both are driven by [`CONFIG_KEYS`], so a key added there is validated, settable and
+//! offered by editors without further changes.
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::agent;
+use crate::config::{self, ConfigSource, KeyKind, CONFIG_KEYS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// One finding of [`validate`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<ConfigSource>,
+}
+
+impl Issue {
+    fn new(severity: Severity, key: &str, message: String, source: Option<&ConfigSource>) -> Self {
+        Self { severity, key: Some(key.to_string()), message, source: source.cloned() }
+    }
+}
+
+/// Check every layer that applies here:
+/// - files that do not parse, or values of the wrong type (error)
+/// - keys qp does not know (warning)
+/// - `optimization.steps` entries without a `[review_agents.<step>]` (error)
+/// - empty review-agent prompts (error)
+/// - references to undefined profiles (error)
+/// - agent commands not found on `PATH`: an error for `agent.command` and agents of
+///   configured steps, a warning for other review agents
+pub fn validate(qp_root: Option<&std::path::Path>) -> Vec<Issue> {
+    let fatal = |e: anyhow::Error| vec![Issue { severity: Severity::Error, key: None, message: format!("{:#}", e), source: None }];
+    let layers = match config::load_layers(qp_root) {
+        Ok(layers) => layers,
+        Err(e) => return fatal(e),
+    };
+    let mut issues = vec![];
+    for layer in &layers {
+        for key in &layer.unknown {
+            issues.push(Issue::new(Severity::Warning, key, config::unknown_key_message(key), Some(&layer.source(key))));
+        }
+    }
+    let (config, sources) = match config::resolve_layers(&layers, qp_root) {
+        Ok(merged) => merged,
+        Err(e) => return fatal(e),
+    };
+
+    let steps_source = sources.get("optimization.steps");
+    for step in &config.optimization.steps {
+        if !config.review_agents.contains_key(step) {
+            let message = format!("step `{}` has no [review_agents.{}] entry", step, step);
+            issues.push(Issue::new(Severity::Error, "optimization.steps", message, steps_source));
+        }
+    }
+
+    let command_issue = |key: String, agent: anyhow::Result<config::AgentInvocation>, severity: Severity| {
+        let agent = match agent {
+            Ok(agent) => agent,
+            Err(e) => return Some(Issue::new(Severity::Error, &key, format!("{:#}", e), sources.get(&key))),
+        };
+        if agent::find_command(&agent.command).is_some() {
+            return None;
+        }
+        let message = if agent.command.trim().is_empty() {
+            "command is empty".to_string()
+        } else {
+            format!("command `{}` not found on PATH", agent.command)
+        };
+        let key = agent.profile.map(|p| format!("profiles.{}.command", p)).unwrap_or(key);
+        Some(Issue::new(severity, &key, message, sources.get(&key)))
+    };
+    let key = if config.agent.profile.is_some() { "agent.profile" } else { "agent.command" };
+    issues.extend(command_issue(key.into(), config.main_agent(), Severity::Error));
+    let mut agents: Vec<_> = config.review_agents.iter().collect();
+    agents.sort_by_key(|(name, _)| name.as_str());
+    for (name, ra) in agents {
+        if let Some(e) = &ra.prompt_error {
+            let key = format!("review_agents.{}.prompt_file", name);
+            issues.push(Issue::new(Severity::Error, &key, e.clone(), sources.get(&key)));
+        } else if ra.prompt.trim().is_empty() {
+            let key = format!("review_agents.{}.prompt", name);
+            let message = "prompt is empty (set prompt or prompt_file)".into();
+            issues.push(Issue::new(Severity::Error, &key, message, sources.get(&key)));
+        }
+        let severity = if config.optimization.steps.contains(name) { Severity::Error } else { Severity::Warning };
+        let key = match ra.profile {
+            Some(_) => format!("review_agents.{}.profile", name),
+            None => format!("review_agents.{}.command", name),
+        };
+        issues.extend(command_issue(key, config.step_agent(name), severity));
+    }
+    issues
+}
+
+fn key_schema(kind: KeyKind, description: &str) -> Value {
+    let mut schema = match kind {
+        KeyKind::String => json!({ "type": "string" }),
+        KeyKind::StringList => json!({ "type": "array", "items": { "type": "string" } }),
+        KeyKind::Integer => json!({ "type": "integer", "minimum": 0 }),
+        KeyKind::Float => json!({ "type": "number", "minimum": 0 }),
+        KeyKind::Choice(choices) => json!({ "type": "string", "enum": choices }),
+    };
+    schema["description"] = description.into();
+    schema
+}
+
+fn object() -> Value {
+    json!({ "type": "object", "additionalProperties": false, "properties": {} })
+}
+
+/// JSON Schema (draft 2020-12) for `config.toml`, with built-in defaults where a key has one.
+pub fn schema() -> Value {
+    let defaults = toml::Value::try_from(config::ConfigFile::default()).ok();
+    let mut root = object();
+    root["$schema"] = "https://json-schema.org/draft/2020-12/schema".into();
+    root["title"] = "qp config.toml".into();
+    for (pattern, kind, description) in CONFIG_KEYS {
+        let parts: Vec<&str> = pattern.split('.').collect();
+        let (last, parents) = parts.split_last().unwrap();
+        let mut node = &mut root;
+        for part in parents {
+            node = if *part == "*" {
+                let additional = &mut node["additionalProperties"];
+                if !additional.is_object() {
+                    *additional = object();
+                }
+                additional
+            } else {
+                let props = node["properties"].as_object_mut().unwrap();
+                props.entry(part.to_string()).or_insert_with(object)
+            };
+        }
+        let mut leaf = key_schema(*kind, description);
+        let default = defaults
+            .as_ref()
+            .and_then(|d| parts.iter().try_fold(d, |v, p| v.get(p)))
+            .and_then(|v| serde_json::to_value(v).ok());
+        if let Some(default) = default {
+            leaf["default"] = default;
+        }
+        if *last == "*" {
+            node["additionalProperties"] = leaf;
+        } else {
+            let props: &mut Map<String, Value> = node["properties"].as_object_mut().unwrap();
+            props.insert(last.to_string(), leaf);
+        }
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_and_validate() {
+        let schema = schema();
+        assert_eq!(schema["properties"]["plans"]["properties"]["naming"]["enum"], json!(["uuid", "date-slug", "seq-slug"]));
+        assert_eq!(schema["properties"]["optimization"]["properties"]["steps"]["default"][0], "holes");
+        let agent = &schema["properties"]["review_agents"]["additionalProperties"];
+        assert_eq!(agent["properties"]["prompt"]["type"], "string");
+        assert_eq!(agent["additionalProperties"], false);
+
+        let tmp = std::env::temp_dir().join("qp_test_config_validate");
+        let _ = std::fs::remove_dir_all(&tmp);
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(
+            tmp.join("config.toml"),
+            "[optimisation]\nsteps = [\"holes\"]\n\n[optimization]\nsteps = [\"holes\", \"style\"]\n\n[review_agents.holes]\nprompt = \" \"\n",
+        )
+        .unwrap();
+        let issues = validate(Some(&tmp));
+        let find = |key: &str| issues.iter().find(|i| i.key.as_deref() == Some(key)).unwrap();
+        let typo = find("optimisation.steps");
+        assert_eq!(typo.severity, Severity::Warning);
+        assert!(typo.message.contains("did you mean `optimization.steps`"));
+        assert_eq!(typo.source.as_ref().and_then(|s| s.line), Some(2));
+        assert!(find("optimization.steps").message.contains("`style`"));
+        assert_eq!(find("review_agents.holes.prompt").severity, Severity::Error);
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}
@@ -1,5 +1,6 @@
 //! Find .qp directory: current dir first, then walk up to repo root.
 //! Stops at repository root (or filesystem root). Nearest .qp wins (monorepo).
+//! `QP_ROOT` bypasses the search.
 
 use std::path::{Path, PathBuf};
 
@@ -25,6 +26,51 @@ pub fn find_qp_root(start: &Path) -> Option<PathBuf> {
     }
 }
 
+fn qp_root_env() -> Option<std::ffi::OsString> {
+    std::env::var_os("QP_ROOT").filter(|r| !r.is_empty())
+}
+
+/// A directory qp may use as `.qp`: named `.qp`, or already holding a config or plans.
+fn looks_like_qp_dir(dir: &Path) -> bool {
+    dir.is_dir() && (dir.file_name().is_some_and(|n| n == ".qp") || dir.join("config.toml").is_file() || dir.join("plans").is_dir())
+}
+
+/// The `.qp` directory `QP_ROOT` names: the directory itself when it looks like one, the
+/// `.qp` inside a project, or (when it does not exist yet) the directory `qp init` creates.
+fn qp_dir_for(start: &Path, root: &std::ffi::OsStr) -> PathBuf {
+    let root = start.join(root);
+    if looks_like_qp_dir(&root) || !root.exists() {
+        root
+    } else {
+        root.join(".qp")
+    }
+}
+
+/// `$QP_ROOT` when set, else [`find_qp_root`] from `start`. A `QP_ROOT` that is not an
+/// initialized `.qp` directory (or a project containing one) resolves to none.
+pub fn resolve_qp_root(start: &Path) -> anyhow::Result<Option<PathBuf>> {
+    resolve_with(start, qp_root_env().as_deref())
+}
+
+fn resolve_with(start: &Path, env: Option<&std::ffi::OsStr>) -> anyhow::Result<Option<PathBuf>> {
+    let Some(root) = env else {
+        return Ok(find_qp_root(start));
+    };
+    if start.join(root).is_file() {
+        anyhow::bail!("QP_ROOT: {} is not a directory", start.join(root).display());
+    }
+    let dir = qp_dir_for(start, root);
+    Ok(looks_like_qp_dir(&dir).then_some(dir))
+}
+
+/// Where `qp init` creates the `.qp` directory: as named by `QP_ROOT`, else in `cwd`.
+pub fn init_dir(cwd: &Path) -> PathBuf {
+    match qp_root_env() {
+        Some(root) => qp_dir_for(cwd, &root),
+        None => cwd.join(".qp"),
+    }
+}
+
 fn is_repo_root(path: &Path) -> bool {
     path.join(".git").exists()
 }
@@ -42,4 +88,22 @@ mod tests {
         assert_eq!(find_qp_root(&tmp), Some(tmp.join(".qp")));
         let _ = fs::remove_dir_all(&tmp);
     }
+
+    #[test]
+    fn test_qp_root_env_only_accepts_qp_dirs() {
+        let tmp = std::env::temp_dir().join("qp_test_qp_root_env");
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(tmp.join("project/src")).unwrap();
+        fs::create_dir_all(tmp.join("custom/plans")).unwrap();
+        let resolve = |root: &str| resolve_with(&tmp, Some(std::ffi::OsStr::new(root))).unwrap();
+        assert_eq!(resolve("project"), None);
+        assert_eq!(qp_dir_for(&tmp, "project".as_ref()), tmp.join("project/.qp"));
+        assert_eq!(resolve("project/src"), None);
+        assert_eq!(resolve("custom"), Some(tmp.join("custom")));
+        assert_eq!(resolve("missing"), None);
+        assert_eq!(qp_dir_for(&tmp, "missing".as_ref()), tmp.join("missing"));
+        fs::create_dir_all(tmp.join("project/.qp")).unwrap();
+        assert_eq!(resolve("project"), Some(tmp.join("project/.qp")));
+        let _ = fs::remove_dir_all(&tmp);
+    }
 }
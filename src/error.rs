@@ -0,0 +1,58 @@
+//! Errors that scripts need to tell apart, and the process exit codes they map to.
+
+use thiserror::Error;
+
+/// Exit codes. Anything not listed exits with `GENERAL`; clap exits with `USAGE`.
+pub mod exit_code {
+    pub const OK: i32 = 0;
+    pub const GENERAL: i32 = 1;
+    pub const USAGE: i32 = 2;
+    pub const PLAN_NOT_FOUND: i32 = 3;
+    pub const NO_QP_ROOT: i32 = 4;
+    pub const CONFIRMATION_REQUIRED: i32 = 5;
+}
+
+#[derive(Debug, Error)]
+pub enum QpError {
+    #[error("No .qp directory found. Run from a project with .qp or run `qp init` first.")]
+    NoQpRoot,
+    #[error("plan not found: {0}")]
+    PlanNotFound(String),
+    /// A destructive command was run without `--yes`.
+    #[error("{0} Use --yes to confirm.")]
+    ConfirmationRequired(String),
+}
+
+impl QpError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            QpError::NoQpRoot => exit_code::NO_QP_ROOT,
+            QpError::PlanNotFound(_) => exit_code::PLAN_NOT_FOUND,
+            QpError::ConfirmationRequired(_) => exit_code::CONFIRMATION_REQUIRED,
+        }
+    }
+}
+
+/// Exit code for an error returned from `qp::run`.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    err.downcast_ref::<QpError>()
+        .map(QpError::exit_code)
+        .unwrap_or(exit_code::GENERAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_exit_codes() {
+        let code = |e: QpError| exit_code(&anyhow::Error::new(e));
+        assert_eq!(code(QpError::NoQpRoot), 4);
+        assert_eq!(code(QpError::PlanNotFound("x".into())), 3);
+        assert_eq!(code(QpError::ConfirmationRequired("Delete?".into())), 5);
+        let wrapped = Err::<(), _>(QpError::PlanNotFound("x".into())).context("show plan").unwrap_err();
+        assert_eq!(exit_code(&wrapped), exit_code::PLAN_NOT_FOUND);
+        assert_eq!(exit_code(&anyhow::anyhow!("boom")), exit_code::GENERAL);
+    }
+}
@@ -0,0 +1,334 @@
+//! Export a plan's tickets as issue-tracker payloads: GitHub or GitLab issue JSON,
+//! a Jira CSV import file, or Linear issue JSON. `--apply` creates the issues over
+//! REST (GitHub/GitLab) and records them in the plan's `tracker.json`.
+
+use anyhow::Result;
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::{CapacityConfig, ConfigFile};
+use crate::graph;
+use crate::plan::{self, Plan, Priority};
+use crate::tickets::{self, Ticket};
+use crate::tracker::{self, IssueLink, TrackerClient, TrackerKind, TrackerMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportTarget {
+    GithubIssues,
+    GitlabIssues,
+    JiraCsv,
+    LinearJson,
+}
+
+impl ExportTarget {
+    /// Tracker that `--apply` talks to, if the target has a REST API we support.
+    pub fn tracker(self) -> Option<TrackerKind> {
+        match self {
+            ExportTarget::GithubIssues => Some(TrackerKind::Github),
+            ExportTarget::GitlabIssues => Some(TrackerKind::Gitlab),
+            ExportTarget::JiraCsv | ExportTarget::LinearJson => None,
+        }
+    }
+}
+
+/// A ticket shaped as a tracker issue.
+#[derive(Debug, Clone, Serialize)]
+pub struct IssueDraft {
+    pub ticket: String,
+    pub title: String,
+    pub body: String,
+    pub labels: Vec<String>,
+    /// Ids of tickets this one depends on.
+    pub depends_on: Vec<String>,
+    pub estimate_hours: Option<f64>,
+}
+
+/// Tickets in dependency order (upstream first); document order if there is a cycle.
+fn ordered_tickets(tickets: &[Ticket]) -> Vec<&Ticket> {
+    match graph::ticket_graph(tickets).topo_order() {
+        Ok(order) => order
+            .iter()
+            .filter_map(|id| tickets.iter().find(|t| &t.id == id))
+            .collect(),
+        Err(_) => tickets.iter().collect(),
+    }
+}
+
+/// Markdown issue body: summary, definition of done, acceptance criteria, steps and
+/// dependencies. `issue_refs` maps already-created tickets to issue references (`#12`).
+fn issue_body(plan: &Plan, t: &Ticket, deps: &[&Ticket], issue_refs: &HashMap<String, String>) -> String {
+    let mut parts = vec![];
+    if let Some(s) = t.summary() {
+        parts.push(format!("**Summary:** {}", s));
+    }
+    if let Some(d) = t.definition_of_done() {
+        parts.push(format!("**Definition of Done:** {}", d));
+    }
+    for (heading, name) in [("Acceptance Criteria", "acceptance criteria"), ("Steps", "steps")] {
+        if let Some(text) = t.section(name).filter(|s| !s.is_empty()) {
+            parts.push(format!("## {}\n\n{}", heading, text));
+        }
+    }
+    if !deps.is_empty() {
+        let lines: Vec<String> = deps
+            .iter()
+            .map(|d| match issue_refs.get(&d.id) {
+                Some(r) => format!("- {} (Ticket {}: {})", r, d.id, d.title),
+                None => format!("- Ticket {}: {}", d.id, d.title),
+            })
+            .collect();
+        parts.push(format!("## Depends on\n\n{}", lines.join("\n")));
+    }
+    parts.push(format!("---\n_Exported from qp plan \"{}\", ticket {}._", plan.meta.title, t.id));
+    parts.join("\n\n")
+}
+
+fn draft(plan: &Plan, t: &Ticket, tickets: &[Ticket], capacity: &CapacityConfig, issue_refs: &HashMap<String, String>) -> IssueDraft {
+    let deps: Vec<&Ticket> = t
+        .depends_on()
+        .iter()
+        .filter_map(|r| tickets.iter().find(|u| u.matches_ref(r)))
+        .collect();
+    IssueDraft {
+        ticket: t.id.clone(),
+        title: t.title.clone(),
+        body: issue_body(plan, t, &deps, issue_refs),
+        labels: plan.meta.tags.clone(),
+        depends_on: deps.iter().map(|d| d.id.clone()).collect(),
+        estimate_hours: t.estimate_hours(capacity),
+    }
+}
+
+/// Issue drafts for every ticket, upstream tickets first.
+pub fn issue_drafts(plan: &Plan, capacity: &CapacityConfig) -> Vec<IssueDraft> {
+    let tickets = tickets::parse_tickets(&plan.body);
+    ordered_tickets(&tickets)
+        .into_iter()
+        .map(|t| draft(plan, t, &tickets, capacity, &HashMap::new()))
+        .collect()
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn jira_priority(p: Option<Priority>) -> &'static str {
+    match p {
+        Some(Priority::Critical) => "Highest",
+        Some(Priority::High) => "High",
+        Some(Priority::Low) => "Low",
+        Some(Priority::Medium) | None => "Medium",
+    }
+}
+
+/// Jira CSV import: one row per ticket. `Issue Id` is the row number and dependencies
+/// use "Inward issue link (Blocks)" columns, so the importer recreates the links.
+fn render_jira_csv(plan: &Plan, drafts: &[IssueDraft]) -> String {
+    let max_labels = drafts.iter().map(|d| d.labels.len()).max().unwrap_or(0);
+    let max_deps = drafts.iter().map(|d| d.depends_on.len()).max().unwrap_or(0);
+    let mut header = vec!["Issue Id", "Summary", "Description", "Issue Type", "Priority", "Original Estimate"];
+    header.extend(std::iter::repeat_n("Labels", max_labels));
+    header.extend(std::iter::repeat_n("Inward issue link (Blocks)", max_deps));
+    let row_id: HashMap<&str, usize> = drafts.iter().enumerate().map(|(i, d)| (d.ticket.as_str(), i + 1)).collect();
+    let mut out = format!("{}\n", header.join(","));
+    for (i, d) in drafts.iter().enumerate() {
+        let mut row = vec![
+            (i + 1).to_string(),
+            d.title.clone(),
+            d.body.clone(),
+            "Task".to_string(),
+            jira_priority(plan.meta.priority).to_string(),
+            // Jira expects seconds.
+            d.estimate_hours.map(|h| ((h * 3600.0).round() as u64).to_string()).unwrap_or_default(),
+        ];
+        row.extend((0..max_labels).map(|n| d.labels.get(n).cloned().unwrap_or_default()));
+        row.extend((0..max_deps).map(|n| {
+            d.depends_on
+                .get(n)
+                .and_then(|t| row_id.get(t.as_str()))
+                .map(|id| id.to_string())
+                .unwrap_or_default()
+        }));
+        let cells: Vec<String> = row.iter().map(|c| csv_field(c)).collect();
+        out.push_str(&format!("{}\n", cells.join(",")));
+    }
+    out
+}
+
+/// Render the payload for `target` (without contacting any tracker).
+pub fn render(plan: &Plan, target: ExportTarget, capacity: &CapacityConfig) -> Result<String> {
+    let drafts = issue_drafts(plan, capacity);
+    let json = |v: serde_json::Value| -> Result<String> { Ok(format!("{}\n", serde_json::to_string_pretty(&v)?)) };
+    match target {
+        ExportTarget::GithubIssues => json(serde_json::Value::Array(
+            drafts
+                .iter()
+                .map(|d| serde_json::json!({ "title": d.title, "body": d.body, "labels": d.labels }))
+                .collect(),
+        )),
+        ExportTarget::GitlabIssues => json(serde_json::Value::Array(
+            drafts
+                .iter()
+                .map(|d| serde_json::json!({ "title": d.title, "description": d.body, "labels": d.labels.join(",") }))
+                .collect(),
+        )),
+        ExportTarget::JiraCsv => Ok(render_jira_csv(plan, &drafts)),
+        ExportTarget::LinearJson => {
+            // Linear priorities: 1 urgent .. 4 low, 0 none.
+            let priority = match plan.meta.priority {
+                Some(Priority::Critical) => 1,
+                Some(Priority::High) => 2,
+                Some(Priority::Medium) => 3,
+                Some(Priority::Low) => 4,
+                None => 0,
+            };
+            json(serde_json::json!({
+                "issues": drafts
+                    .iter()
+                    .map(|d| serde_json::json!({
+                        "id": d.ticket,
+                        "title": d.title,
+                        "description": d.body,
+                        "labelNames": d.labels,
+                        "priority": priority,
+                        "estimate": d.estimate_hours,
+                        "blockedBy": d.depends_on,
+                    }))
+                    .collect::<Vec<_>>(),
+            }))
+        }
+    }
+}
+
+/// What `--apply` did for one ticket.
+#[derive(Debug, Clone, Serialize)]
+pub struct Applied {
+    pub ticket: String,
+    pub title: String,
+    pub number: u64,
+    pub url: Option<String>,
+    /// False when the ticket was already mapped to an issue and was skipped.
+    pub created: bool,
+}
+
+/// Create issues for tickets that are not in `tracker.json` yet, upstream tickets first so
+/// dependency links can reference real issue numbers. The mapping is saved after each issue.
+pub fn apply(qp_root: &Path, plan_ref: &str, client: &TrackerClient, config: &ConfigFile) -> Result<Vec<Applied>> {
+    let plan = plan::get_plan(qp_root, plan_ref)?;
+    let dir = plan::plan_dir(qp_root, &plan.meta.id);
+    let mut map = match tracker::load_map(&dir)? {
+        Some(m) if m.tracker != client.kind || m.repo != client.repo => anyhow::bail!(
+            "plan was exported to {} {}; refusing to export it to {} {} as well (remove {} to start over)",
+            m.tracker,
+            m.repo,
+            client.kind,
+            client.repo,
+            tracker::map_path(&dir).display()
+        ),
+        Some(m) => m,
+        None => TrackerMap {
+            tracker: client.kind,
+            base_url: client.base_url.clone(),
+            repo: client.repo.clone(),
+            synced_at: None,
+            issues: vec![],
+        },
+    };
+    let tickets = tickets::parse_tickets(&plan.body);
+    let mut issue_refs: HashMap<String, String> =
+        map.issues.iter().map(|i| (i.ticket.clone(), format!("#{}", i.number))).collect();
+    let mut results = vec![];
+    for t in ordered_tickets(&tickets) {
+        if let Some(existing) = map.issue_for(&t.id) {
+            results.push(Applied {
+                ticket: t.id.clone(),
+                title: t.title.clone(),
+                number: existing.number,
+                url: existing.url.clone(),
+                created: false,
+            });
+            continue;
+        }
+        let d = draft(&plan, t, &tickets, &config.capacity, &issue_refs);
+        let issue = client.create_issue(&d.title, &d.body, &d.labels)?;
+        issue_refs.insert(t.id.clone(), format!("#{}", issue.number));
+        map.issues.push(IssueLink {
+            ticket: t.id.clone(),
+            title: t.title.clone(),
+            number: issue.number,
+            url: issue.url.clone(),
+            exported_at: Utc::now().to_rfc3339(),
+            ticket_hash: tracker::ticket_hash(t),
+            remote_updated_at: issue.updated_at.clone(),
+            remote_state: Some(issue.state.clone()),
+        });
+        tracker::save_map(&dir, &map)?;
+        results.push(Applied {
+            ticket: t.id.clone(),
+            title: t.title.clone(),
+            number: issue.number,
+            url: issue.url,
+            created: true,
+        });
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payloads_include_sections_labels_and_links() {
+        let content = "---\nid: p1\ntitle: Gateway\nstate: ready\ncreated_at: x\nupdated_at: x\ntags: [backend]\n---\n\n## Tickets\n\n### Ticket 1: Setup\n\n**Summary:** Set up, fast.\n\n**Definition of Done:** Runs.\n\n#### Acceptance Criteria\n\n- [ ] boots\n\n#### Steps\n\n1. **Install**\n   - Estimate: 2h\n\n### Ticket 2: Auth\n\n**Depends on:** Ticket 1\n";
+        let plan = plan::parse_plan(content).unwrap();
+        let capacity = CapacityConfig::default();
+        let drafts = issue_drafts(&plan, &capacity);
+        assert_eq!(drafts.len(), 2);
+        assert!(drafts[0].body.contains("## Acceptance Criteria\n\n- [ ] boots"));
+        assert!(drafts[0].body.contains("## Steps"));
+        assert_eq!(drafts[1].depends_on, vec!["1"]);
+        assert!(drafts[1].body.contains("- Ticket 1: Setup"));
+
+        let github: serde_json::Value = serde_json::from_str(&render(&plan, ExportTarget::GithubIssues, &capacity).unwrap()).unwrap();
+        assert_eq!(github[0]["labels"][0], "backend");
+        let csv = render(&plan, ExportTarget::JiraCsv, &capacity).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "Issue Id,Summary,Description,Issue Type,Priority,Original Estimate,Labels,Inward issue link (Blocks)"
+        );
+        assert!(lines.next().unwrap().starts_with("1,Setup,\"**Summary:** Set up, fast."));
+        assert!(csv.trim_end().ends_with(",Task,Medium,,backend,1"));
+    }
+
+    #[test]
+    fn test_apply_creates_issues_once() {
+        let tmp = std::env::temp_dir().join("qp_test_export_apply");
+        let _ = std::fs::remove_dir_all(&tmp);
+        let body = "## Tickets\n\n### Ticket 1: Setup\n\n**Summary:** Set up.\n\n### Ticket 2: Auth\n\n**Depends on:** Ticket 1\n";
+        let plan = plan::create_plan_with_body(&tmp, Some("Gateway"), body, plan::PlanDirNaming::Uuid).unwrap();
+        let server = tracker::mock::MockGithub::start();
+        let client = server.client();
+        let config = ConfigFile::default();
+
+        let applied = apply(&tmp, &plan.meta.id, &client, &config).unwrap();
+        assert_eq!(applied.iter().map(|a| (a.number, a.created)).collect::<Vec<_>>(), vec![(1, true), (2, true)]);
+        assert!(server.issues.lock().unwrap()[1]["body"].as_str().unwrap().contains("- #1 (Ticket 1: Setup)"));
+        let map = tracker::load_map(&plan::plan_dir(&tmp, &plan.meta.id)).unwrap().unwrap();
+        assert_eq!((map.repo.as_str(), map.issues.len()), ("acme/api", 2));
+        assert_eq!(map.issue_for("2").unwrap().remote_state.as_deref(), Some("open"));
+
+        // Already-mapped tickets are skipped on a second run.
+        let again = apply(&tmp, &plan.meta.id, &client, &config).unwrap();
+        assert!(again.iter().all(|a| !a.created));
+        assert_eq!(server.requests.lock().unwrap().as_slice(), ["POST /repos/acme/api/issues", "POST /repos/acme/api/issues"]);
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}
@@ -0,0 +1,298 @@
+//! Dependency graphs between plans (frontmatter `depends_on`) and between tickets
+//! (`**Depends on:**` lines). Cycle detection plus DOT and Mermaid output.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::plan::{self, Plan, PlanLocation, PlanMeta, PlanState};
+use crate::tickets::{self, Ticket};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Node {
+    pub id: String,
+    pub label: String,
+    /// Shown next to the label, e.g. plan state or ticket status.
+    pub status: Option<String>,
+}
+
+/// Directed graph; an edge `(a, b)` means `b` depends on `a` (a blocks b).
+#[derive(Debug, Clone, Default)]
+pub struct DepGraph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<(String, String)>,
+    /// References that did not resolve to a node, as (node id, reference).
+    pub unresolved: Vec<(String, String)>,
+}
+
+impl DepGraph {
+    fn index(&self) -> HashMap<&str, usize> {
+        self.nodes.iter().enumerate().map(|(i, n)| (n.id.as_str(), i)).collect()
+    }
+
+    /// Ids of nodes that `id` depends on.
+    pub fn upstream(&self, id: &str) -> Vec<&str> {
+        self.edges
+            .iter()
+            .filter(|(_, to)| to == id)
+            .map(|(from, _)| from.as_str())
+            .collect()
+    }
+
+    /// Return one dependency cycle as a list of node ids (first id repeated at the end), if any.
+    pub fn find_cycle(&self) -> Option<Vec<String>> {
+        let idx = self.index();
+        let mut adj: Vec<Vec<usize>> = vec![vec![]; self.nodes.len()];
+        for (from, to) in &self.edges {
+            if let (Some(&a), Some(&b)) = (idx.get(from.as_str()), idx.get(to.as_str())) {
+                adj[a].push(b);
+            }
+        }
+        // 0 = unvisited, 1 = on stack, 2 = done
+        let mut color = vec![0u8; self.nodes.len()];
+        let mut stack: Vec<usize> = vec![];
+        fn visit(
+            n: usize,
+            adj: &[Vec<usize>],
+            color: &mut [u8],
+            stack: &mut Vec<usize>,
+        ) -> Option<Vec<usize>> {
+            color[n] = 1;
+            stack.push(n);
+            for &m in &adj[n] {
+                if color[m] == 1 {
+                    let pos = stack.iter().position(|&x| x == m).unwrap_or(0);
+                    let mut cycle = stack[pos..].to_vec();
+                    cycle.push(m);
+                    return Some(cycle);
+                }
+                if color[m] == 0 {
+                    if let Some(c) = visit(m, adj, color, stack) {
+                        return Some(c);
+                    }
+                }
+            }
+            stack.pop();
+            color[n] = 2;
+            None
+        }
+        for n in 0..self.nodes.len() {
+            if color[n] == 0 {
+                if let Some(c) = visit(n, &adj, &mut color, &mut stack) {
+                    return Some(c.into_iter().map(|i| self.nodes[i].id.clone()).collect());
+                }
+            }
+        }
+        None
+    }
+
+    /// Node ids in dependency order (upstream first). Fails on a cycle.
+    pub fn topo_order(&self) -> Result<Vec<String>> {
+        if let Some(cycle) = self.find_cycle() {
+            anyhow::bail!("dependency cycle: {}", cycle.join(" -> "));
+        }
+        let idx = self.index();
+        let mut indegree = vec![0usize; self.nodes.len()];
+        for (from, to) in &self.edges {
+            if idx.contains_key(from.as_str()) {
+                if let Some(&b) = idx.get(to.as_str()) {
+                    indegree[b] += 1;
+                }
+            }
+        }
+        let mut ready: Vec<usize> = (0..self.nodes.len()).filter(|&i| indegree[i] == 0).collect();
+        let mut order = vec![];
+        while !ready.is_empty() {
+            let n = ready.remove(0);
+            order.push(self.nodes[n].id.clone());
+            for (from, to) in &self.edges {
+                if from == &self.nodes[n].id {
+                    if let Some(&b) = idx.get(to.as_str()) {
+                        indegree[b] -= 1;
+                        if indegree[b] == 0 {
+                            ready.push(b);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    fn display_label(node: &Node) -> String {
+        match &node.status {
+            Some(s) => format!("{} ({})", node.label, s),
+            None => node.label.clone(),
+        }
+    }
+
+    /// Graphviz DOT output.
+    pub fn to_dot(&self, name: &str) -> String {
+        let esc = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut out = format!("digraph \"{}\" {{\n  rankdir=LR;\n  node [shape=box];\n", esc(name));
+        for n in &self.nodes {
+            out.push_str(&format!("  \"{}\" [label=\"{}\"];\n", esc(&n.id), esc(&Self::display_label(n))));
+        }
+        for (from, to) in &self.edges {
+            out.push_str(&format!("  \"{}\" -> \"{}\";\n", esc(from), esc(to)));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Mermaid flowchart output.
+    pub fn to_mermaid(&self) -> String {
+        let idx = self.index();
+        let key = |id: &str| format!("n{}", idx.get(id).copied().unwrap_or(0));
+        let esc = |s: &str| s.replace('"', "#quot;");
+        let mut out = "flowchart LR\n".to_string();
+        for (i, n) in self.nodes.iter().enumerate() {
+            out.push_str(&format!("  n{}[\"{}\"]\n", i, esc(&Self::display_label(n))));
+        }
+        for (from, to) in &self.edges {
+            if idx.contains_key(from.as_str()) && idx.contains_key(to.as_str()) {
+                out.push_str(&format!("  {} --> {}\n", key(from), key(to)));
+            }
+        }
+        out
+    }
+}
+
+/// Resolve a plan reference (id, slug or title) against a list of plans.
+pub fn resolve_plan_ref<'a>(plans: &'a [PlanMeta], reference: &str) -> Option<&'a PlanMeta> {
+    let slug = plan::title_to_slug(reference);
+    plans.iter().find(|m| {
+        m.id == reference || plan::plan_slug(m) == slug || m.title == reference
+    })
+}
+
+/// Graph of active plans linked by their `depends_on` frontmatter. References may also
+/// name an `archived` plan, which is then added as a node of its own.
+pub fn plan_graph(plans: &[PlanMeta], archived: &[PlanMeta]) -> DepGraph {
+    let mut g = DepGraph::default();
+    for m in plans {
+        g.nodes.push(Node {
+            id: m.id.clone(),
+            label: m.title.clone(),
+            status: Some(m.state.to_string()),
+        });
+    }
+    for m in plans {
+        for dep in &m.depends_on {
+            let up = resolve_plan_ref(plans, dep).or_else(|| {
+                let up = resolve_plan_ref(archived, dep)?;
+                if !g.nodes.iter().any(|n| n.id == up.id) {
+                    g.nodes.push(Node {
+                        id: up.id.clone(),
+                        label: format!("{} (archived)", up.title),
+                        status: Some(up.state.to_string()),
+                    });
+                }
+                Some(up)
+            });
+            match up {
+                Some(up) => g.edges.push((up.id.clone(), m.id.clone())),
+                None => g.unresolved.push((m.id.clone(), dep.clone())),
+            }
+        }
+    }
+    g
+}
+
+/// Load the plan graph for a .qp root.
+pub fn load_plan_graph(qp_root: &Path) -> Result<DepGraph> {
+    Ok(plan_graph(&plan::list_plans(qp_root)?, &plan::list_plans_in(qp_root, PlanLocation::Archived)?))
+}
+
+/// Graph of a plan's tickets linked by `**Depends on:**` lines.
+pub fn ticket_graph(tickets: &[Ticket]) -> DepGraph {
+    let mut g = DepGraph::default();
+    for t in tickets {
+        g.nodes.push(Node {
+            id: t.id.clone(),
+            label: t.title.clone(),
+            status: t.is_done().then(|| "done".to_string()),
+        });
+    }
+    for t in tickets {
+        for dep in t.depends_on() {
+            match tickets.iter().find(|u| u.matches_ref(&dep)) {
+                Some(up) => g.edges.push((up.id.clone(), t.id.clone())),
+                None => g.unresolved.push((t.id.clone(), dep)),
+            }
+        }
+    }
+    g
+}
+
+/// Ticket graph for a loaded plan.
+pub fn plan_ticket_graph(plan: &Plan) -> DepGraph {
+    ticket_graph(&tickets::parse_tickets(&plan.body))
+}
+
+/// Plans that `plan_id` depends on (active or archived) that are not completed yet, and
+/// its `depends_on` references that match no plan.
+pub fn upstream_blockers(qp_root: &Path, plan_id: &str) -> Result<(Vec<PlanMeta>, Vec<String>)> {
+    let plans = plan::list_plans(qp_root)?;
+    let archived = plan::list_plans_in(qp_root, PlanLocation::Archived)?;
+    let graph = plan_graph(&plans, &archived);
+    let incomplete = graph
+        .upstream(plan_id)
+        .into_iter()
+        .filter_map(|id| plans.iter().chain(&archived).find(|m| m.id == id))
+        .filter(|m| m.state != PlanState::Completed)
+        .cloned()
+        .collect();
+    let unresolved = graph.unresolved.into_iter().filter(|(id, _)| id == plan_id).map(|(_, r)| r).collect();
+    Ok((incomplete, unresolved))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str) -> Node {
+        Node { id: id.to_string(), label: id.to_uppercase(), status: None }
+    }
+
+    #[test]
+    fn test_cycle_detection_and_topo_order() {
+        let mut g = DepGraph {
+            nodes: vec![node("a"), node("b"), node("c")],
+            edges: vec![("a".into(), "b".into()), ("b".into(), "c".into())],
+            unresolved: vec![],
+        };
+        assert!(g.find_cycle().is_none());
+        assert_eq!(g.topo_order().unwrap(), vec!["a", "b", "c"]);
+        assert!(g.to_mermaid().contains("n0 --> n1"));
+
+        g.edges.push(("c".into(), "a".into()));
+        assert_eq!(g.find_cycle().unwrap(), vec!["a", "b", "c", "a"]);
+        assert!(g.topo_order().is_err());
+    }
+
+    #[test]
+    fn test_upstream_blockers_include_archived_plans() {
+        let tmp = std::env::temp_dir().join("qp_test_upstream_archived");
+        let _ = std::fs::remove_dir_all(&tmp);
+        let up = plan::create_plan(&tmp, Some("Schema"), plan::PlanDirNaming::Uuid).unwrap();
+        let down = plan::create_plan(&tmp, Some("Search"), plan::PlanDirNaming::Uuid).unwrap();
+        plan::archive_plan(&tmp, "schema").unwrap();
+        plan::set_plan_fields(&tmp, "search", &[("depends_on".to_string(), "schema".to_string())]).unwrap();
+        let mut plan = plan::get_plan(&tmp, "search").unwrap();
+        plan.meta.depends_on.push("nope".to_string());
+        plan::save_plan(&tmp, &plan).unwrap();
+
+        let (incomplete, unresolved) = upstream_blockers(&tmp, &down.meta.id).unwrap();
+        assert_eq!(incomplete.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), [up.meta.id.as_str()]);
+        assert_eq!(unresolved, ["nope"]);
+        assert!(load_plan_graph(&tmp).unwrap().nodes.iter().any(|n| n.label == "Schema (archived)"));
+
+        plan.meta.state = PlanState::Completed;
+        plan::save_plan(&tmp, &plan).unwrap();
+        assert!(plan::start_plan(&tmp, "search", false).is_err());
+        assert_eq!(plan::start_plan(&tmp, "search", true).unwrap().meta.state, PlanState::InProgress);
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}
@@ -0,0 +1,386 @@
+//! Turn existing documents into qp plans: Markdown (RFCs, Google-Doc exports), GitHub
+//! issue JSON and plain text.
+//!
+//! Headings are mapped onto the required sections by name ("Background" → Overview,
+//! "Non-goals" → Constraints, "Tasks" → Tickets, ...). Sections that match nothing are
+//! kept under Implementation Notes and reported, so nothing from the source is lost.
+//! `qp import --normalize` hands the converted plan to the agent for a proper rewrite.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::config::ConfigFile;
+use crate::plan;
+
+/// Required body sections, in order.
+pub const SECTIONS: &[&str] = &["Overview", "Constraints", "Implementation Notes", "Review Notes", "Tickets"];
+
+/// Heading words that map onto each section (matched against the lowercased heading).
+const SYNONYMS: &[(&str, &[&str])] = &[
+    (
+        "Overview",
+        &[
+            "overview", "summary", "background", "context", "motivation", "problem", "goal", "introduction",
+            "abstract", "tl;dr", "tldr", "description", "purpose", "scope",
+        ],
+    ),
+    (
+        "Constraints",
+        &["constraint", "requirement", "non-goal", "non goal", "limitation", "assumption", "out of scope", "risk"],
+    ),
+    (
+        "Implementation Notes",
+        &[
+            "implementation", "design", "approach", "proposal", "architecture", "solution", "technical", "how it works",
+            "details",
+        ],
+    ),
+    ("Review Notes", &["review", "open question", "question", "feedback", "discussion", "alternative", "faq"]),
+    (
+        "Tickets",
+        &[
+            "ticket", "task", "milestone", "work item", "action item", "todo", "to do", "rollout", "next step",
+            "deliverable", "phase", "work breakdown",
+        ],
+    ),
+];
+
+const EMPTY_SECTION: &str = "(Not covered by the imported document.)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImportFormat {
+    /// Guess from the file extension and content
+    Auto,
+    Markdown,
+    /// A GitHub issue as returned by the REST API (`gh issue view --json title,body,labels,url`)
+    GithubIssue,
+    Text,
+}
+
+impl std::fmt::Display for ImportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportFormat::Auto => write!(f, "auto"),
+            ImportFormat::Markdown => write!(f, "markdown"),
+            ImportFormat::GithubIssue => write!(f, "github-issue"),
+            ImportFormat::Text => write!(f, "text"),
+        }
+    }
+}
+
+/// A document converted to qp's plan shape, before it is written.
+#[derive(Debug, Clone, Serialize)]
+pub struct Imported {
+    pub title: Option<String>,
+    pub body: String,
+    pub tags: Vec<String>,
+    /// Source headings that matched no section (kept under Implementation Notes).
+    pub unmapped: Vec<String>,
+}
+
+/// Pick a format from the file name (None for stdin) and content.
+pub fn detect(name: Option<&str>, content: &str) -> ImportFormat {
+    let trimmed = content.trim_start();
+    if trimmed.starts_with('{') {
+        if let Ok(v) = serde_json::from_str::<serde_json::Value>(trimmed) {
+            if v.get("title").is_some() {
+                return ImportFormat::GithubIssue;
+            }
+        }
+    }
+    let ext = name
+        .and_then(|n| Path::new(n).extension())
+        .map(|e| e.to_string_lossy().to_lowercase());
+    match ext.as_deref() {
+        Some("md" | "markdown" | "mdown") => ImportFormat::Markdown,
+        Some("txt" | "text") => ImportFormat::Text,
+        _ if content.lines().any(|l| heading(l).is_some()) => ImportFormat::Markdown,
+        _ => ImportFormat::Text,
+    }
+}
+
+/// Convert `content` in the given format (Auto is resolved with [`detect`]).
+pub fn convert(format: ImportFormat, name: Option<&str>, content: &str) -> Result<Imported> {
+    match format {
+        ImportFormat::Auto => convert(detect(name, content), name, content),
+        ImportFormat::Markdown => Ok(from_markdown(content)),
+        ImportFormat::GithubIssue => from_github_issue(content),
+        ImportFormat::Text => Ok(from_text(content)),
+    }
+}
+
+/// `(level, text)` for an ATX heading line.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.bytes().take_while(|&b| b == b'#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.starts_with(' ') {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim()))
+}
+
+/// Section a source heading belongs to, if any.
+fn section_for(heading: &str) -> Option<&'static str> {
+    let h = heading.to_lowercase();
+    let h = h.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == ' ');
+    // The longest match wins across all sections, so "Out of Scope" is a constraint
+    // rather than an overview ("scope").
+    SYNONYMS
+        .iter()
+        .flat_map(|(section, words)| words.iter().map(move |w| (*section, *w)))
+        .filter(|(_, w)| h.starts_with(w) || h.contains(&format!(" {}", w)))
+        .max_by_key(|(_, w)| w.len())
+        .map(|(section, _)| section)
+}
+
+/// Drop YAML frontmatter, returning its `title` if there was one.
+fn strip_frontmatter(content: &str) -> (Option<String>, &str) {
+    let Some(rest) = content.strip_prefix("---\n") else {
+        return (None, content);
+    };
+    let Some(end) = rest.find("\n---") else {
+        return (None, content);
+    };
+    let title = serde_yaml::from_str::<serde_yaml::Value>(&rest[..end])
+        .ok()
+        .and_then(|v| v.get("title").and_then(|t| t.as_str()).map(str::to_string));
+    let after = &rest[end + 4..];
+    (title, after.strip_prefix('\n').unwrap_or(after))
+}
+
+fn from_markdown(content: &str) -> Imported {
+    let content = content.replace("\r\n", "\n");
+    let (mut title, content) = strip_frontmatter(&content);
+
+    // Split into (heading level, heading, lines), ignoring `#` lines inside code fences.
+    let mut blocks: Vec<(usize, String, Vec<String>)> = vec![(0, String::new(), vec![])];
+    let mut in_fence = false;
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        match heading(line).filter(|_| !in_fence) {
+            Some((level, text)) => blocks.push((level, text.to_string(), vec![])),
+            None => blocks.last_mut().unwrap().2.push(line.to_string()),
+        }
+    }
+
+    // A single top-level `#` heading is the document title.
+    if blocks.iter().filter(|b| b.0 == 1).count() == 1 {
+        let i = blocks.iter().position(|b| b.0 == 1).unwrap();
+        let (_, text, lines) = blocks.remove(i);
+        title.get_or_insert(text);
+        blocks[0].2.extend(lines);
+    }
+
+    let top = blocks.iter().skip(1).map(|b| b.0).min().unwrap_or(2);
+    let mut sections: Vec<(&str, String)> = SECTIONS.iter().map(|s| (*s, String::new())).collect();
+    let mut unmapped = vec![];
+    let mut current = "Overview";
+    let mut in_unmapped = false;
+    let push = |sections: &mut Vec<(&str, String)>, name: &str, text: &str| {
+        let s = &mut sections.iter_mut().find(|(n, _)| *n == name).unwrap().1;
+        s.push_str(text);
+        s.push('\n');
+    };
+    for (i, (level, text, lines)) in blocks.iter().enumerate() {
+        if i > 0 && *level == top {
+            match section_for(text) {
+                Some(section) => {
+                    current = section;
+                    in_unmapped = false;
+                }
+                None => {
+                    unmapped.push(text.clone());
+                    current = "Implementation Notes";
+                    in_unmapped = true;
+                    push(&mut sections, current, &format!("\n### {}\n", text));
+                }
+            }
+        } else if i > 0 {
+            // Nested heading: keep it, shifted below the section (or below an unmapped subsection).
+            let depth = (level - top + if in_unmapped { 3 } else { 2 }).min(6);
+            push(&mut sections, current, &format!("\n{} {}\n", "#".repeat(depth), text));
+        }
+        push(&mut sections, current, &lines.join("\n"));
+    }
+
+    let tickets = &mut sections.iter_mut().find(|(n, _)| *n == "Tickets").unwrap().1;
+    *tickets = tickets_from_lists(tickets);
+    Imported { title, body: render_sections(&sections), tags: vec![], unmapped }
+}
+
+/// Turn top-level list items and `###` subheadings in a Tickets section into
+/// `### Ticket N:` entries. A section already in qp's ticket format is left alone.
+fn tickets_from_lists(text: &str) -> String {
+    if text.lines().any(|l| l.to_lowercase().starts_with("### ticket")) {
+        return text.to_string();
+    }
+    let mut items: Vec<(String, Vec<String>)> = vec![];
+    let mut preamble = vec![];
+    for line in text.lines() {
+        let item = line
+            .strip_prefix("- [ ] ")
+            .or_else(|| line.strip_prefix("- [x] "))
+            .or_else(|| line.strip_prefix("- "))
+            .or_else(|| line.strip_prefix("* "))
+            .or_else(|| {
+                let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+                (digits > 0).then(|| line[digits..].strip_prefix(". ")).flatten()
+            })
+            .or_else(|| line.strip_prefix("### "));
+        match item {
+            Some(title) if !title.trim().is_empty() => items.push((title.trim().to_string(), vec![])),
+            _ => match items.last_mut() {
+                Some((_, detail)) => detail.push(line.trim().to_string()),
+                None => preamble.push(line.to_string()),
+            },
+        }
+    }
+    if items.is_empty() {
+        return text.to_string();
+    }
+    let mut out = preamble.join("\n").trim().to_string();
+    for (i, (title, detail)) in items.iter().enumerate() {
+        let detail = detail.iter().filter(|l| !l.is_empty()).cloned().collect::<Vec<_>>().join(" ");
+        let summary = if detail.is_empty() { title.clone() } else { detail };
+        out.push_str(&format!(
+            "\n\n### Ticket {}: {}\n\n**Summary:** {}\n\n**Definition of Done:** (to be defined)",
+            i + 1,
+            title.trim_end_matches(['.', ':']),
+            summary
+        ));
+    }
+    out.trim().to_string()
+}
+
+fn render_sections(sections: &[(&str, String)]) -> String {
+    sections
+        .iter()
+        .map(|(name, text)| {
+            // Joined blocks leave runs of blank lines; keep at most one.
+            let mut text = text.trim().to_string();
+            while text.contains("\n\n\n") {
+                text = text.replace("\n\n\n", "\n\n");
+            }
+            format!("## {}\n\n{}", name, if text.is_empty() { EMPTY_SECTION } else { &text })
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn from_github_issue(content: &str) -> Result<Imported> {
+    let v: serde_json::Value = serde_json::from_str(content).context("parse GitHub issue JSON")?;
+    let title = v["title"].as_str().context("GitHub issue JSON has no \"title\"")?;
+    let mut imported = from_markdown(v["body"].as_str().unwrap_or_default());
+    imported.title = Some(title.trim().to_string());
+    imported.tags = v["labels"]
+        .as_array()
+        .map(|labels| {
+            labels
+                .iter()
+                .filter_map(|l| l["name"].as_str().or_else(|| l.as_str()))
+                .map(|l| l.to_lowercase().replace(' ', "-"))
+                .collect()
+        })
+        .unwrap_or_default();
+    let url = v["html_url"].as_str().or_else(|| v["url"].as_str());
+    if let Some(url) = url.filter(|u| u.starts_with("http")) {
+        let source = format!("Imported from {}", url);
+        imported.body = match imported.body.strip_prefix(&format!("## Overview\n\n{}", EMPTY_SECTION)) {
+            Some(rest) => format!("## Overview\n\n{}{}", source, rest),
+            None => imported.body.replacen("## Overview\n\n", &format!("## Overview\n\n{}\n\n", source), 1),
+        };
+    }
+    Ok(imported)
+}
+
+fn from_text(content: &str) -> Imported {
+    let content = content.replace("\r\n", "\n");
+    let mut lines = content.lines().skip_while(|l| l.trim().is_empty()).peekable();
+    let first = lines.next().unwrap_or_default().trim();
+    // A short first line followed by a blank line (or nothing) reads as a title.
+    let blank_after = lines.peek().is_none_or(|l| l.trim().is_empty());
+    let (title, rest) = if blank_after && !first.is_empty() && first.len() <= 100 && !first.ends_with('.') {
+        (Some(first.to_string()), lines.collect::<Vec<_>>().join("\n"))
+    } else {
+        (None, content.clone())
+    };
+    let mut sections: Vec<(&str, String)> = SECTIONS.iter().map(|s| (*s, String::new())).collect();
+    sections[0].1 = rest;
+    Imported { title, body: render_sections(&sections), tags: vec![], unmapped: vec![] }
+}
+
+/// Prompt for `qp import --normalize`.
+pub fn normalize_prompt(plan_md_path: &Path, plan_id: &str, title: &str) -> String {
+    format!(
+        "Rewrite the document below as a qp plan. It was imported from another format and its \
+sections were mapped heuristically. Keep all of its content, move each part into the right section, \
+and break the work into tickets with Summary and Definition of Done. Output only the complete plan \
+(frontmatter and body), nothing else.\n\n{}",
+        plan::plan_format_instructions(plan_md_path, plan_id, title)
+    )
+}
+
+/// Run the agent over an imported plan and return the normalized body.
+pub fn normalize(config: &ConfigFile, prompt: &str, plan_content: &str) -> Result<String> {
+    let output = crate::agent::run_agent_oneshot(&config.main_agent()?, prompt, plan_content)?;
+    let trimmed = output.trim();
+    if trimmed.starts_with("---") {
+        if let Ok(parsed) = plan::parse_plan(trimmed) {
+            return Ok(parsed.body);
+        }
+    }
+    // Body without frontmatter, possibly after a line of chatter.
+    if let Some(pos) = trimmed.find("## Overview") {
+        return Ok(trimmed[pos..].to_string());
+    }
+    anyhow::bail!("agent output does not look like a qp plan")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_headings_map_to_sections() {
+        let doc = "# Search v2\n\nIntro paragraph.\n\n## Background\n\nWhy.\n\n## Non-goals\n\nNo UI.\n\n## Out of Scope\n\nMobile.\n\n## Non goals\n\nNo sync.\n\n\
+## Detailed Design\n\n### Indexing\n\n```\n# not a heading\n```\n\n## Rollout Plan\n\n- Build index\n- Ship API\n  behind a flag\n\n## Glossary\n\nTerms.\n";
+        assert_eq!(detect(Some("rfc.md"), doc), ImportFormat::Markdown);
+        let imported = convert(ImportFormat::Auto, Some("rfc.md"), doc).unwrap();
+        assert_eq!(imported.title.as_deref(), Some("Search v2"));
+        assert_eq!(imported.unmapped, vec!["Glossary"]);
+        let body = &imported.body;
+        let pos: Vec<_> = SECTIONS.iter().map(|s| body.find(&format!("## {}\n", s)).unwrap()).collect();
+        assert!(pos.windows(2).all(|w| w[0] < w[1]));
+        assert!(body.contains("## Overview\n\nIntro paragraph.\n\nWhy."));
+        let constraints = &body[body.find("## Constraints").unwrap()..body.find("## Implementation Notes").unwrap()];
+        assert!(constraints.contains("No UI.") && constraints.contains("Mobile.") && constraints.contains("No sync."));
+        assert!(body.contains("### Indexing") && body.contains("# not a heading") && body.contains("### Glossary"));
+        let tickets = crate::tickets::parse_tickets(body);
+        assert_eq!(tickets.len(), 2);
+        assert_eq!(tickets[1].title, "Ship API");
+        assert!(body.contains("**Summary:** behind a flag"));
+    }
+
+    #[test]
+    fn test_github_issue_and_text() {
+        let json = r#"{"title":"Flaky login","body":"Users get logged out.\n\n## Tasks\n\n- [ ] Add retries","labels":[{"name":"Bug"}],"html_url":"https://github.com/acme/api/issues/7"}"#;
+        assert_eq!(detect(None, json), ImportFormat::GithubIssue);
+        let imported = convert(ImportFormat::Auto, None, json).unwrap();
+        assert_eq!(imported.title.as_deref(), Some("Flaky login"));
+        assert_eq!(imported.tags, vec!["bug"]);
+        assert!(imported.body.starts_with("## Overview\n\nImported from https://github.com/acme/api/issues/7\n\nUsers get logged out."));
+        assert_eq!(crate::tickets::parse_tickets(&imported.body)[0].title, "Add retries");
+
+        let text = convert(ImportFormat::Auto, Some("notes.txt"), "Cache warmup\n\nWarm the cache on deploy.\n").unwrap();
+        assert_eq!(text.title.as_deref(), Some("Cache warmup"));
+        assert!(text.body.starts_with("## Overview\n\nWarm the cache on deploy."));
+        let text = convert(ImportFormat::Text, None, "Warm the cache\non every deploy.\n").unwrap();
+        assert_eq!(text.title, None);
+    }
+}
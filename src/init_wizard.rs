@@ -1,174 +1,385 @@
-//! Interactive init wizard: walk user through configuring agent, skills, and plugins.
+//! Init wizard: walk user through configuring agent, skills, and plugins.
+//!
+//! Every question can be answered up front with [`WizardAnswers`] (from `qp init` flags or
+//! an `--answers` TOML file). Answered questions are not asked; when the wizard is not
+//! interactive the remaining ones take their defaults. Input and output are injected so
+//! the wizard runs against a terminal, a script or a test alike.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use serde::Deserialize;
 use std::collections::HashMap;
-use std::io::{self, BufRead, Write};
+use std::io::{BufRead, Write};
+use std::path::Path;
 
-use crate::config::{ConfigFile, ReviewAgentConfig};
+use crate::agent;
+use crate::config::{ConfigFile, PromptInput, ReviewAgentConfig};
+use crate::plugins::{self, PluginStep};
+use crate::prompts;
 
 const DEFAULT_STEPS: &[&str] = &["holes", "details", "breakdown", "deliverables"];
 
-/// Predefined agent profiles: command and description.
-pub const AGENT_PROFILES: &[(&str, &str, &str)] = &[
-    ("cursor", "agent", "Cursor CLI (agent)"),
-    ("claude", "claude", "Claude CLI"),
-    ("aider", "aider", "Aider"),
+/// A known agent CLI and how qp runs it for optimization steps.
+pub struct AgentTool {
+    /// Profile name, as in `qp init --profile`.
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Executable names to look for on `PATH`, preferred first.
+    pub commands: &'static [&'static str],
+    /// Arguments for a one-shot run: print mode, plain text output, no prompts or edits.
+    pub oneshot_args: &'static [&'static str],
+    pub input: PromptInput,
+}
+
+/// Predefined agent profiles, in the order the wizard offers them.
+pub const AGENT_TOOLS: &[AgentTool] = &[
+    AgentTool {
+        name: "cursor",
+        description: "Cursor CLI",
+        commands: &["agent", "cursor-agent"],
+        oneshot_args: &["-p", "--output-format", "text"],
+        input: PromptInput::Arg,
+    },
+    AgentTool {
+        name: "claude",
+        description: "Claude CLI",
+        commands: &["claude"],
+        oneshot_args: &["-p", "--output-format", "text"],
+        input: PromptInput::Stdin,
+    },
+    AgentTool {
+        name: "aider",
+        description: "Aider",
+        commands: &["aider"],
+        oneshot_args: &["--no-pretty", "--no-stream", "--yes-always", "--no-auto-commits", "--message"],
+        input: PromptInput::Arg,
+    },
 ];
 
-/// Predefined review-agent prompts (step name -> prompt).
-fn default_prompts() -> HashMap<String, String> {
-    let mut m = HashMap::new();
-    m.insert(
-        "holes".to_string(),
-        "Review this plan and identify weaknesses, missing considerations, edge cases, potential failures, and assumptions that need validation.".to_string(),
-    );
-    m.insert(
-        "details".to_string(),
-        "Expand this plan with implementation details. Add specifics about technologies, APIs, data structures, and algorithms.".to_string(),
-    );
-    m.insert(
-        "breakdown".to_string(),
-        "Break this plan into precise, atomic steps. Each step should be independently implementable and testable.".to_string(),
-    );
-    m.insert(
-        "deliverables".to_string(),
-        "Define clear acceptance criteria for each component. What tests must pass? What can be demonstrated? How do we know it's done?".to_string(),
-    );
-    m
+/// The known tool a command runs, matched by executable name (`/usr/bin/claude` is claude).
+pub fn tool_for_command(command: &str) -> Option<&'static AgentTool> {
+    let name = Path::new(command).file_name()?.to_str()?;
+    AGENT_TOOLS.iter().find(|t| t.commands.contains(&name))
 }
 
-/// Optional plugins: extra optimization steps with prompts.
-pub const PLUGINS: &[(&str, &str)] = &[
-    (
-        "risk-check",
-        "Identify risks, failure modes, and mitigation strategies for this plan. Output concrete mitigations.",
-    ),
-    (
-        "strict-deliverables",
-        "For each deliverable, define strict acceptance criteria: automated test or manual check, and a one-sentence definition of done.",
-    ),
-    (
-        "dependencies",
-        "List explicit dependencies between tasks and any external blockers. Output a dependency section for the plan.",
-    ),
-];
+/// For each of [`AGENT_TOOLS`], the first of its commands `installed` accepts.
+pub fn detect_tools(installed: impl Fn(&str) -> bool) -> Vec<Option<&'static str>> {
+    AGENT_TOOLS.iter().map(|t| t.commands.iter().copied().find(|c| installed(c))).collect()
+}
 
-fn read_line(prompt: &str, default: Option<&str>) -> Result<String> {
-    let mut stdout = io::stdout();
-    if let Some(d) = default {
-        write!(stdout, "{} [{}]: ", prompt, d)?;
-    } else {
-        write!(stdout, "{} ", prompt)?;
+/// Answers given ahead of time. `None` means "ask" (or use the default).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WizardAnswers {
+    /// One of [`AGENT_TOOLS`] or `custom`.
+    pub profile: Option<String>,
+    /// Agent command; required for `custom`, overrides the profile's command otherwise.
+    pub command: Option<String>,
+    pub agent_args: Option<Vec<String>>,
+    /// Optimization steps, from the built-in steps.
+    pub steps: Option<Vec<String>>,
+    /// Steps of the built-in plugin pack to add (empty for none).
+    pub plugins: Option<Vec<String>>,
+}
+
+impl WizardAnswers {
+    /// Read an answers file, e.g. `profile = "claude"` / `steps = ["holes", "details"]`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("parse {}", path.display()))
+    }
+
+    /// These answers, with `fallback` filling the gaps (flags over an answers file).
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            profile: self.profile.or(fallback.profile),
+            command: self.command.or(fallback.command),
+            agent_args: self.agent_args.or(fallback.agent_args),
+            steps: self.steps.or(fallback.steps),
+            plugins: self.plugins.or(fallback.plugins),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
     }
-    stdout.flush()?;
-    let mut line = String::new();
-    io::stdin().lock().read_line(&mut line)?;
-    let line = line.trim().to_string();
-    Ok(if line.is_empty() && default.is_some() {
-        default.unwrap().to_string()
-    } else {
-        line
-    })
 }
 
-fn read_line_optional(prompt: &str) -> Result<String> {
-    read_line(prompt, Some(""))
+/// Names from a comma-separated answer, trimmed, without empty entries.
+fn split_names(s: &str) -> Vec<String> {
+    s.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
 }
 
-/// Run the interactive wizard and return a ConfigFile to write.
-pub fn run_wizard(agent_command: &str) -> Result<ConfigFile> {
-    let prompts = default_prompts();
-
-    println!("\n  qp init – configure agent, skills, and plugins\n");
-
-    // 1) Agent command (pre-filled from profile)
-    let cmd = read_line("Agent command", Some(agent_command))?;
-    let cmd = if cmd.is_empty() { agent_command.to_string() } else { cmd };
-
-    // 2) Extra args
-    let args_str = read_line_optional("Extra agent args (space-separated, optional)")?;
-    let args: Vec<String> = args_str
-        .split_whitespace()
-        .map(|s| s.to_string())
-        .collect();
-
-    // 3) Optimization steps
-    println!("\n  Optimization steps: holes, details, breakdown, deliverables");
-    let steps_str = read_line("Use all default steps?", Some("y"))?;
-    let use_default_steps = steps_str.is_empty() || steps_str.eq_ignore_ascii_case("y") || steps_str.eq_ignore_ascii_case("yes");
-    let steps: Vec<String> = if use_default_steps {
-        DEFAULT_STEPS.iter().map(|s| s.to_string()).collect()
-    } else {
-        let custom = read_line("Comma-separated step names (e.g. holes,details,deliverables)", None)?;
-        custom.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
-    };
-
-    // 4) Plugins (extra steps)
-    println!("\n  Optional plugins (add extra review steps):");
-    for (name, desc) in PLUGINS {
-        println!("    - {}: {}", name, desc);
+/// Every step must be one of the built-in prompts.
+fn check_steps(steps: &[String]) -> Result<()> {
+    for step in steps {
+        if prompts::builtin(step).is_none() {
+            let names: Vec<&str> = prompts::BUILTIN.iter().map(|p| p.name).collect();
+            anyhow::bail!("unknown step: {} (built-in steps: {})", step, names.join(", "));
+        }
     }
-    let plugins_str = read_line("Enable plugins (comma-separated names, or 'none')", Some("none"))?;
-    let mut plugin_steps: Vec<String> = vec![];
-    if !plugins_str.is_empty() && !plugins_str.eq_ignore_ascii_case("none") {
-        for name in plugins_str.split(',') {
-            let name = name.trim();
-            if PLUGINS.iter().any(|(n, _)| *n == name) {
-                plugin_steps.push(name.to_string());
-            }
+    Ok(())
+}
+
+/// Steps of `pack` named in `names`; "none" (any case) selects nothing.
+fn plugin_steps<'a>(pack: &'a plugins::Plugin, names: &[String]) -> Result<Vec<&'a PluginStep>> {
+    let available: Vec<&str> = pack.manifest.steps.iter().map(|s| s.name.as_str()).collect();
+    names
+        .iter()
+        .filter(|n| !n.eq_ignore_ascii_case("none"))
+        .map(|name| {
+            pack.step(name)
+                .with_context(|| format!("unknown plugin step: {} (available: {})", name, available.join(", ")))
+        })
+        .collect()
+}
+
+/// Result of the wizard: the chosen profile name and the config to write.
+pub struct WizardResult {
+    pub profile: String,
+    pub config: ConfigFile,
+}
+
+pub struct Wizard<R, W> {
+    input: R,
+    output: W,
+    interactive: bool,
+    /// Installed command per [`AGENT_TOOLS`] entry.
+    installed: Vec<Option<&'static str>>,
+}
+
+impl<R: BufRead, W: Write> Wizard<R, W> {
+    /// A wizard reading answers from `input`. When not `interactive`, nothing is read
+    /// and nothing is printed: unanswered questions take their defaults. Agent CLIs are
+    /// looked up on `PATH`; the first one installed is the default profile.
+    pub fn new(input: R, output: W, interactive: bool) -> Self {
+        let installed = detect_tools(|c| agent::find_command(c).is_some());
+        Self { input, output, interactive, installed }
+    }
+
+    /// Use these detection results instead of searching `PATH`.
+    pub fn with_installed(mut self, installed: Vec<Option<&'static str>>) -> Self {
+        self.installed = installed;
+        self
+    }
+
+    /// Command for a tool: the installed one, else its preferred name.
+    fn tool_command(&self, index: usize) -> &'static str {
+        self.installed.get(index).copied().flatten().unwrap_or(AGENT_TOOLS[index].commands[0])
+    }
+
+    fn say(&mut self, text: &str) -> Result<()> {
+        if self.interactive {
+            writeln!(self.output, "{}", text)?;
         }
+        Ok(())
     }
 
-    // Build review_agents: steps + plugin steps; resolve prompt from default_prompts or PLUGINS
-    let mut review_agents: HashMap<String, ReviewAgentConfig> = HashMap::new();
-    let ra = |_step: &str, prompt: &str| ReviewAgentConfig {
-        command: cmd.clone(),
-        args: args.clone(),
-        prompt: prompt.to_string(),
-    };
-    for step in &steps {
-        let prompt_opt = prompts.get(step)
-            .map(String::as_str)
-            .or_else(|| PLUGINS.iter().find(|(n, _)| *n == step).map(|(_, p)| *p));
-        if let Some(prompt) = prompt_opt {
-            review_agents.insert(step.clone(), ra(step, prompt));
+    fn read_line(&mut self, prompt: &str, default: Option<&str>) -> Result<String> {
+        if !self.interactive {
+            return Ok(default.unwrap_or_default().to_string());
+        }
+        match default {
+            Some(d) => write!(self.output, "{} [{}]: ", prompt, d)?,
+            None => write!(self.output, "{} ", prompt)?,
         }
+        self.output.flush()?;
+        let mut line = String::new();
+        self.input.read_line(&mut line)?;
+        let line = line.trim().to_string();
+        Ok(match default {
+            Some(d) if line.is_empty() => d.to_string(),
+            _ => line,
+        })
     }
-    for step in &plugin_steps {
-        if let Some((_, prompt)) = PLUGINS.iter().find(|(n, _)| *n == step) {
-            review_agents.insert(step.clone(), ra(step, prompt));
+
+    /// Prompt for agent profile and return (profile_name, command).
+    pub fn choose_agent_profile(&mut self, answers: &WizardAnswers) -> Result<(String, String)> {
+        if let Some(name) = &answers.profile {
+            if name == "custom" {
+                let command = answers.command.clone().context("profile \"custom\" needs a command")?;
+                return Ok((name.clone(), command));
+            }
+            let index = AGENT_TOOLS.iter().position(|t| t.name == name).with_context(|| {
+                let names: Vec<&str> = AGENT_TOOLS.iter().map(|t| t.name).collect();
+                format!("unknown profile: {} (expected {} or custom)", name, names.join(", "))
+            })?;
+            let command = answers.command.clone().unwrap_or(self.tool_command(index).to_string());
+            return Ok((name.clone(), command));
+        }
+        self.say("\n  Choose agent profile (used for creating/editing plans and for review steps):")?;
+        for (i, tool) in AGENT_TOOLS.iter().enumerate() {
+            let status = if self.installed[i].is_some() { "installed" } else { "not found" };
+            self.say(&format!("    {}) {} – command: {} ({})", i + 1, tool.description, self.tool_command(i), status))?;
         }
+        self.say(&format!("    {}) Custom – you'll enter the command", AGENT_TOOLS.len() + 1))?;
+
+        let default = self.installed.iter().position(Option::is_some).unwrap_or(0) + 1;
+        let n = self.read_line("Profile", Some(&default.to_string()))?;
+        let n: usize = n.parse().unwrap_or(default);
+        if n >= 1 && n <= AGENT_TOOLS.len() {
+            return Ok((AGENT_TOOLS[n - 1].name.to_string(), self.tool_command(n - 1).to_string()));
+        }
+        let custom = self.read_line("Custom command (e.g. agent, claude, npx -y tsx agent)", Some("agent"))?;
+        Ok(("custom".to_string(), if custom.is_empty() { "agent".to_string() } else { custom }))
     }
 
-    let mut all_steps = steps;
-    all_steps.extend(plugin_steps);
+    /// Run the whole wizard and return the config to write.
+    pub fn run(&mut self, answers: &WizardAnswers) -> Result<WizardResult> {
+        let (profile, agent_command) = self.choose_agent_profile(answers)?;
+        self.say("\n  qp init – configure agent, skills, and plugins\n")?;
 
-    let config = ConfigFile {
-        agent: crate::config::AgentConfig {
-            command: cmd,
-            args,
-        },
-        review_agents,
-        optimization: crate::config::OptimizationConfig { steps: all_steps },
-    };
+        // 1) Agent command (pre-filled from profile; already final when answered)
+        let cmd = if answers.profile.is_some() || answers.command.is_some() {
+            answers.command.clone().unwrap_or(agent_command)
+        } else {
+            let cmd = self.read_line("Agent command", Some(&agent_command))?;
+            if cmd.is_empty() { agent_command } else { cmd }
+        };
 
-    Ok(config)
-}
+        // 2) Extra args
+        let args: Vec<String> = match &answers.agent_args {
+            Some(args) => args.clone(),
+            None => self
+                .read_line("Extra agent args (space-separated, optional)", Some(""))?
+                .split_whitespace()
+                .map(|s| s.to_string())
+                .collect(),
+        };
+
+        // 3) Optimization steps
+        let steps: Vec<String> = match &answers.steps {
+            Some(steps) => {
+                check_steps(steps)?;
+                steps.clone()
+            }
+            None => {
+                self.say("\n  Optimization steps: holes, details, breakdown, deliverables (also available: estimate)")?;
+                let steps_str = self.read_line("Use all default steps?", Some("y"))?;
+                let use_default_steps = steps_str.is_empty() || steps_str.eq_ignore_ascii_case("y") || steps_str.eq_ignore_ascii_case("yes");
+                if use_default_steps {
+                    DEFAULT_STEPS.iter().map(|s| s.to_string()).collect()
+                } else {
+                    loop {
+                        let custom = self.read_line("Comma-separated step names (e.g. holes,details,deliverables)", None)?;
+                        let steps = split_names(&custom);
+                        match check_steps(&steps) {
+                            Ok(()) => break steps,
+                            Err(e) => self.say(&format!("  {}", e))?,
+                        }
+                    }
+                }
+            }
+        };
 
-/// Prompt for agent profile and return (profile_name, command).
-pub fn choose_agent_profile() -> Result<(String, String)> {
-    println!("\n  Choose agent profile (used for creating/editing plans and for review steps):");
-    for (i, (_name, cmd, desc)) in AGENT_PROFILES.iter().enumerate() {
-        println!("    {}) {} – command: {}", i + 1, desc, cmd);
+        // 4) Plugins (extra steps from the built-in pack; `qp plugin` manages others later)
+        let extras = plugins::builtin();
+        let plugin_steps: Vec<&PluginStep> = match &answers.plugins {
+            Some(names) => plugin_steps(&extras, names)?,
+            None => {
+                self.say("\n  Optional plugins (add extra review steps):")?;
+                for step in &extras.manifest.steps {
+                    self.say(&format!("    - {}: {}", step.name, step.description))?;
+                }
+                loop {
+                    let plugins_str = self.read_line("Enable plugins (comma-separated names, or 'none')", Some("none"))?;
+                    match plugin_steps(&extras, &split_names(&plugins_str)) {
+                        Ok(steps) => break steps,
+                        Err(e) => self.say(&format!("  {}", e))?,
+                    }
+                }
+            }
+        };
+
+        // Build review_agents: built-in steps keep their prompt from the prompt library (left
+        // out of config.toml so it stays current); plugin steps get their prompt inline.
+        // A known tool runs in its one-shot mode; `[agent]` stays interactive for qp new/edit.
+        let tool = tool_for_command(&cmd);
+        let mut step_args = args.clone();
+        step_args.extend(tool.map_or(&[][..], |t| t.oneshot_args).iter().map(|a| a.to_string()));
+        let mut review_agents: HashMap<String, ReviewAgentConfig> = HashMap::new();
+        let ra = |prompt: &str| ReviewAgentConfig {
+            command: cmd.clone(),
+            args: step_args.clone(),
+            prompt: prompt.to_string(),
+            prompt_file: None,
+            profile: None,
+            output: Default::default(),
+            section: None,
+            input: tool.map_or(PromptInput::Stdin, |t| t.input),
+            prompt_error: None,
+        };
+        for step in &steps {
+            if prompts::builtin(step).is_some() {
+                review_agents.insert(step.clone(), ra(""));
+            }
+        }
+        let mut all_steps = steps;
+        for step in plugin_steps {
+            review_agents.insert(
+                step.name.clone(),
+                ReviewAgentConfig { output: step.output, section: step.section.clone(), ..ra(&step.prompt) },
+            );
+            plugins::insert_step(&mut all_steps, step);
+        }
+
+        let config = ConfigFile {
+            agent: crate::config::AgentConfig {
+                command: cmd,
+                args,
+                profile: None,
+            },
+            review_agents,
+            optimization: crate::config::OptimizationConfig { steps: all_steps },
+            plans: Default::default(),
+            capacity: Default::default(),
+            tracker: Default::default(),
+            profiles: Default::default(),
+        };
+
+        Ok(WizardResult { profile, config })
     }
-    println!("    {}) Custom – you'll enter the command", AGENT_PROFILES.len() + 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wizard_with_input_and_answers() {
+        let input = "2\n\n--verbose\nn\nholes,estimat\nholes,estimate\nrisk-check, nope\nrisk-check\n";
+        let mut out = vec![];
+        let mut wizard = Wizard::new(input.as_bytes(), &mut out, true).with_installed(vec![None, Some("claude"), None]);
+        let result = wizard.run(&WizardAnswers::default()).unwrap();
+        assert_eq!(result.profile, "claude");
+        let config = result.config;
+        assert_eq!((config.agent.command.as_str(), config.agent.args.clone()), ("claude", vec!["--verbose".to_string()]));
+        assert_eq!(config.review_agents["holes"].args, ["--verbose", "-p", "--output-format", "text"]);
+        assert_eq!(config.review_agents["holes"].input, PromptInput::Stdin);
+        assert_eq!(config.optimization.steps, vec!["holes", "risk-check", "estimate"]);
+        assert!(config.review_agents["holes"].prompt.is_empty() && !config.review_agents["risk-check"].prompt.is_empty());
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("Enable plugins") && out.contains("Profile [2]") && out.contains("agent (not found)"));
+        // Unknown names are reported and asked again.
+        assert!(out.contains("unknown step: estimat (") && out.contains("unknown plugin step: nope ("));
+
+        let file: WizardAnswers = toml::from_str("profile = \"aider\"\nsteps = [\"details\"]\nplugins = []\n").unwrap();
+        let flags = WizardAnswers { agent_args: Some(vec!["--yes".into()]), ..Default::default() };
+        let mut out = vec![];
+        let config = Wizard::new(&b""[..], &mut out, false).run(&flags.or(file)).unwrap().config;
+        assert!(out.is_empty());
+        assert_eq!((config.agent.command.as_str(), config.agent.args[0].as_str()), ("aider", "--yes"));
+        assert_eq!(config.review_agents["details"].args.last().map(String::as_str), Some("--message"));
+        assert_eq!(config.review_agents["details"].input, PromptInput::Arg);
+        assert_eq!(config.optimization.steps, vec!["details"]);
+
+        let installed = detect_tools(|c| c == "cursor-agent" || c == "aider");
+        assert_eq!(installed, vec![Some("cursor-agent"), None, Some("aider")]);
+        let result = Wizard::new(&b""[..], &mut vec![], false).with_installed(installed).run(&WizardAnswers::default()).unwrap();
+        assert_eq!((result.profile.as_str(), result.config.agent.command.as_str()), ("cursor", "cursor-agent"));
+        assert_eq!(tool_for_command("/usr/local/bin/claude").map(|t| t.name), Some("claude"));
 
-    let n = read_line("Profile", Some("1"))?;
-    let n: usize = n.parse().unwrap_or(1);
-    if n >= 1 && n <= AGENT_PROFILES.len() {
-        let (name, cmd, _) = AGENT_PROFILES[n - 1];
-        return Ok((name.to_string(), cmd.to_string()));
+        let bad = WizardAnswers { steps: Some(vec!["nope".into()]), ..Default::default() };
+        assert!(Wizard::new(&b""[..], &mut vec![], false).run(&bad).is_err());
+        assert!(toml::from_str::<WizardAnswers>("profiel = \"claude\"").is_err());
     }
-    let custom = read_line("Custom command (e.g. agent, claude, npx -y tsx agent)", Some("agent"))?;
-    Ok(("custom".to_string(), if custom.is_empty() { "agent".to_string() } else { custom }))
 }
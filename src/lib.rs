@@ -1,6 +1,24 @@
+pub mod changelog;
 pub mod config;
+pub mod config_schema;
 pub mod discovery;
+pub mod error;
+pub mod export;
+pub mod graph;
+pub mod import;
+pub mod output;
 pub mod plan;
+pub mod plugins;
+pub mod prompts;
+pub mod quarter;
+pub mod render;
+pub mod report;
+pub mod schedule;
+pub mod stats;
+pub mod sync;
+pub mod tickets;
+pub mod tracker;
+pub mod upgrade;
 pub mod agent;
 pub mod optimize;
 pub mod init_wizard;
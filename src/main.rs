@@ -1,6 +1,6 @@
 fn main() {
     if let Err(e) = qp::run() {
         eprintln!("error: {:#}", e);
-        std::process::exit(1);
+        std::process::exit(qp::error::exit_code(&e));
     }
 }
@@ -4,7 +4,8 @@ use anyhow::{Context, Result};
 use std::path::Path;
 
 use crate::agent;
-use crate::config::ConfigFile;
+use crate::changelog;
+use crate::config::{ConfigFile, ReviewAgentConfig, StepOutput};
 use crate::plan::{
     self, ensure_review_steps, record_review_step, save_version_snapshot,
     Plan, PlanState,
@@ -21,7 +22,15 @@ pub fn run_step(
         .review_agents
         .get(step_name)
         .with_context(|| format!("unknown step: {}", step_name))?;
+    if let Some(e) = &review_agent.prompt_error {
+        anyhow::bail!("{}", e);
+    }
+    if review_agent.prompt.trim().is_empty() {
+        anyhow::bail!("review_agents.{} has no prompt or prompt_file", step_name);
+    }
+    let invocation = config.step_agent(step_name)?;
     let mut plan = plan::get_plan(qp_root, plan_id)?;
+    let state_before = plan.meta.state;
     plan.meta.state = PlanState::Optimizing;
     plan::save_plan(qp_root, &plan)?;
 
@@ -36,16 +45,22 @@ pub fn run_step(
         None,
     )?;
 
-    let output = agent::run_agent_oneshot(
-        &review_agent.command,
-        &review_agent.args,
-        &review_agent.prompt,
-        &plan_content,
-    )?;
+    let output = match agent::run_agent_oneshot(&invocation, &review_agent.prompt, &plan_content) {
+        Ok(output) => output,
+        Err(e) => {
+            // Record the failure and leave the plan as it was before the step.
+            record_review_step(qp_root, &plan.meta.id, step_name, "failed")?;
+            let mut plan = plan::get_plan(qp_root, &plan.meta.id)?;
+            plan.meta.state = state_before;
+            plan::save_plan(qp_root, &plan)?;
+            return Err(e);
+        }
+    };
 
+    let body_before = plan.body.clone();
     // Agent output may be raw markdown (revised plan) or markdown with frontmatter.
     // If it looks like a full plan (has --- and body), use body only; else append as review notes.
-    plan.body = parse_agent_output(&output, &plan);
+    plan.body = parse_agent_output(&output, &plan, review_agent);
     plan.meta.updated_at = chrono::Utc::now().to_rfc3339();
     plan::save_plan(qp_root, &plan)?;
 
@@ -56,6 +71,15 @@ pub fn run_step(
         &crate::plan::serialize_plan(&plan)?,
         None,
     )?;
+    let changes = changelog::Changes {
+        version: version_before + 1,
+        from_version: version_before,
+        step: Some(step_name.to_string()),
+        agent: Some(invocation.command.clone()),
+        created_at: plan.meta.updated_at.clone(),
+        ..changelog::compute(&body_before, &plan.body)
+    };
+    changelog::save(qp_root, &plan.meta.id, &changes)?;
     record_review_step(qp_root, &plan.meta.id, step_name, "done")?;
 
     let mut plan = plan::get_plan(qp_root, plan_id)?;
@@ -75,12 +99,16 @@ pub fn run_step(
 }
 
 /// If agent returned full plan (frontmatter + body), return its body; else append output as review notes and return combined body.
-fn parse_agent_output(output: &str, fallback_plan: &Plan) -> String {
+fn parse_agent_output(output: &str, fallback_plan: &Plan, agent: &ReviewAgentConfig) -> String {
     let trimmed = output.trim();
-    if trimmed.starts_with("---") {
-        if let Ok(parsed) = plan::parse_plan(trimmed) {
-            return parsed.body;
+    match (agent.output, agent.section.as_deref()) {
+        (StepOutput::Section, Some(section)) => return replace_section(&fallback_plan.body, section, trimmed),
+        (StepOutput::Auto, _) | (StepOutput::Section, None) if trimmed.starts_with("---") => {
+            if let Ok(parsed) = plan::parse_plan(trimmed) {
+                return parsed.body;
+            }
         }
+        _ => {}
     }
     let marker = "## Review Notes";
     let mut body = fallback_plan.body.clone();
@@ -94,6 +122,38 @@ fn parse_agent_output(output: &str, fallback_plan: &Plan) -> String {
     body
 }
 
+/// Put `content` under `## {section}`, replacing what the section held. A missing section
+/// goes before `## Tickets`, or at the end.
+fn replace_section(body: &str, section: &str, content: &str) -> String {
+    let heading = format!("## {}", section);
+    let content = content.strip_prefix(heading.as_str()).unwrap_or(content).trim();
+    let block = format!("{}\n\n{}\n", heading, content);
+    let lines: Vec<&str> = body.lines().collect();
+    let is_heading = |l: &&str| l.starts_with("## ");
+    let (start, end) = match lines.iter().position(|l| l.trim_end().eq_ignore_ascii_case(&heading)) {
+        Some(start) => {
+            let end = lines[start + 1..].iter().position(is_heading).map(|i| start + 1 + i).unwrap_or(lines.len());
+            (start, end)
+        }
+        None => {
+            let at = lines.iter().position(|l| l.trim_end() == "## Tickets").unwrap_or(lines.len());
+            (at, at)
+        }
+    };
+    let mut out = lines[..start].join("\n").trim_end().to_string();
+    if !out.is_empty() {
+        out.push_str("\n\n");
+    }
+    out.push_str(&block);
+    let rest = lines[end..].join("\n");
+    if !rest.trim().is_empty() {
+        out.push('\n');
+        out.push_str(rest.trim_start());
+        out.push('\n');
+    }
+    out
+}
+
 /// Run all optimization steps in order. Skips steps already done unless --force.
 pub fn run_all_steps(
     qp_root: &Path,
@@ -0,0 +1,260 @@
+//! Machine-readable output for the global `--format` flag.
+//!
+//! Every command prints human-readable text by default. With `--format json` or
+//! `--format yaml` it prints one document using the serde schemas below (or the
+//! command's own report types), so scripts and editors never have to scrape text.
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::config::ConfigFile;
+use crate::plan::{PlanMeta, ReviewStepStatus};
+use crate::tickets::{Estimate, EstimateTotals, Step, Ticket};
+
+/// Value of the global `--format` flag. `markdown`, `dot` and `mermaid` are only
+/// accepted by the commands that render them (`qp quarter`, `qp graph`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    #[value(alias = "table")]
+    Text,
+    Json,
+    Yaml,
+    Markdown,
+    Dot,
+    Mermaid,
+}
+
+impl OutputFormat {
+    pub fn is_text(self) -> bool {
+        self == OutputFormat::Text
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Markdown => "markdown",
+            OutputFormat::Dot => "dot",
+            OutputFormat::Mermaid => "mermaid",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Serialize `value` as JSON or YAML.
+pub fn to_string<T: Serialize>(format: OutputFormat, value: &T) -> Result<String> {
+    Ok(match format {
+        OutputFormat::Json => format!("{}\n", serde_json::to_string_pretty(value)?),
+        OutputFormat::Yaml => serde_yaml::to_string(value)?,
+        other => anyhow::bail!("--format {} is not supported by this command (expected text, json or yaml)", other),
+    })
+}
+
+/// Print `value` for `--format json|yaml` and return true; return false for text so the
+/// caller prints its usual output.
+pub fn emit<T: Serialize>(format: OutputFormat, value: &T) -> Result<bool> {
+    if format.is_text() {
+        return Ok(false);
+    }
+    print!("{}", to_string(format, value)?);
+    Ok(true)
+}
+
+/// Progress message: stdout for text output, stderr otherwise so JSON/YAML stays parseable.
+pub fn note(format: OutputFormat, message: &str) {
+    if format.is_text() {
+        println!("{}", message);
+    } else {
+        eprintln!("{}", message);
+    }
+}
+
+/// A plan in `qp list` / `qp status` / `qp trash list`.
+#[derive(Debug, Serialize)]
+pub struct PlanEntry {
+    #[serde(flatten)]
+    pub meta: PlanMeta,
+    pub estimate: Option<EstimateTotals>,
+}
+
+/// Result of a command that changes one plan (`qp set`, `qp approve`, `qp archive`, ...).
+#[derive(Debug, Serialize)]
+pub struct PlanChange {
+    pub action: &'static str,
+    pub plan: PlanMeta,
+    pub path: Option<String>,
+}
+
+/// `qp show --format json`.
+#[derive(Debug, Serialize)]
+pub struct PlanDocument {
+    pub meta: PlanMeta,
+    pub body: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TicketSummary {
+    pub id: String,
+    pub title: String,
+    pub done: bool,
+    pub estimate: Option<Estimate>,
+    pub estimate_hours: Option<f64>,
+    pub depends_on: Vec<String>,
+    pub steps: Vec<Step>,
+}
+
+impl TicketSummary {
+    pub fn new(t: &Ticket, capacity: &crate::config::CapacityConfig) -> Self {
+        Self {
+            id: t.id.clone(),
+            title: t.title.clone(),
+            done: t.is_done(),
+            estimate: t.estimate(),
+            estimate_hours: t.estimate_hours(capacity),
+            depends_on: t.depends_on(),
+            steps: t.steps.clone(),
+        }
+    }
+}
+
+/// `qp show --summary --format json`.
+#[derive(Debug, Serialize)]
+pub struct PlanSummary {
+    pub meta: PlanMeta,
+    pub tickets: Vec<TicketSummary>,
+    pub estimate: EstimateTotals,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Review {
+    pub id: String,
+    pub title: String,
+    pub review_cycles: u32,
+    pub review_steps: Vec<ReviewStepStatus>,
+    pub history_dir: Option<String>,
+    /// What each optimization step changed, oldest first.
+    pub changes: Vec<crate::changelog::Changes>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistoryEntry {
+    pub version: Option<u32>,
+    pub file: String,
+    pub modified_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct History {
+    pub id: String,
+    pub title: String,
+    pub versions: Vec<HistoryEntry>,
+}
+
+/// `qp config get|set|unset --format json`.
+#[derive(Debug, Serialize)]
+pub struct ConfigValue {
+    pub key: String,
+    pub value: Option<String>,
+    pub scope: Option<String>,
+    pub path: Option<String>,
+}
+
+/// One entry of `qp config --explain --format json`.
+#[derive(Debug, Serialize)]
+pub struct ExplainedValue {
+    pub key: String,
+    pub value: String,
+    pub source: crate::config::ConfigSource,
+}
+
+/// `qp config --format json`: the merged config plus the files it came from.
+#[derive(Debug, Serialize)]
+pub struct ResolvedConfig {
+    pub config: ConfigFile,
+    pub global_path: Option<String>,
+    pub local_path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Graph {
+    pub name: String,
+    pub nodes: Vec<crate::graph::Node>,
+    pub edges: Vec<GraphEdge>,
+    pub unresolved: Vec<GraphEdge>,
+    pub cycle: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LayoutMove {
+    pub title: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Top-level field names of `value` serialized as JSON, sorted.
+    fn keys<T: Serialize>(value: &T) -> Vec<String> {
+        let v = serde_json::to_value(value).unwrap();
+        let mut keys: Vec<String> = v.as_object().expect("an object").keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// Scripts rely on these names; renaming a field is a breaking change.
+    #[test]
+    fn test_json_field_names_are_stable() {
+        let plan = crate::plan::parse_plan("---\nid: p1\ntitle: Search\nstate: draft\ncreated_at: a\nupdated_at: b\n---\n\n## Tickets\n\n### Ticket 1: A\n\n**Estimate:** 2h\n\n#### Steps\n\n1. **Do** (Estimate: 1h)\n").unwrap();
+        let capacity = crate::config::CapacityConfig::default();
+        let tickets = crate::tickets::parse_tickets(&plan.body);
+        let totals = crate::tickets::estimate_totals(&tickets, &capacity);
+        let meta = &plan.meta;
+        assert_eq!(keys(meta), ["created_at", "id", "review_cycles", "review_steps", "state", "title", "updated_at"]);
+        let entry = PlanEntry { meta: meta.clone(), estimate: Some(totals.clone()) };
+        assert_eq!(keys(&entry), ["created_at", "estimate", "id", "review_cycles", "review_steps", "state", "title", "updated_at"]);
+        assert_eq!(keys(&totals), ["estimated", "hours", "points", "tickets"]);
+
+        assert_eq!(keys(&PlanDocument { meta: meta.clone(), body: plan.body.clone() }), ["body", "meta"]);
+        let ticket = TicketSummary::new(&tickets[0], &capacity);
+        assert_eq!(keys(&ticket), ["depends_on", "done", "estimate", "estimate_hours", "id", "steps", "title"]);
+        assert_eq!(keys(&ticket.steps[0]), ["estimate", "number", "title"]);
+        assert_eq!(keys(&ticket.estimate.unwrap()), ["unit", "value"]);
+        assert_eq!(keys(&PlanSummary { meta: meta.clone(), tickets: vec![ticket], estimate: totals }), ["estimate", "meta", "tickets"]);
+
+        let step = ReviewStepStatus { step: "holes".into(), status: "done".into(), completed_at: Some("c".into()) };
+        assert_eq!(keys(&step), ["completed_at", "status", "step"]);
+        let changes = crate::changelog::compute("a", "a b");
+        let review = Review { id: "p1".into(), title: "Search".into(), review_cycles: 1, review_steps: vec![step], history_dir: None, changes: vec![changes] };
+        assert_eq!(keys(&review), ["changes", "history_dir", "id", "review_cycles", "review_steps", "title"]);
+
+        let history = History { id: "p1".into(), title: "Search".into(), versions: vec![] };
+        assert_eq!(keys(&history), ["id", "title", "versions"]);
+        assert_eq!(keys(&HistoryEntry { version: Some(1), file: "v1.md".into(), modified_at: None }), ["file", "modified_at", "version"]);
+
+        assert_eq!(
+            keys(&crate::stats::Stats::default()),
+            [
+                "agents", "avg_days_in_state", "avg_optimizations", "by_state", "completed", "generated_at",
+                "plans_with_history", "quarters", "steps", "total", "with_optimization", "words_draft", "words_final",
+            ]
+        );
+        assert_eq!(keys(&crate::stats::StepStats::default()), ["failed", "runs", "words_added"]);
+
+        assert_eq!(keys(&ConfigValue { key: "k".into(), value: None, scope: None, path: None }), ["key", "path", "scope", "value"]);
+        let resolved = ResolvedConfig { config: ConfigFile::default(), global_path: None, local_path: None };
+        assert_eq!(keys(&resolved), ["config", "global_path", "local_path"]);
+        assert_eq!(keys(&resolved.config), ["agent", "capacity", "optimization", "plans", "review_agents", "tracker"]);
+    }
+}
@@ -1,12 +1,16 @@
 //! Plan model: frontmatter, body, CRUD, and .qp directory init.
 
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use colored::Colorize;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+use crate::error::QpError;
+use crate::quarter::Quarter;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum PlanState {
@@ -46,6 +50,47 @@ impl std::str::FromStr for PlanState {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    Critical,
+    High,
+    Medium,
+    Low,
+}
+
+/// Accepts everything `FromStr` does, e.g. `High` or `P1` in hand-written frontmatter.
+impl<'de> Deserialize<'de> for Priority {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Priority::Critical => write!(f, "critical"),
+            Priority::High => write!(f, "high"),
+            Priority::Medium => write!(f, "medium"),
+            Priority::Low => write!(f, "low"),
+        }
+    }
+}
+
+impl std::str::FromStr for Priority {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "critical" | "p0" => Ok(Priority::Critical),
+            "high" | "p1" => Ok(Priority::High),
+            "medium" | "p2" => Ok(Priority::Medium),
+            "low" | "p3" => Ok(Priority::Low),
+            _ => anyhow::bail!("unknown priority: {} (expected critical|high|medium|low or p0-p3)", s),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReviewStepStatus {
     pub step: String,
@@ -69,6 +114,182 @@ pub struct PlanMeta {
     pub agent: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub review_agents: Option<HashMap<String, String>>,
+    /// URL-safe name derived from the title when the plan was created or renamed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    /// Id of the plan this one was cloned from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cloned_from: Option<String>,
+    /// When the plan was moved to .qp/archive/.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<String>,
+    /// When the plan was moved to .qp/trash/.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
+    /// Planning quarter, e.g. `2026-Q4`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarter: Option<Quarter>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    /// Ids of plans that must be completed before this one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// State transitions, recorded by qp whenever a plan is saved in a new state.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub state_history: Vec<StateChange>,
+}
+
+/// One entry of `state_history`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateChange {
+    pub from: PlanState,
+    pub to: PlanState,
+    pub at: String,
+}
+
+/// Frontmatter keys settable with `qp set <plan> key=value`.
+pub const SETTABLE_FIELDS: &[&str] = &["tags", "owners", "quarter", "priority", "due_date", "team", "depends_on"];
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+impl PlanMeta {
+    /// Set one categorization field from a string. An empty value clears the field;
+    /// list fields (`tags`, `owners`, `depends_on`) take comma-separated values.
+    pub fn set_field(&mut self, key: &str, value: &str) -> Result<()> {
+        let value = value.trim();
+        let opt = |v: &str| if v.is_empty() { None } else { Some(v.to_string()) };
+        match key {
+            "tags" => self.tags = split_list(value),
+            "owners" => self.owners = split_list(value),
+            "quarter" => self.quarter = opt(value).map(|v| v.parse()).transpose()?,
+            "priority" => self.priority = opt(value).map(|v| v.parse()).transpose()?,
+            "due_date" => {
+                self.due_date = opt(value)
+                    .map(|v| NaiveDate::parse_from_str(&v, "%Y-%m-%d"))
+                    .transpose()
+                    .with_context(|| format!("invalid due_date: {} (expected YYYY-MM-DD)", value))?
+            }
+            "team" => self.team = opt(value),
+            "depends_on" => self.depends_on = split_list(value),
+            _ => anyhow::bail!("unknown field: {} (settable: {})", key, SETTABLE_FIELDS.join(", ")),
+        }
+        Ok(())
+    }
+}
+
+/// Filters for `qp list`. Empty fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct PlanFilter {
+    pub tag: Option<String>,
+    pub quarter: Option<Quarter>,
+    pub owner: Option<String>,
+    pub state: Option<PlanState>,
+}
+
+impl PlanFilter {
+    pub fn matches(&self, meta: &PlanMeta) -> bool {
+        self.tag.as_ref().is_none_or(|t| meta.tags.iter().any(|x| x.eq_ignore_ascii_case(t)))
+            && self.quarter.is_none_or(|q| meta.quarter == Some(q))
+            && self.owner.as_ref().is_none_or(|o| meta.owners.iter().any(|x| x.eq_ignore_ascii_case(o)))
+            && self.state.is_none_or(|s| meta.state == s)
+    }
+}
+
+/// Sort orders for `qp list --sort`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlanSort {
+    /// Most recently updated first (default)
+    #[default]
+    Updated,
+    Created,
+    Title,
+    /// Critical first; plans without a priority last
+    Priority,
+    /// Earliest due date first; plans without one last
+    Due,
+    /// Earliest quarter first; plans without one last
+    Quarter,
+}
+
+impl std::str::FromStr for PlanSort {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "updated" => Ok(PlanSort::Updated),
+            "created" => Ok(PlanSort::Created),
+            "title" => Ok(PlanSort::Title),
+            "priority" => Ok(PlanSort::Priority),
+            "due" | "due_date" => Ok(PlanSort::Due),
+            "quarter" => Ok(PlanSort::Quarter),
+            _ => anyhow::bail!("unknown sort: {} (expected updated|created|title|priority|due|quarter)", s),
+        }
+    }
+}
+
+/// Sort plan metadata in place. Ties keep most recently updated first.
+pub fn sort_plans(plans: &mut [PlanMeta], sort: PlanSort) {
+    // Missing values sort last: compare (is_none, value).
+    fn last<T: Ord + Copy>(v: Option<T>) -> (bool, Option<T>) {
+        (v.is_none(), v)
+    }
+    plans.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    match sort {
+        PlanSort::Updated => {}
+        PlanSort::Created => plans.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+        PlanSort::Title => plans.sort_by_key(|m| m.title.to_lowercase()),
+        PlanSort::Priority => plans.sort_by_key(|m| last(m.priority)),
+        PlanSort::Due => plans.sort_by_key(|m| last(m.due_date)),
+        PlanSort::Quarter => plans.sort_by_key(|m| last(m.quarter)),
+    }
+}
+
+/// How plan directories under `.qp/plans/` are named.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PlanDirNaming {
+    /// `<uuid>` (original layout)
+    #[default]
+    Uuid,
+    /// `<YYYY-MM-DD>-<slug>` using the creation date
+    DateSlug,
+    /// `<NNN>-<slug>` using a per-project sequence number
+    SeqSlug,
+}
+
+impl std::fmt::Display for PlanDirNaming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanDirNaming::Uuid => write!(f, "uuid"),
+            PlanDirNaming::DateSlug => write!(f, "date-slug"),
+            PlanDirNaming::SeqSlug => write!(f, "seq-slug"),
+        }
+    }
+}
+
+impl std::str::FromStr for PlanDirNaming {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uuid" => Ok(PlanDirNaming::Uuid),
+            "date-slug" => Ok(PlanDirNaming::DateSlug),
+            "seq-slug" => Ok(PlanDirNaming::SeqSlug),
+            _ => anyhow::bail!("unknown plan naming: {} (expected uuid, date-slug or seq-slug)", s),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -107,9 +328,42 @@ pub fn serialize_plan(plan: &Plan) -> Result<String> {
     Ok(format!("---\n{}\n---\n\n{}", front.trim(), plan.body))
 }
 
+/// Where a plan directory lives inside .qp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanLocation {
+    /// `.qp/plans/`: shown by `qp list`
+    Active,
+    /// `.qp/archive/`: hidden from `qp list`, still found by id or slug
+    Archived,
+    /// `.qp/trash/`: deleted plans, recoverable until the trash is emptied
+    Trashed,
+}
+
+impl PlanLocation {
+    /// Directory under .qp holding plans in this location.
+    pub fn dir(self, qp_root: &Path) -> PathBuf {
+        qp_root.join(match self {
+            PlanLocation::Active => "plans",
+            PlanLocation::Archived => "archive",
+            PlanLocation::Trashed => "trash",
+        })
+    }
+}
+
 /// List plan IDs and their metadata for a .qp root.
 pub fn list_plans(qp_root: &Path) -> Result<Vec<PlanMeta>> {
-    let plans_dir = qp_root.join("plans");
+    list_plans_in(qp_root, PlanLocation::Active)
+}
+
+/// List plan metadata in one location (active plans, archive or trash), most recently updated first.
+pub fn list_plans_in(qp_root: &Path, location: PlanLocation) -> Result<Vec<PlanMeta>> {
+    Ok(load_plans_in(qp_root, location)?.into_iter().map(|p| p.meta).collect())
+}
+
+/// Load every plan (metadata and body) in one location, most recently updated first.
+/// Unparsable plans are skipped with a warning.
+pub fn load_plans_in(qp_root: &Path, location: PlanLocation) -> Result<Vec<Plan>> {
+    let plans_dir = location.dir(qp_root);
     if !plans_dir.exists() {
         return Ok(vec![]);
     }
@@ -121,20 +375,39 @@ pub fn list_plans(qp_root: &Path) -> Result<Vec<PlanMeta>> {
             let plan_md = path.join("plan.md");
             if plan_md.exists() {
                 let content = std::fs::read_to_string(&plan_md).context("read plan.md")?;
-                if let Ok(plan) = parse_plan(&content) {
-                    out.push(plan.meta);
+                match parse_plan(&content) {
+                    Ok(plan) => out.push(plan),
+                    Err(e) => eprintln!("{} skipping {}: {:#}", "warning:".yellow(), plan_md.display(), e),
                 }
             }
         }
     }
-    out.sort_by(|a, b| a.updated_at.cmp(&b.updated_at).reverse());
+    out.sort_by(|a, b| a.meta.updated_at.cmp(&b.meta.updated_at).reverse());
     Ok(out)
 }
 
 /// Load a single plan by id or by title slug (or title itself).
+/// Active plans are searched first, then archived ones.
 pub fn get_plan(qp_root: &Path, id_or_slug: &str) -> Result<Plan> {
+    for location in [PlanLocation::Active, PlanLocation::Archived] {
+        if let Some((_, plan)) = find_plan_in(qp_root, location, id_or_slug)? {
+            return Ok(plan);
+        }
+    }
+    Err(QpError::PlanNotFound(id_or_slug.to_string()).into())
+}
+
+/// Find a plan by id, slug or title in one location. Returns its directory and contents.
+pub fn find_plan_in(
+    qp_root: &Path,
+    location: PlanLocation,
+    id_or_slug: &str,
+) -> Result<Option<(PathBuf, Plan)>> {
     let slug_input = title_to_slug(id_or_slug);
-    let plans_dir = qp_root.join("plans");
+    let plans_dir = location.dir(qp_root);
+    if !plans_dir.exists() {
+        return Ok(None);
+    }
     for e in std::fs::read_dir(&plans_dir).context("read plans dir")? {
         let e = e?;
         let path = e.path();
@@ -144,18 +417,27 @@ pub fn get_plan(qp_root: &Path, id_or_slug: &str) -> Result<Plan> {
                 let content = std::fs::read_to_string(&plan_md).context("read plan.md")?;
                 let plan = parse_plan(&content)?;
                 let match_id = plan.meta.id == id_or_slug;
-                let slug = title_to_slug(&plan.meta.title);
+                let slug = plan_slug(&plan.meta);
                 let match_slug = slug == id_or_slug || slug == slug_input || plan.meta.title == id_or_slug;
                 if match_id || match_slug {
-                    return Ok(plan);
+                    return Ok(Some((path, plan)));
                 }
             }
         }
     }
-    anyhow::bail!("plan not found: {}", id_or_slug)
+    Ok(None)
 }
 
-fn title_to_slug(title: &str) -> String {
+/// Slug for a plan: the persisted frontmatter slug, or one derived from the title for older plans.
+pub fn plan_slug(meta: &PlanMeta) -> String {
+    match &meta.slug {
+        Some(s) if !s.is_empty() => s.clone(),
+        _ => title_to_slug(&meta.title),
+    }
+}
+
+/// Lowercase, dash-separated ASCII slug for a title.
+pub fn title_to_slug(title: &str) -> String {
     title
         .to_lowercase()
         .chars()
@@ -176,16 +458,16 @@ pub fn plan_format_instructions(
 ) -> String {
     format!(
         r#"Write or edit the plan in this file only: {}
-Keep this plan id and title in frontmatter: id: "{}", title: "{}".
+Keep this plan id, title and slug in frontmatter: id: "{}", title: "{}". (The user can change the title with `qp rename`.)
 
 Required format (see .qp/plan-format.md in the project for the full spec):
-1. YAML frontmatter between --- lines with: id, title, state (snake_case: draft|approved|optimizing|ready|in_progress|completed), created_at, updated_at (RFC3339). Optional: review_cycles, review_steps, agent, review_agents.
+1. YAML frontmatter between --- lines with: id, title, state (snake_case: draft|approved|optimizing|ready|in_progress|completed), created_at, updated_at (RFC3339). Optional: slug, cloned_from, review_cycles, review_steps, agent, review_agents, tags, owners, quarter, priority, due_date, team, depends_on, state_history (keep any that are present).
 2. Body with exactly these ## sections (order and spelling matter for qp):
    - Overview
    - Constraints
    - Implementation Notes
    - Review Notes
-   - Tickets (each ticket as subheading with Summary and Definition of Done underneath)
+   - Tickets (each ticket as subheading with Summary and Definition of Done underneath; optional `**Depends on:** Ticket N` for prerequisites and `**Estimate:** 4h` / `2d`)
 
 When the user is ready for the full plan, output the complete content and tell them to save to the path above."#,
         plan_md_path.display(),
@@ -194,9 +476,131 @@ When the user is ready for the full plan, output the complete content and tell t
     )
 }
 
-/// Path to plan directory.
+/// Path to plan directory. Plans may live in `plans/<id>` or in a named directory
+/// (see `PlanDirNaming`); the latter is found by matching the id in each plan.md.
+/// Archived plans resolve to their directory under `archive/`.
 pub fn plan_dir(qp_root: &Path, plan_id: &str) -> PathBuf {
-    qp_root.join("plans").join(plan_id)
+    let direct = qp_root.join("plans").join(plan_id);
+    if direct.join("plan.md").exists() {
+        return direct;
+    }
+    find_plan_dir(qp_root, PlanLocation::Active, plan_id)
+        .or_else(|| find_plan_dir(qp_root, PlanLocation::Archived, plan_id))
+        .unwrap_or(direct)
+}
+
+fn find_plan_dir(qp_root: &Path, location: PlanLocation, plan_id: &str) -> Option<PathBuf> {
+    let entries = std::fs::read_dir(location.dir(qp_root)).ok()?;
+    for e in entries.flatten() {
+        let path = e.path();
+        let Ok(content) = std::fs::read_to_string(path.join("plan.md")) else {
+            continue;
+        };
+        if let Ok(plan) = parse_plan(&content) {
+            if plan.meta.id == plan_id {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+/// Directory name for a plan under the given naming scheme.
+/// `seq` is only used by `PlanDirNaming::SeqSlug`.
+pub fn plan_dir_name(meta: &PlanMeta, naming: PlanDirNaming, seq: u32) -> String {
+    let slug = plan_slug(meta);
+    let slug = if slug.is_empty() { "plan".to_string() } else { slug };
+    match naming {
+        PlanDirNaming::Uuid => meta.id.clone(),
+        PlanDirNaming::DateSlug => {
+            let date = chrono::DateTime::parse_from_rfc3339(&meta.created_at)
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|_| Utc::now().format("%Y-%m-%d").to_string());
+            format!("{}-{}", date, slug)
+        }
+        PlanDirNaming::SeqSlug => format!("{:03}-{}", seq, slug),
+    }
+}
+
+/// Leading sequence number of a `<NNN>-<slug>` directory name.
+fn seq_prefix(dir_name: &str) -> Option<u32> {
+    let (num, rest) = dir_name.split_once('-')?;
+    if rest.is_empty() || num.len() < 3 || !num.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    num.parse().ok()
+}
+
+/// Next free sequence number for `PlanDirNaming::SeqSlug`.
+fn next_seq(qp_root: &Path) -> u32 {
+    let max = std::fs::read_dir(qp_root.join("plans"))
+        .map(|rd| {
+            rd.flatten()
+                .filter_map(|e| seq_prefix(&e.file_name().to_string_lossy()))
+                .max()
+                .unwrap_or(0)
+        })
+        .unwrap_or(0);
+    max + 1
+}
+
+/// Pick a directory named `name` under `parent`, suffixing the short id if another plan already uses it.
+fn available_plan_dir(parent: &Path, name: &str, plan_id: &str) -> PathBuf {
+    let dir = parent.join(name);
+    if !dir.exists() {
+        return dir;
+    }
+    let short: String = plan_id.chars().take(8).collect();
+    parent.join(format!("{}-{}", name, short))
+}
+
+/// Move a plan's directory to match `naming` (e.g. after a title change or layout migration).
+/// For `SeqSlug`, `seq` forces a number; otherwise an existing prefix is kept or the next one is used.
+/// Returns the plan's directory after the move.
+pub fn relocate_plan(
+    qp_root: &Path,
+    meta: &PlanMeta,
+    naming: PlanDirNaming,
+    seq: Option<u32>,
+) -> Result<PathBuf> {
+    let current = plan_dir(qp_root, &meta.id);
+    let current_name = current
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let seq = seq
+        .or_else(|| seq_prefix(&current_name))
+        .unwrap_or_else(|| next_seq(qp_root));
+    let name = plan_dir_name(meta, naming, seq);
+    if name == current_name {
+        return Ok(current);
+    }
+    let parent = current.parent().map(Path::to_path_buf).unwrap_or_else(|| PlanLocation::Active.dir(qp_root));
+    let target = available_plan_dir(&parent, &name, &meta.id);
+    std::fs::rename(&current, &target)
+        .with_context(|| format!("move {} to {}", current.display(), target.display()))?;
+    Ok(target)
+}
+
+/// Convert every plan under plans/ to `naming`, persisting slugs for older plans.
+/// Sequence numbers follow creation order. Returns (title, old dir, new dir) for moved plans.
+pub fn migrate_layout(qp_root: &Path, naming: PlanDirNaming) -> Result<Vec<(String, PathBuf, PathBuf)>> {
+    let mut plans = list_plans(qp_root)?;
+    plans.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    let mut moved = vec![];
+    for (i, meta) in plans.into_iter().enumerate() {
+        let mut plan = get_plan(qp_root, &meta.id)?;
+        if plan.meta.slug.is_none() {
+            plan.meta.slug = Some(title_to_slug(&plan.meta.title));
+            save_plan(qp_root, &plan)?;
+        }
+        let before = plan_dir(qp_root, &plan.meta.id);
+        let after = relocate_plan(qp_root, &plan.meta, naming, Some(i as u32 + 1))?;
+        if before != after {
+            moved.push((plan.meta.title.clone(), before, after));
+        }
+    }
+    Ok(moved)
 }
 
 /// Path to plan.md.
@@ -204,21 +608,52 @@ pub fn plan_md_path(qp_root: &Path, plan_id: &str) -> PathBuf {
     plan_dir(qp_root, plan_id).join("plan.md")
 }
 
-/// Save plan to .qp/plans/<id>/plan.md.
+/// Write plan.md into the plan's directory (see `plan_dir`). A state different from the one
+/// on disk is appended to `state_history`, and a shorter in-memory history is replaced by the
+/// one on disk first.
 pub fn save_plan(qp_root: &Path, plan: &Plan) -> Result<()> {
     let dir = plan_dir(qp_root, &plan.meta.id);
-    std::fs::create_dir_all(&dir).context("create plan dir")?;
-    let path = plan_md_path(qp_root, &plan.meta.id);
+    let on_disk = std::fs::read_to_string(dir.join("plan.md"))
+        .ok()
+        .and_then(|s| parse_plan(&s).ok())
+        .map(|p| p.meta);
+    match on_disk {
+        Some(old) if old.state != plan.meta.state || old.state_history.len() > plan.meta.state_history.len() => {
+            let mut plan = plan.clone();
+            if old.state_history.len() > plan.meta.state_history.len() {
+                plan.meta.state_history = old.state_history;
+            }
+            if old.state != plan.meta.state {
+                plan.meta.state_history.push(StateChange {
+                    from: old.state,
+                    to: plan.meta.state,
+                    at: Utc::now().to_rfc3339(),
+                });
+            }
+            write_plan_to(&dir, &plan)
+        }
+        _ => write_plan_to(&dir, plan),
+    }
+}
+
+fn write_plan_to(dir: &Path, plan: &Plan) -> Result<()> {
+    std::fs::create_dir_all(dir).context("create plan dir")?;
     let s = serialize_plan(plan)?;
-    std::fs::write(&path, s).context("write plan.md")?;
+    std::fs::write(dir.join("plan.md"), s).context("write plan.md")?;
     Ok(())
 }
 
-/// Create a new plan with default template. Returns the new plan.
-pub fn create_plan(qp_root: &Path, title: Option<&str>) -> Result<Plan> {
+/// Create a new plan with default template in a directory named per `naming`. Returns the new plan.
+pub fn create_plan(qp_root: &Path, title: Option<&str>, naming: PlanDirNaming) -> Result<Plan> {
+    create_plan_with_body(qp_root, title, &default_plan_body(), naming)
+}
+
+/// Like [`create_plan`], with the given body instead of the template (used by `qp import`).
+pub fn create_plan_with_body(qp_root: &Path, title: Option<&str>, body: &str, naming: PlanDirNaming) -> Result<Plan> {
     ensure_qp_structure(qp_root)?;
     let id = Uuid::new_v4().to_string();
     let title = title.unwrap_or("Untitled Plan").to_string();
+    let slug = title_to_slug(&title);
     let now = Utc::now().to_rfc3339();
     let meta = PlanMeta {
         id: id.clone(),
@@ -230,10 +665,22 @@ pub fn create_plan(qp_root: &Path, title: Option<&str>) -> Result<Plan> {
         review_steps: vec![],
         agent: None,
         review_agents: None,
+        slug: Some(slug),
+        cloned_from: None,
+        archived_at: None,
+        deleted_at: None,
+        tags: vec![],
+        owners: vec![],
+        quarter: None,
+        priority: None,
+        due_date: None,
+        team: None,
+        depends_on: vec![],
+        state_history: vec![],
     };
-    let body = default_plan_body();
-    let plan = Plan { meta, body };
-    save_plan(qp_root, &plan)?;
+    let plan = Plan { meta, body: body.to_string() };
+    let name = plan_dir_name(&plan.meta, naming, next_seq(qp_root));
+    write_plan_to(&available_plan_dir(&PlanLocation::Active.dir(qp_root), &name, &plan.meta.id), &plan)?;
     Ok(plan)
 }
 
@@ -241,6 +688,141 @@ fn default_plan_body() -> String {
     "## Ideas\n\n(Add goals and scope here. When ready, have the agent write the full plan.)".to_string()
 }
 
+/// Change a plan's title and slug, moving its directory to match `naming`.
+pub fn rename_plan(
+    qp_root: &Path,
+    id_or_slug: &str,
+    new_title: &str,
+    naming: PlanDirNaming,
+) -> Result<Plan> {
+    let new_title = new_title.trim();
+    if new_title.is_empty() {
+        anyhow::bail!("new title must not be empty");
+    }
+    let mut plan = get_plan(qp_root, id_or_slug)?;
+    let slug = title_to_slug(new_title);
+    let others: Vec<PlanMeta> = list_plans(qp_root)?.into_iter().filter(|m| m.id != plan.meta.id).collect();
+    check_slug_free(&others, &slug)?;
+    plan.meta.title = new_title.to_string();
+    plan.meta.slug = Some(slug);
+    plan.meta.updated_at = Utc::now().to_rfc3339();
+    save_plan(qp_root, &plan)?;
+    relocate_plan(qp_root, &plan.meta, naming, None)?;
+    Ok(plan)
+}
+
+/// Fail when one of `plans` already uses `slug`.
+fn check_slug_free(plans: &[PlanMeta], slug: &str) -> Result<()> {
+    match plans.iter().find(|m| plan_slug(m) == slug) {
+        Some(other) => anyhow::bail!("slug \"{}\" is already used by plan {} ({})", slug, other.title, other.id),
+        None => Ok(()),
+    }
+}
+
+/// Copy a plan's body into a new draft with a fresh id and reset review progress.
+/// The title defaults to "<original title> (copy)", or "(copy 2)" and so on when that
+/// slug is taken; an explicit title must have a free slug.
+pub fn clone_plan(
+    qp_root: &Path,
+    id_or_slug: &str,
+    title: Option<&str>,
+    naming: PlanDirNaming,
+) -> Result<Plan> {
+    let source = get_plan(qp_root, id_or_slug)?;
+    let plans = list_plans(qp_root)?;
+    let title = match title {
+        Some(t) if !t.trim().is_empty() => {
+            check_slug_free(&plans, &title_to_slug(t.trim()))?;
+            t.trim().to_string()
+        }
+        _ => (1..)
+            .map(|n| match n {
+                1 => format!("{} (copy)", source.meta.title),
+                n => format!("{} (copy {})", source.meta.title, n),
+            })
+            .find(|t| check_slug_free(&plans, &title_to_slug(t)).is_ok())
+            .expect("some copy number is free"),
+    };
+    let now = Utc::now().to_rfc3339();
+    let meta = PlanMeta {
+        id: Uuid::new_v4().to_string(),
+        slug: Some(title_to_slug(&title)),
+        title,
+        state: PlanState::Draft,
+        created_at: now.clone(),
+        updated_at: now,
+        review_cycles: 0,
+        review_steps: vec![],
+        cloned_from: Some(source.meta.id.clone()),
+        archived_at: None,
+        deleted_at: None,
+        state_history: vec![],
+        ..source.meta
+    };
+    let plan = Plan { meta, body: source.body };
+    let name = plan_dir_name(&plan.meta, naming, next_seq(qp_root));
+    write_plan_to(&available_plan_dir(&PlanLocation::Active.dir(qp_root), &name, &plan.meta.id), &plan)?;
+    Ok(plan)
+}
+
+/// Set categorization fields (see `SETTABLE_FIELDS`) from `key=value` pairs and save.
+pub fn set_plan_fields(qp_root: &Path, id_or_slug: &str, assignments: &[(String, String)]) -> Result<Plan> {
+    let mut plan = get_plan(qp_root, id_or_slug)?;
+    for (key, value) in assignments {
+        plan.meta.set_field(key, value)?;
+    }
+    if assignments.iter().any(|(k, _)| k == "depends_on") {
+        resolve_plan_dependencies(qp_root, &mut plan.meta)?;
+    }
+    plan.meta.updated_at = Utc::now().to_rfc3339();
+    save_plan(qp_root, &plan)?;
+    Ok(plan)
+}
+
+/// Replace `depends_on` references (ids, slugs or titles of active or archived plans) with
+/// plan ids and reject cycles.
+fn resolve_plan_dependencies(qp_root: &Path, meta: &mut PlanMeta) -> Result<()> {
+    let mut others: Vec<PlanMeta> = list_plans(qp_root)?
+        .into_iter()
+        .chain(list_plans_in(qp_root, PlanLocation::Archived)?)
+        .filter(|m| m.id != meta.id)
+        .collect();
+    let mut resolved = vec![];
+    for reference in &meta.depends_on {
+        if reference == &meta.id || title_to_slug(reference) == plan_slug(meta) {
+            anyhow::bail!("a plan cannot depend on itself");
+        }
+        let up = crate::graph::resolve_plan_ref(&others, reference)
+            .with_context(|| format!("depends_on: plan not found: {}", reference))?;
+        if !resolved.contains(&up.id) {
+            resolved.push(up.id.clone());
+        }
+    }
+    meta.depends_on = resolved;
+    others.push(meta.clone());
+    let graph = crate::graph::plan_graph(&others, &[]);
+    if let Some(cycle) = graph.find_cycle() {
+        let titles: Vec<String> = cycle
+            .iter()
+            .map(|id| others.iter().find(|m| &m.id == id).map(|m| m.title.clone()).unwrap_or_else(|| id.clone()))
+            .collect();
+        anyhow::bail!("depends_on would create a cycle: {}", titles.join(" -> "));
+    }
+    Ok(())
+}
+
+/// Set plan state to InProgress. A completed plan is only reopened with `force`.
+pub fn start_plan(qp_root: &Path, id_or_slug: &str, force: bool) -> Result<Plan> {
+    let mut plan = get_plan(qp_root, id_or_slug)?;
+    if plan.meta.state == PlanState::Completed && !force {
+        anyhow::bail!("{} is already completed; use --force to reopen it", plan.meta.title);
+    }
+    plan.meta.state = PlanState::InProgress;
+    plan.meta.updated_at = Utc::now().to_rfc3339();
+    save_plan(qp_root, &plan)?;
+    Ok(plan)
+}
+
 /// Set plan state to Approved.
 pub fn approve_plan(qp_root: &Path, id_or_slug: &str) -> Result<Plan> {
     let mut plan = get_plan(qp_root, id_or_slug)?;
@@ -250,14 +832,87 @@ pub fn approve_plan(qp_root: &Path, id_or_slug: &str) -> Result<Plan> {
     Ok(plan)
 }
 
-/// Delete plan directory and contents.
-pub fn delete_plan(qp_root: &Path, id_or_slug: &str) -> Result<()> {
-    let plan = get_plan(qp_root, id_or_slug)?;
-    let dir = plan_dir(qp_root, &plan.meta.id);
-    if dir.exists() {
-        std::fs::remove_dir_all(&dir).context("delete plan dir")?;
+/// Move a plan directory into `to`, keeping its directory name unless that is taken.
+fn move_plan_dir(qp_root: &Path, dir: &Path, to: PlanLocation, plan_id: &str) -> Result<PathBuf> {
+    let dest_root = to.dir(qp_root);
+    std::fs::create_dir_all(&dest_root)
+        .with_context(|| format!("create {}", dest_root.display()))?;
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| plan_id.to_string());
+    let target = available_plan_dir(&dest_root, &name, plan_id);
+    std::fs::rename(dir, &target)
+        .with_context(|| format!("move {} to {}", dir.display(), target.display()))?;
+    Ok(target)
+}
+
+/// Take a plan from `from`, stamp its frontmatter with `update`, and move it to `to`.
+fn transfer_plan(
+    qp_root: &Path,
+    id_or_slug: &str,
+    from: PlanLocation,
+    to: PlanLocation,
+    update: impl FnOnce(&mut PlanMeta),
+) -> Result<Plan> {
+    let (dir, mut plan) = find_plan_in(qp_root, from, id_or_slug)?
+        .ok_or_else(|| QpError::PlanNotFound(id_or_slug.to_string()))?;
+    update(&mut plan.meta);
+    write_plan_to(&dir, &plan)?;
+    move_plan_dir(qp_root, &dir, to, &plan.meta.id)?;
+    Ok(plan)
+}
+
+/// Move a plan to .qp/archive/, history included.
+pub fn archive_plan(qp_root: &Path, id_or_slug: &str) -> Result<Plan> {
+    transfer_plan(qp_root, id_or_slug, PlanLocation::Active, PlanLocation::Archived, |m| {
+        m.archived_at = Some(Utc::now().to_rfc3339());
+    })
+}
+
+/// Move an archived plan back to .qp/plans/.
+pub fn unarchive_plan(qp_root: &Path, id_or_slug: &str) -> Result<Plan> {
+    transfer_plan(qp_root, id_or_slug, PlanLocation::Archived, PlanLocation::Active, |m| {
+        m.archived_at = None;
+    })
+}
+
+/// Move a plan (active or archived) to .qp/trash/. Use `restore_plan` to undo.
+pub fn delete_plan(qp_root: &Path, id_or_slug: &str) -> Result<Plan> {
+    let from = if find_plan_in(qp_root, PlanLocation::Active, id_or_slug)?.is_some() {
+        PlanLocation::Active
+    } else {
+        PlanLocation::Archived
+    };
+    transfer_plan(qp_root, id_or_slug, from, PlanLocation::Trashed, |m| {
+        m.deleted_at = Some(Utc::now().to_rfc3339());
+    })
+}
+
+/// Move a plan from the trash back to .qp/plans/.
+pub fn restore_plan(qp_root: &Path, id_or_slug: &str) -> Result<Plan> {
+    transfer_plan(qp_root, id_or_slug, PlanLocation::Trashed, PlanLocation::Active, |m| {
+        m.deleted_at = None;
+        m.archived_at = None;
+    })
+}
+
+/// Permanently remove everything in .qp/trash/. Returns the number of plans removed.
+pub fn empty_trash(qp_root: &Path) -> Result<usize> {
+    let trash = PlanLocation::Trashed.dir(qp_root);
+    if !trash.exists() {
+        return Ok(0);
     }
-    Ok(())
+    let mut removed = 0;
+    for e in std::fs::read_dir(&trash).context("read trash dir")? {
+        let path = e?.path();
+        if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+                .with_context(|| format!("delete {}", path.display()))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
 }
 
 /// Content for .qp/plan-format.md: canonical format spec for AI tools and humans.
@@ -265,7 +920,9 @@ pub fn delete_plan(qp_root: &Path, id_or_slug: &str) -> Result<()> {
 pub fn plan_format_md_content() -> &'static str {
     r#"# qp plan format
 
-Plans live under `.qp/plans/<id>/plan.md`. Each file must follow this structure.
+Plans live under `.qp/plans/<dir>/plan.md`, where `<dir>` is the plan id or a readable
+name such as `2026-01-15-my-plan` or `003-my-plan` (see `[plans] naming` in config.toml).
+Each file must follow this structure.
 
 ## 1. YAML frontmatter (between `---` lines)
 
@@ -276,7 +933,17 @@ Required fields:
 - **state** (string, snake_case): `draft` | `approved` | `optimizing` | `ready` | `in_progress` | `completed`
 - **created_at**, **updated_at** (string): RFC3339 timestamps
 
-Optional: `review_cycles`, `review_steps`, `agent`, `review_agents`.
+Optional: `slug` (set by qp from the title; do not change), `cloned_from` (id of the source plan), `archived_at`, `deleted_at`, `review_cycles`, `review_steps`, `agent`, `review_agents`, `state_history` (state transitions recorded by qp; keep as is).
+
+Optional categorization fields (set with `qp set <plan> key=value`; keep them when editing):
+
+- **tags** (list of strings): e.g. `[backend, security]`
+- **owners** (list of strings): people or handles responsible for the plan
+- **quarter** (string): planning quarter as `YYYY-Qn`, e.g. `2026-Q4`
+- **priority** (string): `critical` | `high` | `medium` | `low` (or `p0`–`p3`)
+- **due_date** (string): `YYYY-MM-DD`
+- **team** (string): owning team
+- **depends_on** (list of plan ids): plans that must be completed first (`qp set <plan> depends_on=<plan>,...`)
 
 ## 2. Body sections (## headings, order and spelling matter)
 
@@ -286,7 +953,26 @@ Optional: `review_cycles`, `review_steps`, `agent`, `review_agents`.
 - **Review Notes**
 - **Tickets** — each ticket as a subheading with **Summary** and **Definition of Done** underneath
 
-When writing or editing a plan, preserve the existing `id` and `title` in frontmatter and use exactly these section names.
+Ticket example:
+
+```
+### Ticket 2: Cache Layer
+
+**Summary:** Cache query results in Redis.
+
+**Definition of Done:** Repeated queries are served from cache.
+
+**Depends on:** Ticket 1
+
+**Estimate:** 2d
+```
+
+`**Depends on:**` is optional and lists other tickets by number or title (comma-separated).
+`**Estimate:**` is optional: hours (`4h`), days (`2d`, `1.5 days`) or points (`3pt`). Numbered steps under
+`#### Steps` may carry their own `- Estimate: 1h` bullet; a ticket without an estimate is the sum of its steps.
+
+When writing or editing a plan, preserve the existing `id`, `title` and `slug` in frontmatter and use exactly these section names.
+To change a title, run `qp rename <plan> <new title>` instead of editing the frontmatter.
 "#
 }
 
@@ -298,21 +984,14 @@ command = "claude"
 [optimization]
 steps = ["holes", "details", "breakdown", "deliverables"]
 
-[review_agents.holes]
-command = "claude"
-prompt = "Review this plan and identify weaknesses, missing considerations, edge cases, potential failures, and assumptions that need validation."
-
-[review_agents.details]
-command = "claude"
-prompt = "Expand this plan with implementation details. Add specifics about technologies, APIs, data structures, and algorithms."
-
-[review_agents.breakdown]
-command = "claude"
-prompt = "Break this plan into precise, atomic steps. Each step should be independently implementable and testable."
+[plans]
+naming = "uuid"
 
-[review_agents.deliverables]
-command = "claude"
-prompt = "Define clear acceptance criteria for each component. What tests must pass? What can be demonstrated? How do we know it's done?"
+# Review agents use the built-in prompts. To customize one, run `qp prompts export`
+# and point the step at its file:
+#
+# [review_agents.holes]
+# prompt_file = "prompts/holes.md"
 "#
 }
 
@@ -322,6 +1001,9 @@ pub fn init_qp(qp_root: &Path, config_toml: Option<&str>) -> Result<()> {
     std::fs::create_dir_all(qp_root).context("create .qp")?;
     std::fs::create_dir_all(qp_root.join("plans")).context("create .qp/plans")?;
     let config_path = qp_root.join("config.toml");
+    if !config_path.exists() {
+        crate::upgrade::write_layout_version(qp_root)?;
+    }
     let to_write = match config_toml {
         Some(s) => s.to_string(),
         None if !config_path.exists() => default_config_toml().to_string(),
@@ -440,3 +1122,79 @@ pub fn save_version_snapshot(
     }
     Ok(path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_migrate_layout_moves_dirs_and_keeps_lookup() {
+        let tmp = std::env::temp_dir().join("qp_test_migrate_layout");
+        let _ = fs::remove_dir_all(&tmp);
+        let first = create_plan(&tmp, Some("First Plan"), PlanDirNaming::Uuid).unwrap();
+        let second = create_plan(&tmp, Some("Second Plan"), PlanDirNaming::Uuid).unwrap();
+        assert!(tmp.join("plans").join(&first.meta.id).exists());
+
+        let moved = migrate_layout(&tmp, PlanDirNaming::SeqSlug).unwrap();
+        assert_eq!(moved.len(), 2);
+        assert_eq!(plan_dir(&tmp, &first.meta.id), tmp.join("plans").join("001-first-plan"));
+        assert_eq!(plan_dir(&tmp, &second.meta.id), tmp.join("plans").join("002-second-plan"));
+        assert_eq!(get_plan(&tmp, "second-plan").unwrap().meta.id, second.meta.id);
+
+        let third = create_plan(&tmp, Some("Third"), PlanDirNaming::SeqSlug).unwrap();
+        assert_eq!(plan_dir(&tmp, &third.meta.id), tmp.join("plans").join("003-third"));
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn test_archive_and_trash_are_recoverable() {
+        let tmp = std::env::temp_dir().join("qp_test_archive_trash");
+        let _ = fs::remove_dir_all(&tmp);
+        let plan = create_plan(&tmp, Some("Keep Me"), PlanDirNaming::DateSlug).unwrap();
+
+        archive_plan(&tmp, "keep-me").unwrap();
+        assert!(list_plans(&tmp).unwrap().is_empty());
+        assert!(get_plan(&tmp, "keep-me").unwrap().meta.archived_at.is_some());
+
+        delete_plan(&tmp, "keep-me").unwrap();
+        assert!(get_plan(&tmp, "keep-me").is_err());
+        restore_plan(&tmp, "keep-me").unwrap();
+        assert_eq!(list_plans(&tmp).unwrap()[0].id, plan.meta.id);
+        assert_eq!(empty_trash(&tmp).unwrap(), 0);
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn test_rename_and_clone() {
+        let tmp = std::env::temp_dir().join("qp_test_rename_clone");
+        let _ = fs::remove_dir_all(&tmp);
+        create_plan(&tmp, Some("Taken"), PlanDirNaming::Uuid).unwrap();
+        let plan = create_plan(&tmp, Some("Draft Idea"), PlanDirNaming::Uuid).unwrap();
+        record_review_step(&tmp, &plan.meta.id, "holes", "done").unwrap();
+
+        let renamed = rename_plan(&tmp, "draft-idea", "Search", PlanDirNaming::SeqSlug).unwrap();
+        assert_eq!((renamed.meta.slug.as_deref(), renamed.meta.id.as_str()), (Some("search"), plan.meta.id.as_str()));
+        assert_eq!(get_plan(&tmp, "search").unwrap().meta.title, "Search");
+        assert!(get_plan(&tmp, "draft-idea").is_err());
+        assert!(rename_plan(&tmp, "search", "Taken", PlanDirNaming::Uuid).is_err());
+
+        let copy = clone_plan(&tmp, "search", None, PlanDirNaming::Uuid).unwrap();
+        assert_eq!((copy.meta.title.as_str(), copy.meta.slug.as_deref()), ("Search (copy)", Some("search-copy")));
+        assert_eq!(copy.meta.cloned_from.as_deref(), Some(plan.meta.id.as_str()));
+        assert!(copy.meta.review_steps.is_empty() && copy.meta.review_cycles == 0);
+        assert_eq!(get_plan(&tmp, "search").unwrap().meta.review_cycles, 1);
+        let again = clone_plan(&tmp, "search", None, PlanDirNaming::Uuid).unwrap();
+        assert_eq!(again.meta.slug.as_deref(), Some("search-copy-2"));
+        assert!(clone_plan(&tmp, "search", Some("Taken"), PlanDirNaming::Uuid).is_err());
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn test_priority_in_frontmatter_ignores_case() {
+        for (raw, priority) in [("High", Priority::High), ("P0", Priority::Critical), ("low", Priority::Low)] {
+            let text = format!("---\nid: x\ntitle: X\nstate: draft\ncreated_at: a\nupdated_at: a\npriority: {}\n---\n\nbody", raw);
+            assert_eq!(parse_plan(&text).unwrap().meta.priority, Some(priority));
+        }
+    }
+}
@@ -0,0 +1,397 @@
+//! Plugin packs: extra review steps with their prompts, output parsing and default
+//! position in `optimization.steps`.
+//!
+//! A pack is a `plugin.toml` in a directory (prompt files next to it) or a single
+//! `<name>.toml` with inline prompts. Packs are installed into `.qp/plugins` or
+//! `~/.config/qp/plugins`; `extras` ships with qp. Installing a pack does not change
+//! the config: `qp plugin enable` writes its steps into `review_agents` and
+//! `optimization.steps`.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::config::{self, ConfigFile, ConfigScope, StepOutput};
+
+const MANIFEST: &str = "plugin.toml";
+const BUILTIN_EXTRAS: &str = include_str!("../plugins/extras.toml");
+
+/// One review step of a pack.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginStep {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub prompt: String,
+    /// Relative to the pack directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_file: Option<String>,
+    #[serde(default)]
+    pub output: StepOutput,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    /// Default position: right after this step, or right before `before`; else last.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+}
+
+/// Contents of `plugin.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub steps: Vec<PluginStep>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginSource {
+    Local,
+    Global,
+    Builtin,
+}
+
+impl std::fmt::Display for PluginSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginSource::Local => write!(f, "local"),
+            PluginSource::Global => write!(f, "global"),
+            PluginSource::Builtin => write!(f, "builtin"),
+        }
+    }
+}
+
+/// An installed (or built-in) pack.
+#[derive(Debug, Clone, Serialize)]
+pub struct Plugin {
+    #[serde(flatten)]
+    pub manifest: PluginManifest,
+    pub source: PluginSource,
+    /// Pack directory or single `.toml` file; none for built-in packs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl Plugin {
+    /// Directory `prompt_file`s are relative to.
+    fn dir(&self) -> Option<&Path> {
+        let path = self.path.as_deref()?;
+        if path.is_dir() {
+            Some(path)
+        } else {
+            path.parent()
+        }
+    }
+
+    pub fn step(&self, name: &str) -> Option<&PluginStep> {
+        self.manifest.steps.iter().find(|s| s.name == name)
+    }
+}
+
+pub fn builtin() -> Plugin {
+    let manifest = toml::from_str(BUILTIN_EXTRAS).expect("built-in plugin manifest");
+    Plugin { manifest, source: PluginSource::Builtin, path: None }
+}
+
+/// `plugins/` next to the config file of `scope`.
+pub fn plugins_dir(scope: ConfigScope, qp_root: Option<&Path>) -> Result<PathBuf> {
+    let config_path = scope.path(qp_root)?;
+    Ok(config_path.parent().context("config path has no parent")?.join("plugins"))
+}
+
+/// Read a pack from a directory (its `plugin.toml`) or a `.toml` file.
+pub fn read_plugin(path: &Path, source: PluginSource) -> Result<Plugin> {
+    let manifest_path = if path.is_dir() { path.join(MANIFEST) } else { path.to_path_buf() };
+    let text = std::fs::read_to_string(&manifest_path).with_context(|| format!("read {}", manifest_path.display()))?;
+    let manifest: PluginManifest = toml::from_str(&text).with_context(|| format!("parse {}", manifest_path.display()))?;
+    if manifest.name.trim().is_empty() || manifest.name.contains(['/', '\\', '.']) {
+        anyhow::bail!("{}: invalid plugin name \"{}\"", manifest_path.display(), manifest.name);
+    }
+    for step in &manifest.steps {
+        if step.prompt.trim().is_empty() && step.prompt_file.is_none() {
+            anyhow::bail!("{}: step {} has no prompt or prompt_file", manifest_path.display(), step.name);
+        }
+        // Installing copies the pack directory, so prompt files must live inside one.
+        if let Some(file) = &step.prompt_file {
+            if !path.is_dir() {
+                anyhow::bail!(
+                    "{}: step {} uses prompt_file, which needs a pack directory with {}; inline the prompt instead",
+                    manifest_path.display(),
+                    step.name,
+                    MANIFEST
+                );
+            }
+            if !path.join(file).is_file() {
+                anyhow::bail!("{}: step {}: prompt_file {} not found", manifest_path.display(), step.name, file);
+            }
+        }
+        if step.output == StepOutput::Section && step.section.is_none() {
+            anyhow::bail!("{}: step {} has output = \"section\" but no section", manifest_path.display(), step.name);
+        }
+    }
+    Ok(Plugin { manifest, source, path: Some(path.to_path_buf()) })
+}
+
+fn installed_in(dir: &Path, source: PluginSource) -> Result<Vec<Plugin>> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    entries.sort();
+    let mut out = vec![];
+    for path in entries {
+        let is_pack = path.join(MANIFEST).exists() || path.extension().is_some_and(|e| e == "toml");
+        if is_pack {
+            match read_plugin(&path, source) {
+                Ok(plugin) => out.push(plugin),
+                Err(e) => eprintln!("{} skipping plugin {}: {:#}", "warning:".yellow(), path.display(), e),
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Local packs, then global, then built-in. A name found earlier shadows later ones.
+pub fn list(qp_root: Option<&Path>) -> Result<Vec<Plugin>> {
+    let mut out = vec![];
+    if qp_root.is_some() {
+        out.extend(installed_in(&plugins_dir(ConfigScope::Local, qp_root)?, PluginSource::Local)?);
+    }
+    if let Ok(dir) = plugins_dir(ConfigScope::Global, qp_root) {
+        out.extend(installed_in(&dir, PluginSource::Global)?);
+    }
+    out.push(builtin());
+    Ok(out)
+}
+
+pub fn find(qp_root: Option<&Path>, name: &str) -> Result<Plugin> {
+    let plugins = list(qp_root)?;
+    let names: Vec<String> = plugins.iter().map(|p| p.manifest.name.clone()).collect();
+    plugins
+        .into_iter()
+        .find(|p| p.manifest.name == name)
+        .with_context(|| format!("unknown plugin: {} (available: {})", name, names.join(", ")))
+}
+
+fn is_git_url(source: &str) -> bool {
+    ["https://", "http://", "git@", "ssh://", "git://"].iter().any(|p| source.starts_with(p)) || source.ends_with(".git")
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    std::fs::create_dir_all(to).with_context(|| format!("create {}", to.display()))?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == ".git" {
+            continue;
+        }
+        let target = to.join(&name);
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target).with_context(|| format!("copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Install a pack from a local directory, a `.toml` file or a git URL into `scope`'s
+/// plugin directory. An installed pack with the same name is replaced only with `force`.
+pub fn install(source: &str, scope: ConfigScope, qp_root: Option<&Path>, force: bool) -> Result<Plugin> {
+    let dir = plugins_dir(scope, qp_root)?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+    let checkout = dir.join(format!(".checkout-{}", std::process::id()));
+    let from = if is_git_url(source) {
+        let status = std::process::Command::new("git")
+            .args(["clone", "--depth", "1", "--quiet", "--", source])
+            .arg(&checkout)
+            .status()
+            .context("run git clone")?;
+        if !status.success() {
+            let _ = std::fs::remove_dir_all(&checkout);
+            anyhow::bail!("git clone {} failed", source);
+        }
+        checkout.clone()
+    } else {
+        PathBuf::from(source)
+    };
+    let result = (|| {
+        let plugin = read_plugin(&from, PluginSource::from(scope))?;
+        let name = &plugin.manifest.name;
+        let target = if from.is_dir() { dir.join(name) } else { dir.join(format!("{}.toml", name)) };
+        for existing in [dir.join(name), dir.join(format!("{}.toml", name))] {
+            if existing.exists() {
+                if !force {
+                    anyhow::bail!("plugin {} is already installed at {} (use --force to replace it)", name, existing.display());
+                }
+                remove_path(&existing)?;
+            }
+        }
+        if from.is_dir() {
+            copy_dir(&from, &target)?;
+        } else {
+            std::fs::copy(&from, &target).with_context(|| format!("copy {}", from.display()))?;
+        }
+        read_plugin(&target, PluginSource::from(scope))
+    })();
+    if checkout.exists() {
+        let _ = std::fs::remove_dir_all(&checkout);
+    }
+    result
+}
+
+impl From<ConfigScope> for PluginSource {
+    fn from(scope: ConfigScope) -> Self {
+        match scope {
+            ConfigScope::Global => PluginSource::Global,
+            ConfigScope::Local => PluginSource::Local,
+        }
+    }
+}
+
+fn remove_path(path: &Path) -> Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+    .with_context(|| format!("remove {}", path.display()))
+}
+
+/// Delete an installed pack from `scope`'s plugin directory.
+pub fn uninstall(name: &str, scope: ConfigScope, qp_root: Option<&Path>) -> Result<Plugin> {
+    let plugin = installed_in(&plugins_dir(scope, qp_root)?, PluginSource::from(scope))?
+        .into_iter()
+        .find(|p| p.manifest.name == name)
+        .with_context(|| format!("plugin {} is not installed in {} plugins", name, scope))?;
+    if let Some(path) = &plugin.path {
+        remove_path(path)?;
+    }
+    Ok(plugin)
+}
+
+/// Insert `step` into `steps` at its default position (no-op when already present).
+pub fn insert_step(steps: &mut Vec<String>, step: &PluginStep) {
+    if steps.contains(&step.name) {
+        return;
+    }
+    let after = step.after.as_ref().and_then(|a| steps.iter().position(|s| s == a)).map(|i| i + 1);
+    let before = step.before.as_ref().and_then(|b| steps.iter().position(|s| s == b));
+    let at = after.or(before).unwrap_or(steps.len());
+    steps.insert(at, step.name.clone());
+}
+
+/// Enable a pack's steps (or one of them) in the config file at `config_path`: write
+/// each step's `review_agents` entry and insert it into `optimization.steps`, starting
+/// from the currently effective steps. Returns the new step list.
+pub fn enable(plugin: &Plugin, only: Option<&str>, config: &ConfigFile, config_path: &Path) -> Result<Vec<String>> {
+    let steps: Vec<&PluginStep> = match only {
+        Some(name) => vec![plugin
+            .step(name)
+            .with_context(|| format!("plugin {} has no step {}", plugin.manifest.name, name))?],
+        None => plugin.manifest.steps.iter().collect(),
+    };
+    let config_dir = config_path.parent().unwrap_or(Path::new("."));
+    let mut values = vec![];
+    let mut list = config.optimization.steps.clone();
+    for step in steps {
+        let key = |field: &str| format!("review_agents.{}.{}", step.name, field);
+        match (&step.prompt_file, plugin.dir()) {
+            (Some(file), Some(dir)) => {
+                let path = dir.join(file);
+                let path = path.strip_prefix(config_dir).map(Path::to_path_buf).unwrap_or(path);
+                values.push((key("prompt_file"), path.display().to_string().into()));
+            }
+            _ => values.push((key("prompt"), step.prompt.clone().into())),
+        }
+        if step.output != StepOutput::Auto {
+            values.push((key("output"), toml::Value::try_from(step.output)?.as_str().unwrap_or_default().into()));
+        }
+        if let Some(section) = &step.section {
+            values.push((key("section"), section.clone().into()));
+        }
+        insert_step(&mut list, step);
+    }
+    values.push(("optimization.steps".to_string(), toml_edit::Value::Array(list.iter().collect())));
+    config::set_config_values(config_path, values)?;
+    Ok(list)
+}
+
+/// Remove a pack's steps from `optimization.steps` and their `review_agents` entries
+/// from the config file at `config_path`. Returns the new step list.
+pub fn disable(plugin: &Plugin, config: &ConfigFile, config_path: &Path) -> Result<Vec<String>> {
+    let names: Vec<&str> = plugin.manifest.steps.iter().map(|s| s.name.as_str()).collect();
+    for name in &names {
+        for field in ["prompt", "prompt_file", "output", "section"] {
+            config::unset_config_value(config_path, &format!("review_agents.{}.{}", name, field))?;
+        }
+    }
+    let list: Vec<String> = config.optimization.steps.iter().filter(|s| !names.contains(&s.as_str())).cloned().collect();
+    if list != config.optimization.steps {
+        config::set_config_values(config_path, vec![("optimization.steps".to_string(), toml_edit::Value::Array(list.iter().collect()))])?;
+    }
+    Ok(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigLayer;
+
+    #[test]
+    fn test_install_enable_disable() {
+        let tmp = std::env::temp_dir().join("qp_test_plugins");
+        let _ = std::fs::remove_dir_all(&tmp);
+        let qp_root = tmp.join(".qp");
+        let pack = tmp.join("security-pack");
+        std::fs::create_dir_all(pack.join("prompts")).unwrap();
+        std::fs::create_dir_all(&qp_root).unwrap();
+        std::fs::write(
+            pack.join(MANIFEST),
+            "name = \"security\"\n\n[[steps]]\nname = \"threats\"\nprompt_file = \"prompts/threats.md\"\n\
+output = \"section\"\nsection = \"Threat Model\"\nbefore = \"details\"\n",
+        )
+        .unwrap();
+        std::fs::write(pack.join("prompts/threats.md"), "List threats.\n").unwrap();
+
+        let plugin = install(pack.to_str().unwrap(), ConfigScope::Local, Some(&qp_root), false).unwrap();
+        assert!(qp_root.join("plugins/security/prompts/threats.md").exists());
+        assert!(install(pack.to_str().unwrap(), ConfigScope::Local, Some(&qp_root), false).is_err());
+        let local = || installed_in(&qp_root.join("plugins"), PluginSource::Local).unwrap();
+        assert_eq!(local()[0].manifest.name, "security");
+        // A broken pack next to it is skipped rather than hiding the others.
+        std::fs::write(qp_root.join("plugins/broken.toml"), "name = ").unwrap();
+        assert_eq!(local().len(), 1);
+        std::fs::remove_file(qp_root.join("plugins/broken.toml")).unwrap();
+        // A single-file pack can't carry a prompt file along.
+        assert!(install(pack.join(MANIFEST).to_str().unwrap(), ConfigScope::Local, Some(&qp_root), true).is_err());
+
+        // Only the defaults and the local file, so a global config or QP_* variables don't leak in.
+        let config_path = qp_root.join("config.toml");
+        let load = || {
+            let layers = [ConfigLayer::defaults().unwrap(), ConfigLayer::read("local", config_path.clone()).unwrap().unwrap()];
+            config::resolve_layers(&layers, Some(&qp_root)).unwrap().0
+        };
+        let steps = enable(&plugin, None, &ConfigFile::default(), &config_path).unwrap();
+        assert_eq!(steps, vec!["holes", "threats", "details", "breakdown", "deliverables"]);
+        let config = load();
+        let threats = &config.review_agents["threats"];
+        assert_eq!((threats.prompt.as_str(), threats.output), ("List threats.", StepOutput::Section));
+        assert_eq!(threats.prompt_file.as_deref(), Some("plugins/security/prompts/threats.md"));
+
+        disable(&plugin, &config, &config_path).unwrap();
+        let config = load();
+        assert!(!config.review_agents.contains_key("threats") && !config.optimization.steps.contains(&"threats".to_string()));
+        uninstall("security", ConfigScope::Local, Some(&qp_root)).unwrap();
+        assert!(local().is_empty());
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}
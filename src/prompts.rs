@@ -0,0 +1,73 @@
+//! Built-in review-agent prompts, one Markdown file per step under `prompts/`.
+//!
+//! The files are compiled in. Config defaults, the init wizard and `eval-agent` all read
+//! them from here; `qp prompts export` writes them out for customization.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+pub struct Prompt {
+    /// Step name, also the file stem.
+    pub name: &'static str,
+    source: &'static str,
+}
+
+impl Prompt {
+    pub fn text(&self) -> &'static str {
+        self.source.trim_end()
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("{}.md", self.name)
+    }
+}
+
+/// Default steps first, in their default order.
+pub const BUILTIN: &[Prompt] = &[
+    Prompt { name: "holes", source: include_str!("../prompts/holes.md") },
+    Prompt { name: "details", source: include_str!("../prompts/details.md") },
+    Prompt { name: "breakdown", source: include_str!("../prompts/breakdown.md") },
+    Prompt { name: "deliverables", source: include_str!("../prompts/deliverables.md") },
+    Prompt { name: "estimate", source: include_str!("../prompts/estimate.md") },
+];
+
+pub fn builtin(name: &str) -> Option<&'static Prompt> {
+    BUILTIN.iter().find(|p| p.name == name)
+}
+
+/// Write the built-in prompts into `dir` as `<step>.md`. Existing files are kept unless
+/// `force`. Returns each path and whether it was written.
+pub fn export(dir: &Path, force: bool) -> Result<Vec<(PathBuf, bool)>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    let mut out = vec![];
+    for prompt in BUILTIN {
+        let path = dir.join(prompt.file_name());
+        let write = force || !path.exists();
+        if write {
+            std::fs::write(&path, format!("{}\n", prompt.text())).with_context(|| format!("write {}", path.display()))?;
+        }
+        out.push((path, write));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_keeps_existing_files() {
+        assert!(BUILTIN.iter().all(|p| !p.text().is_empty() && !p.text().ends_with('\n')));
+        let dir = std::env::temp_dir().join("qp_test_prompts_export");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("holes.md"), "mine").unwrap();
+        let files = export(&dir, false).unwrap();
+        assert_eq!(files.len(), BUILTIN.len());
+        assert!(files.iter().all(|(path, written)| *written != path.ends_with("holes.md")));
+        assert_eq!(std::fs::read_to_string(dir.join("holes.md")).unwrap(), "mine");
+        export(&dir, true).unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("holes.md")).unwrap().trim_end(), builtin("holes").unwrap().text());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
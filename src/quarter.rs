@@ -0,0 +1,96 @@
+//! Calendar quarters (`2026-Q4`) used to assign plans to a planning period.
+
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Quarter {
+    pub year: i32,
+    /// 1..=4
+    pub quarter: u32,
+}
+
+impl Quarter {
+    /// Quarter containing the given date.
+    pub fn containing(date: NaiveDate) -> Self {
+        Self {
+            year: date.year(),
+            quarter: (date.month0() / 3) + 1,
+        }
+    }
+
+    /// Quarter containing today (UTC).
+    pub fn current() -> Self {
+        Self::containing(Utc::now().date_naive())
+    }
+
+    /// First day of the quarter.
+    pub fn start(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, (self.quarter - 1) * 3 + 1, 1).expect("valid quarter start")
+    }
+
+    /// Last day of the quarter.
+    pub fn end(&self) -> NaiveDate {
+        self.next().start().pred_opt().expect("valid quarter end")
+    }
+
+    /// The following quarter.
+    pub fn next(&self) -> Self {
+        if self.quarter == 4 {
+            Self { year: self.year + 1, quarter: 1 }
+        } else {
+            Self { year: self.year, quarter: self.quarter + 1 }
+        }
+    }
+}
+
+impl std::fmt::Display for Quarter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-Q{}", self.year, self.quarter)
+    }
+}
+
+impl std::str::FromStr for Quarter {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || anyhow::anyhow!("invalid quarter: {} (expected YYYY-Qn, e.g. 2026-Q4)", s);
+        let (year, q) = s.trim().split_once('-').ok_or_else(invalid)?;
+        let q = q.strip_prefix(['Q', 'q']).ok_or_else(invalid)?;
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+        let quarter: u32 = q.parse().map_err(|_| invalid())?;
+        if year.to_string().len() != 4 || !(1..=4).contains(&quarter) {
+            return Err(invalid());
+        }
+        Ok(Self { year, quarter })
+    }
+}
+
+impl Serialize for Quarter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Quarter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_bounds() {
+        let q: Quarter = "2026-q4".parse().unwrap();
+        assert_eq!(q.to_string(), "2026-Q4");
+        assert_eq!(q.start(), NaiveDate::from_ymd_opt(2026, 10, 1).unwrap());
+        assert_eq!(q.end(), NaiveDate::from_ymd_opt(2026, 12, 31).unwrap());
+        assert_eq!(Quarter::containing(NaiveDate::from_ymd_opt(2026, 5, 2).unwrap()).to_string(), "2026-Q2");
+        assert!("2026-Q5".parse::<Quarter>().is_err());
+        assert!("26-Q1".parse::<Quarter>().is_err());
+    }
+}
@@ -0,0 +1,518 @@
+//! Static HTML for sharing plans: `qp render <plan> --html` and `qp render --all`.
+//!
+//! Pages are self-contained (inline CSS, no scripts or network assets) and print cleanly,
+//! so "Save as PDF" from a browser gives a shareable report.
+
+use anyhow::{Context, Result};
+use pulldown_cmark::{html, Event, Options, Parser, Tag, TagEnd};
+use similar::{ChangeTag, TextDiff};
+use std::path::{Path, PathBuf};
+
+use crate::config::CapacityConfig;
+use crate::plan::{self, Plan, PlanMeta};
+use crate::tickets::{self, Ticket};
+
+const STYLE: &str = r#"
+:root { --fg: #1f2328; --muted: #656d76; --border: #d0d7de; --accent: #0969da; --ok: #1a7f37; --bg-soft: #f6f8fa; }
+* { box-sizing: border-box; }
+body { font: 15px/1.55 -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; color: var(--fg); margin: 0; }
+.layout { display: flex; max-width: 1180px; margin: 0 auto; }
+nav.toc { flex: 0 0 230px; position: sticky; top: 0; align-self: flex-start; max-height: 100vh; overflow: auto; padding: 24px 16px; font-size: 13px; border-right: 1px solid var(--border); }
+nav.toc ul { list-style: none; padding-left: 12px; margin: 4px 0; }
+nav.toc > ul { padding-left: 0; }
+nav.toc a { color: var(--fg); text-decoration: none; }
+nav.toc a:hover { color: var(--accent); }
+main { flex: 1; min-width: 0; padding: 24px 40px 64px; }
+h1 { margin: 0 0 8px; font-size: 28px; }
+h2 { border-bottom: 1px solid var(--border); padding-bottom: 6px; margin-top: 36px; }
+a { color: var(--accent); }
+.meta { color: var(--muted); font-size: 13px; display: flex; flex-wrap: wrap; gap: 6px 14px; margin-bottom: 8px; }
+.pill { display: inline-block; padding: 1px 8px; border-radius: 10px; background: var(--bg-soft); border: 1px solid var(--border); font-size: 12px; color: var(--fg); }
+.pill.state { background: #ddf4ff; border-color: #54aeff; }
+.pill.done { background: #dafbe1; border-color: #4ac26b; color: var(--ok); }
+pre, code { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 13px; }
+pre { background: var(--bg-soft); padding: 10px 12px; overflow: auto; border-radius: 6px; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid var(--border); padding: 4px 10px; text-align: left; }
+.ticket { border: 1px solid var(--border); border-radius: 8px; padding: 12px 16px; margin: 14px 0; }
+.ticket.is-done { border-left: 4px solid var(--ok); }
+.ticket > header { display: flex; align-items: baseline; flex-wrap: wrap; gap: 8px; }
+.ticket > header h3 { margin: 0; font-size: 17px; }
+.ticket .num { color: var(--muted); font-size: 13px; }
+.progress { height: 6px; background: var(--bg-soft); border-radius: 3px; margin: 8px 0 2px; overflow: hidden; }
+.progress > div { height: 100%; background: var(--ok); }
+.small { color: var(--muted); font-size: 12px; }
+ul.timeline { list-style: none; padding-left: 0; border-left: 2px solid var(--border); }
+ul.timeline > li { position: relative; padding: 0 0 18px 18px; }
+ul.timeline > li::before { content: ""; position: absolute; left: -7px; top: 6px; width: 12px; height: 12px; border-radius: 50%; background: var(--accent); }
+ul.timeline h3 { margin: 0; font-size: 16px; }
+details { margin: 6px 0; }
+summary { cursor: pointer; color: var(--accent); }
+pre.diff span { display: block; }
+pre.diff .add { background: #dafbe1; }
+pre.diff .del { background: #ffebe9; }
+pre.diff .hunk { color: var(--muted); }
+@media print {
+  nav.toc { display: none; }
+  main { padding: 0; }
+  .ticket, ul.timeline > li { break-inside: avoid; }
+  a { color: inherit; text-decoration: none; }
+}
+"#;
+
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Scheme of a URL (`https`, `javascript`, ...), lowercased; None for relative URLs.
+/// Whitespace and control characters are ignored, as browsers do.
+fn url_scheme(url: &str) -> Option<String> {
+    let url: String = url.chars().filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control()).collect();
+    let end = url.find([':', '/', '?', '#'])?;
+    (url[end..].starts_with(':') && end > 0).then(|| url[..end].to_ascii_lowercase())
+}
+
+/// Links may go to the web, to mail or within the page (or site).
+fn is_safe_link(url: &str) -> bool {
+    url_scheme(url).is_none_or(|s| matches!(s.as_str(), "http" | "https" | "mailto"))
+}
+
+/// Images may only come from next to the page, never from the network.
+fn is_local_image(url: &str) -> bool {
+    url_scheme(url).is_none() && !url.trim_start().starts_with("//")
+}
+
+/// Render Markdown to HTML. Plans may come from imported issues or agent output, so pages
+/// never run scripts or load remote content: raw HTML is shown as text, remote images become
+/// links (or their alt text), and links other than http(s), mailto and relative ones keep
+/// only their text.
+fn markdown(s: &str) -> String {
+    let opts = Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH;
+    // For each open link or image, the end event to emit in place of its own (None drops it).
+    let mut ends: Vec<Option<TagEnd>> = vec![];
+    let events = Parser::new_ext(s, opts).filter_map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Some(Event::Text(raw)),
+        Event::Start(Tag::Link { link_type, dest_url, title, id }) => {
+            let safe = is_safe_link(&dest_url);
+            ends.push(safe.then_some(TagEnd::Link));
+            safe.then_some(Event::Start(Tag::Link { link_type, dest_url, title, id }))
+        }
+        Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
+            if is_local_image(&dest_url) {
+                ends.push(Some(TagEnd::Image));
+                Some(Event::Start(Tag::Image { link_type, dest_url, title, id }))
+            } else {
+                // Inside a link the alt text alone stands in for it; anchors can't nest.
+                let safe = is_safe_link(&dest_url) && !ends.contains(&Some(TagEnd::Link));
+                ends.push(safe.then_some(TagEnd::Link));
+                safe.then_some(Event::Start(Tag::Link { link_type, dest_url, title, id }))
+            }
+        }
+        Event::End(TagEnd::Link | TagEnd::Image) => ends.pop().flatten().map(Event::End),
+        other => Some(other),
+    });
+    let mut out = String::new();
+    html::push_html(&mut out, events);
+    out
+}
+
+fn anchor(s: &str) -> String {
+    plan::title_to_slug(s)
+}
+
+/// Split a plan body into `## ` sections (outside code fences): `(heading, content)`,
+/// with an empty heading for text before the first section.
+fn sections(body: &str) -> Vec<(String, String)> {
+    let mut out = vec![(String::new(), String::new())];
+    let mut in_fence = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        match line.strip_prefix("## ").filter(|_| !in_fence) {
+            Some(h) => out.push((h.trim().to_string(), String::new())),
+            None => {
+                let s = &mut out.last_mut().unwrap().1;
+                s.push_str(line);
+                s.push('\n');
+            }
+        }
+    }
+    out
+}
+
+/// Text of a Tickets section before its first ticket heading.
+fn tickets_preamble(content: &str) -> String {
+    let mut in_fence = false;
+    let mut out = String::new();
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if !in_fence && line.starts_with('#') {
+            break;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+fn ticket_label(t: &Ticket) -> String {
+    if t.id.chars().all(|c| c.is_ascii_digit()) {
+        format!("Ticket {}", t.id)
+    } else {
+        String::new()
+    }
+}
+
+fn ticket_card(t: &Ticket, tickets: &[Ticket], capacity: &CapacityConfig) -> String {
+    let mut out = format!(
+        "<article class=\"ticket{}\" id=\"ticket-{}\">\n<header><span class=\"num\">{}</span><h3>{}</h3>",
+        if t.is_done() { " is-done" } else { "" },
+        escape(&t.id),
+        escape(&ticket_label(t)),
+        escape(&t.title)
+    );
+    if t.is_done() {
+        out.push_str("<span class=\"pill done\">done</span>");
+    } else if let Some(status) = t.field("status") {
+        out.push_str(&format!("<span class=\"pill\">{}</span>", escape(status)));
+    }
+    if let Some(h) = t.estimate_hours(capacity) {
+        out.push_str(&format!("<span class=\"pill\">~{}h</span>", h.round()));
+    }
+    out.push_str("</header>\n");
+    if let Some(pct) = (t.checks_done * 100).checked_div(t.checks_total) {
+        out.push_str(&format!(
+            "<div class=\"progress\"><div style=\"width:{}%\"></div></div><div class=\"small\">{}/{} checks done</div>\n",
+            pct, t.checks_done, t.checks_total
+        ));
+    }
+    let deps = t.depends_on();
+    if !deps.is_empty() {
+        let links: Vec<String> = deps
+            .iter()
+            .map(|d| match tickets.iter().find(|u| u.matches_ref(d)) {
+                Some(up) => format!("<a href=\"#ticket-{}\">{}</a>", escape(&up.id), escape(d)),
+                None => escape(d),
+            })
+            .collect();
+        out.push_str(&format!("<div class=\"small\">Depends on: {}</div>\n", links.join(", ")));
+    }
+    out.push_str(&markdown(&t.body));
+    out.push_str("</article>\n");
+    out
+}
+
+/// One snapshot from `history/`.
+struct Version {
+    number: u32,
+    content: String,
+    plan: Option<Plan>,
+    review: Option<String>,
+    changes: Option<crate::changelog::Changes>,
+    modified_at: Option<String>,
+}
+
+fn versions(qp_root: &Path, plan_id: &str) -> Result<Vec<Version>> {
+    let dir = plan::plan_dir(qp_root, plan_id).join("history");
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut out = vec![];
+    for entry in std::fs::read_dir(&dir).with_context(|| format!("read {}", dir.display()))? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(number) = name.strip_prefix('v').and_then(|v| v.strip_suffix(".md")).and_then(|v| v.parse().ok())
+        else {
+            continue;
+        };
+        let content = std::fs::read_to_string(entry.path())?;
+        let review = std::fs::read_to_string(dir.join(format!("v{}.review.md", number))).ok();
+        let modified_at = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .map(|t| chrono::DateTime::<chrono::Utc>::from(t).format("%Y-%m-%d %H:%M").to_string());
+        out.push(Version {
+            number,
+            plan: plan::parse_plan(&content).ok(),
+            content,
+            review,
+            changes: crate::changelog::load(qp_root, plan_id, number),
+            modified_at,
+        });
+    }
+    out.sort_by_key(|v| v.number);
+    Ok(out)
+}
+
+fn diff_html(old: &str, new: &str) -> (String, usize, usize) {
+    let diff = TextDiff::from_lines(old, new);
+    let (mut added, mut removed) = (0, 0);
+    let mut out = String::from("<pre class=\"diff\">");
+    for group in diff.grouped_ops(3) {
+        let first = &group[0];
+        out.push_str(&format!(
+            "<span class=\"hunk\">@@ -{} +{} @@</span>",
+            first.old_range().start + 1,
+            first.new_range().start + 1
+        ));
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                let (class, sign) = match change.tag() {
+                    ChangeTag::Insert => {
+                        added += 1;
+                        ("add", '+')
+                    }
+                    ChangeTag::Delete => {
+                        removed += 1;
+                        ("del", '-')
+                    }
+                    ChangeTag::Equal => ("", ' '),
+                };
+                out.push_str(&format!(
+                    "<span class=\"{}\">{}{}</span>",
+                    class,
+                    sign,
+                    escape(change.value().trim_end_matches('\n'))
+                ));
+            }
+        }
+    }
+    out.push_str("</pre>");
+    (out, added, removed)
+}
+
+fn timeline(versions: &[Version]) -> String {
+    let mut out = String::from("<ul class=\"timeline\">\n");
+    for (i, v) in versions.iter().enumerate() {
+        out.push_str(&format!("<li id=\"v{}\"><h3>Version {}</h3>", v.number, v.number));
+        let when = v
+            .plan
+            .as_ref()
+            .and_then(|p| chrono::DateTime::parse_from_rfc3339(&p.meta.updated_at).ok())
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .or(v.modified_at.clone());
+        if let Some(when) = when {
+            out.push_str(&format!("<div class=\"small\">{}</div>", escape(&when)));
+        }
+        if let Some(c) = &v.changes {
+            let step = c.step.as_deref().map(|s| format!("{}: ", s)).unwrap_or_default();
+            out.push_str(&format!("<div>{}{}</div>", escape(&step), escape(&c.to_string())));
+            let added: Vec<&String> = c.sections_added.iter().chain(&c.tickets_added).collect();
+            if !added.is_empty() {
+                let items: Vec<String> = added.iter().map(|a| format!("<li>{}</li>", escape(a))).collect();
+                out.push_str(&format!("<ul class=\"small\">{}</ul>", items.join("")));
+            }
+        }
+        if let Some(p) = &v.plan {
+            let steps: Vec<&str> = p
+                .meta
+                .review_steps
+                .iter()
+                .filter(|s| s.status == "done")
+                .map(|s| s.step.as_str())
+                .collect();
+            if !steps.is_empty() {
+                out.push_str(&format!("<div class=\"small\">Review steps done: {}</div>", escape(&steps.join(", "))));
+            }
+            if let Some((_, notes)) = sections(&p.body).into_iter().find(|(h, _)| h == "Review Notes") {
+                if !notes.trim().is_empty() {
+                    out.push_str(&format!("<details><summary>Review notes</summary>{}</details>", markdown(&notes)));
+                }
+            }
+        }
+        if let Some(review) = &v.review {
+            out.push_str(&format!("<details><summary>Review output</summary>{}</details>", markdown(review)));
+        }
+        if i > 0 {
+            let prev = &versions[i - 1];
+            let (diff, added, removed) = diff_html(&prev.content, &v.content);
+            out.push_str(&format!(
+                "<details><summary>Changes from version {} (+{} −{})</summary>{}</details>",
+                prev.number, added, removed, diff
+            ));
+        }
+        out.push_str("</li>\n");
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+fn page(title: &str, nav: &str, main: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<meta name=\"generator\" content=\"qp\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<div class=\"layout\">\n{}<main>\n{}</main>\n</div>\n</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        nav,
+        main
+    )
+}
+
+fn meta_line(meta: &PlanMeta) -> String {
+    let mut items = vec![format!("<span class=\"pill state\">{}</span>", meta.state)];
+    if let Some(p) = &meta.priority {
+        items.push(format!("<span>priority {}</span>", escape(&p.to_string())));
+    }
+    if let Some(q) = &meta.quarter {
+        items.push(format!("<span>{}</span>", q));
+    }
+    if let Some(d) = &meta.due_date {
+        items.push(format!("<span>due {}</span>", d));
+    }
+    if let Some(t) = &meta.team {
+        items.push(format!("<span>team {}</span>", escape(t)));
+    }
+    if !meta.owners.is_empty() {
+        items.push(format!("<span>owners {}</span>", escape(&meta.owners.join(", "))));
+    }
+    for tag in &meta.tags {
+        items.push(format!("<span class=\"pill\">#{}</span>", escape(tag)));
+    }
+    items.push(format!("<span>updated {}</span>", escape(meta.updated_at.get(..10).unwrap_or(&meta.updated_at))));
+    format!("<div class=\"meta\">{}</div>\n", items.join(""))
+}
+
+/// Render one plan as a standalone HTML page. `index` links back to a site index.
+pub fn render_plan(qp_root: &Path, plan: &Plan, capacity: &CapacityConfig, index: Option<&str>) -> Result<String> {
+    let tickets = tickets::parse_tickets(&plan.body);
+    let versions = versions(qp_root, &plan.meta.id)?;
+    let mut nav = String::from("<nav class=\"toc\">\n");
+    if let Some(index) = index {
+        nav.push_str(&format!("<p><a href=\"{}\">← All plans</a></p>\n", escape(index)));
+    }
+    nav.push_str("<ul>\n");
+    let mut main = format!("<h1>{}</h1>\n{}", escape(&plan.meta.title), meta_line(&plan.meta));
+    if !tickets.is_empty() {
+        let totals = tickets::estimate_totals(&tickets, capacity);
+        let done = tickets.iter().filter(|t| t.is_done()).count();
+        main.push_str(&format!(
+            "<p class=\"small\">{}/{} tickets done{}</p>\n",
+            done,
+            tickets.len(),
+            if totals.estimated > 0 { format!(" · estimate {}", totals) } else { String::new() }
+        ));
+    }
+    for (heading, content) in sections(&plan.body) {
+        if heading.is_empty() {
+            main.push_str(&markdown(&content));
+            continue;
+        }
+        let id = anchor(&heading);
+        nav.push_str(&format!("<li><a href=\"#{}\">{}</a>", id, escape(&heading)));
+        main.push_str(&format!("<section id=\"{}\">\n<h2>{}</h2>\n", id, escape(&heading)));
+        if heading == "Tickets" && !tickets.is_empty() {
+            nav.push_str("<ul>");
+            for t in &tickets {
+                let label = match ticket_label(t) {
+                    l if l.is_empty() => t.title.clone(),
+                    l => format!("{}: {}", l, t.title),
+                };
+                nav.push_str(&format!("<li><a href=\"#ticket-{}\">{}</a></li>", escape(&t.id), escape(&label)));
+            }
+            nav.push_str("</ul>");
+            main.push_str(&markdown(&tickets_preamble(&content)));
+            for t in &tickets {
+                main.push_str(&ticket_card(t, &tickets, capacity));
+            }
+        } else {
+            main.push_str(&markdown(&content));
+        }
+        nav.push_str("</li>\n");
+        main.push_str("</section>\n");
+    }
+    if !versions.is_empty() {
+        nav.push_str("<li><a href=\"#history\">Review history</a></li>\n");
+        main.push_str("<section id=\"history\">\n<h2>Review history</h2>\n");
+        main.push_str(&timeline(&versions));
+        main.push_str("</section>\n");
+    }
+    nav.push_str("</ul>\n</nav>\n");
+    Ok(page(&plan.meta.title, &nav, &main))
+}
+
+/// File name of a plan's page in the static site (its plan directory name).
+fn page_name(qp_root: &Path, meta: &PlanMeta) -> String {
+    let dir = plan::plan_dir(qp_root, &meta.id);
+    let name = dir.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| meta.id.clone());
+    format!("{}.html", name)
+}
+
+/// Write `index.html` and one page per active plan into `out_dir`. Returns the files written.
+pub fn render_site(qp_root: &Path, out_dir: &Path, capacity: &CapacityConfig) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(out_dir).with_context(|| format!("create {}", out_dir.display()))?;
+    let mut metas = plan::list_plans(qp_root)?;
+    plan::sort_plans(&mut metas, plan::PlanSort::Updated);
+    let mut written = vec![];
+    let mut rows = String::new();
+    for meta in &metas {
+        let plan = plan::get_plan(qp_root, &meta.id)?;
+        let name = page_name(qp_root, meta);
+        let path = out_dir.join(&name);
+        std::fs::write(&path, render_plan(qp_root, &plan, capacity, Some("index.html"))?)
+            .with_context(|| format!("write {}", path.display()))?;
+        written.push(path);
+        let tickets = tickets::parse_tickets(&plan.body);
+        let totals = tickets::estimate_totals(&tickets, capacity);
+        rows.push_str(&format!(
+            "<tr><td><a href=\"{}\">{}</a></td><td><span class=\"pill state\">{}</span></td><td>{}</td><td>{}</td><td>{}/{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&name),
+            escape(&meta.title),
+            meta.state,
+            meta.quarter.map(|q| q.to_string()).unwrap_or_default(),
+            meta.priority.map(|p| p.to_string()).unwrap_or_default(),
+            tickets.iter().filter(|t| t.is_done()).count(),
+            tickets.len(),
+            if totals.estimated > 0 { format!("{}h", totals.hours.round()) } else { String::new() },
+            escape(meta.updated_at.get(..10).unwrap_or(&meta.updated_at)),
+        ));
+    }
+    let main = format!(
+        "<h1>Plans</h1>\n<p class=\"small\">{} plans · generated {}</p>\n<table>\n<tr><th>Plan</th><th>State</th><th>Quarter</th><th>Priority</th><th>Tickets done</th><th>Estimate</th><th>Updated</th></tr>\n{}</table>\n",
+        metas.len(),
+        chrono::Utc::now().format("%Y-%m-%d %H:%M UTC"),
+        rows
+    );
+    let index = out_dir.join("index.html");
+    std::fs::write(&index, page("Plans", "", &main)).with_context(|| format!("write {}", index.display()))?;
+    written.insert(0, index);
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_plan_is_self_contained() {
+        let tmp = std::env::temp_dir().join("qp_test_render");
+        let _ = std::fs::remove_dir_all(&tmp);
+        let body = "## Overview\n\nShip search & facets.\n\n## Tickets\n\n### Ticket 1: Index\n\n**Summary:** Build it.\n\n- [x] schema\n- [ ] writer\n\n### Ticket 2: API\n\n**Depends on:** Ticket 1\n\n<script>alert(1)</script>\n\nSee <img src=\"https://example.com/x.png\">.\n\n![chart](https://example.com/chart.png) ![local](chart.png) [docs](https://example.com/docs) [x](javascript:alert(1)) [y]( JavaScript:alert(2)) [![badge](https://example.com/b.svg)](https://example.com)\n";
+        let plan = plan::create_plan_with_body(&tmp, Some("Search"), body, plan::PlanDirNaming::Uuid).unwrap();
+        let v1 = plan::serialize_plan(&plan).unwrap();
+        plan::save_version_snapshot(&tmp, &plan.meta.id, 1, &v1, None).unwrap();
+        plan::save_version_snapshot(&tmp, &plan.meta.id, 2, &v1.replace("Build it.", "Build the index."), None).unwrap();
+
+        let html = render_plan(&tmp, &plan, &CapacityConfig::default(), None).unwrap();
+        assert!(html.contains("Ship search &amp; facets."));
+        assert!(html.contains("id=\"ticket-1\"") && html.contains("1/2 checks done"));
+        assert!(html.contains("<a href=\"#ticket-1\">Ticket 1</a>"));
+        assert!(html.contains("Changes from version 1 (+1 −1)"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;") && html.contains("&lt;img src="));
+        assert!(!html.contains("<script") && !html.contains("<img src=\"https"));
+        assert!(html.contains("<a href=\"https://example.com/chart.png\">chart</a>") && html.contains("<img src=\"chart.png\""));
+        assert!(html.contains("<a href=\"https://example.com/docs\">docs</a>") && !html.to_lowercase().contains("javascript:"));
+        assert!(html.contains("<a href=\"https://example.com\">badge</a>"));
+
+        let out = tmp.join("site");
+        let files = render_site(&tmp, &out, &CapacityConfig::default()).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(std::fs::read_to_string(out.join("index.html")).unwrap().contains("Search</a>"));
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}
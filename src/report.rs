@@ -0,0 +1,377 @@
+//! Quarter roll-up: gather plans assigned to a quarter with state, ticket progress,
+//! optimization coverage and owners. Rendered as a terminal table, Markdown or JSON.
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::config::ConfigFile;
+use crate::plan::{self, PlanLocation, PlanMeta, PlanState, Priority};
+use crate::quarter::Quarter;
+use crate::tickets;
+
+/// Share of the quarter after which plans still in `draft` are flagged.
+const LATE_DRAFT_THRESHOLD_PCT: u32 = 50;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    #[default]
+    Table,
+    Markdown,
+    Json,
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" | "text" => Ok(ReportFormat::Table),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "json" => Ok(ReportFormat::Json),
+            _ => anyhow::bail!("unknown format: {} (expected table, markdown or json)", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarterPlanRow {
+    pub id: String,
+    pub title: String,
+    pub state: PlanState,
+    pub priority: Option<Priority>,
+    pub owners: Vec<String>,
+    pub tickets_done: usize,
+    pub tickets_total: usize,
+    pub checks_done: usize,
+    pub checks_total: usize,
+    pub steps_done: usize,
+    pub steps_total: usize,
+    pub archived: bool,
+    pub flags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarterSummary {
+    pub plans: usize,
+    pub by_state: BTreeMap<String, usize>,
+    pub tickets_done: usize,
+    pub tickets_total: usize,
+    pub fully_optimized: usize,
+    pub flagged: usize,
+    pub owners: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarterReport {
+    pub quarter: Quarter,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub as_of: NaiveDate,
+    /// How much of the quarter has elapsed at `as_of`, 0-100.
+    pub elapsed_pct: u32,
+    pub plans: Vec<QuarterPlanRow>,
+    pub summary: QuarterSummary,
+}
+
+fn elapsed_pct(quarter: Quarter, as_of: NaiveDate) -> u32 {
+    if as_of < quarter.start() {
+        return 0;
+    }
+    if as_of > quarter.end() {
+        return 100;
+    }
+    let total = (quarter.end() - quarter.start()).num_days() + 1;
+    let done = (as_of - quarter.start()).num_days() + 1;
+    ((done * 100) / total) as u32
+}
+
+fn plan_row(meta: &PlanMeta, body: &str, config: &ConfigFile, elapsed: u32, archived: bool) -> QuarterPlanRow {
+    let tickets = tickets::parse_tickets(body);
+    let steps_total = config.optimization.steps.len();
+    let steps_done = config
+        .optimization
+        .steps
+        .iter()
+        .filter(|s| meta.review_steps.iter().any(|r| r.step == **s && r.status == "done"))
+        .count();
+    let mut flags = vec![];
+    if meta.state == PlanState::Draft && elapsed >= LATE_DRAFT_THRESHOLD_PCT {
+        flags.push(format!("still draft at {}% of quarter", elapsed));
+    }
+    if elapsed >= 100 && meta.state != PlanState::Completed {
+        flags.push("quarter ended before completion".to_string());
+    }
+    if tickets.is_empty() && meta.state != PlanState::Draft {
+        flags.push("no tickets".to_string());
+    }
+    if meta.owners.is_empty() {
+        flags.push("no owner".to_string());
+    }
+    QuarterPlanRow {
+        id: meta.id.clone(),
+        title: meta.title.clone(),
+        state: meta.state,
+        priority: meta.priority,
+        owners: meta.owners.clone(),
+        tickets_done: tickets.iter().filter(|t| t.is_done()).count(),
+        tickets_total: tickets.len(),
+        checks_done: tickets.iter().map(|t| t.checks_done).sum(),
+        checks_total: tickets.iter().map(|t| t.checks_total).sum(),
+        steps_done,
+        steps_total,
+        archived,
+        flags,
+    }
+}
+
+/// Build the roll-up for all plans whose `quarter` matches. Archived plans are included when asked.
+pub fn quarter_report(
+    qp_root: &Path,
+    quarter: Quarter,
+    config: &ConfigFile,
+    as_of: NaiveDate,
+    include_archived: bool,
+) -> Result<QuarterReport> {
+    let elapsed = elapsed_pct(quarter, as_of);
+    let mut locations = vec![PlanLocation::Active];
+    if include_archived {
+        locations.push(PlanLocation::Archived);
+    }
+    let mut rows = vec![];
+    for location in locations {
+        for plan in plan::load_plans_in(qp_root, location)? {
+            if plan.meta.quarter != Some(quarter) {
+                continue;
+            }
+            rows.push(plan_row(&plan.meta, &plan.body, config, elapsed, location == PlanLocation::Archived));
+        }
+    }
+    rows.sort_by(|a, b| {
+        (a.priority.is_none(), a.priority, &a.title).cmp(&(b.priority.is_none(), b.priority, &b.title))
+    });
+
+    let mut by_state = BTreeMap::new();
+    let mut owners: Vec<String> = vec![];
+    for r in &rows {
+        *by_state.entry(r.state.to_string()).or_insert(0) += 1;
+        for o in &r.owners {
+            if !owners.contains(o) {
+                owners.push(o.clone());
+            }
+        }
+    }
+    owners.sort();
+    let summary = QuarterSummary {
+        plans: rows.len(),
+        by_state,
+        tickets_done: rows.iter().map(|r| r.tickets_done).sum(),
+        tickets_total: rows.iter().map(|r| r.tickets_total).sum(),
+        fully_optimized: rows.iter().filter(|r| r.steps_total > 0 && r.steps_done == r.steps_total).count(),
+        flagged: rows.iter().filter(|r| !r.flags.is_empty()).count(),
+        owners,
+    };
+    Ok(QuarterReport {
+        quarter,
+        start: quarter.start(),
+        end: quarter.end(),
+        as_of,
+        elapsed_pct: elapsed,
+        plans: rows,
+        summary,
+    })
+}
+
+fn ratio(done: usize, total: usize) -> String {
+    if total == 0 {
+        "-".to_string()
+    } else {
+        format!("{}/{}", done, total)
+    }
+}
+
+fn pct(done: usize, total: usize) -> u32 {
+    (done * 100).checked_div(total).unwrap_or(0) as u32
+}
+
+/// Cells for one plan row, shared by the table and Markdown renderers.
+fn row_cells(r: &QuarterPlanRow) -> Vec<String> {
+    let mut title = r.title.clone();
+    if r.archived {
+        title.push_str(" (archived)");
+    }
+    vec![
+        r.state.to_string(),
+        r.priority.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string()),
+        title,
+        ratio(r.tickets_done, r.tickets_total),
+        ratio(r.checks_done, r.checks_total),
+        ratio(r.steps_done, r.steps_total),
+        if r.owners.is_empty() { "-".to_string() } else { r.owners.join(", ") },
+        r.flags.join("; "),
+    ]
+}
+
+const HEADERS: &[&str] = &["State", "Priority", "Plan", "Tickets", "Checks", "Optimized", "Owners", "Flags"];
+
+fn summary_lines(report: &QuarterReport) -> Vec<String> {
+    let s = &report.summary;
+    let states = s
+        .by_state
+        .iter()
+        .map(|(k, v)| format!("{} {}", v, k))
+        .collect::<Vec<_>>()
+        .join(", ");
+    vec![
+        format!("{} plan(s): {}", s.plans, if states.is_empty() { "none".to_string() } else { states }),
+        format!(
+            "Tickets: {} ({}% done)",
+            ratio(s.tickets_done, s.tickets_total),
+            pct(s.tickets_done, s.tickets_total)
+        ),
+        format!("Fully optimized: {}/{}", s.fully_optimized, s.plans),
+        format!("Needs attention: {}", s.flagged),
+        format!("Owners: {}", if s.owners.is_empty() { "-".to_string() } else { s.owners.join(", ") }),
+    ]
+}
+
+/// Render the report as an aligned terminal table with a summary.
+pub fn render_table(report: &QuarterReport) -> String {
+    let mut out = format!(
+        "{} ({} to {}, {}% elapsed as of {})\n\n",
+        format!("Quarter {}", report.quarter).bold(),
+        report.start,
+        report.end,
+        report.elapsed_pct,
+        report.as_of
+    );
+    if report.plans.is_empty() {
+        out.push_str(&format!("No plans assigned to {}. Assign one with: qp set <plan> quarter={}\n", report.quarter, report.quarter));
+        return out;
+    }
+    let rows: Vec<Vec<String>> = report.plans.iter().map(row_cells).collect();
+    let mut widths: Vec<usize> = HEADERS.iter().map(|h| h.len()).collect();
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!("{:<width$}", c, width = w))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let headers: Vec<String> = HEADERS.iter().map(|h| h.to_string()).collect();
+    out.push_str(&format!("{}\n", line(&headers).bold()));
+    for (row, r) in rows.iter().zip(&report.plans) {
+        let text = line(row);
+        if r.flags.is_empty() {
+            out.push_str(&format!("{}\n", text));
+        } else {
+            out.push_str(&format!("{}\n", text.yellow()));
+        }
+    }
+    out.push('\n');
+    for l in summary_lines(report) {
+        out.push_str(&format!("{}\n", l));
+    }
+    out
+}
+
+fn md_escape(cell: &str) -> String {
+    cell.replace('|', "\\|")
+}
+
+/// Render the report as Markdown (for planning-meeting notes).
+pub fn render_markdown(report: &QuarterReport) -> String {
+    let mut out = format!(
+        "# Quarter {}\n\n{} to {} ({}% elapsed as of {})\n\n",
+        report.quarter, report.start, report.end, report.elapsed_pct, report.as_of
+    );
+    out.push_str("## Summary\n\n");
+    for l in summary_lines(report) {
+        out.push_str(&format!("- {}\n", l));
+    }
+    out.push_str("\n## Plans\n\n");
+    if report.plans.is_empty() {
+        out.push_str("No plans assigned to this quarter.\n");
+        return out;
+    }
+    out.push_str(&format!("| {} |\n", HEADERS.join(" | ")));
+    out.push_str(&format!("|{}\n", "---|".repeat(HEADERS.len())));
+    for r in &report.plans {
+        let cells: Vec<String> = row_cells(r).iter().map(|c| md_escape(c)).collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out
+}
+
+/// Render the report in the requested format.
+pub fn render(report: &QuarterReport, format: ReportFormat) -> Result<String> {
+    Ok(match format {
+        ReportFormat::Table => render_table(report),
+        ReportFormat::Markdown => render_markdown(report),
+        ReportFormat::Json => format!("{}\n", serde_json::to_string_pretty(report)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarter_report_flags_and_renderers() {
+        let tmp = std::env::temp_dir().join("qp_test_quarter_report");
+        let _ = std::fs::remove_dir_all(&tmp);
+        let q1: Quarter = "2026-Q1".parse().unwrap();
+        let add = |title: &str, body: &str, state: PlanState, owners: &[&str], quarter: Quarter| {
+            let mut plan = plan::create_plan_with_body(&tmp, Some(title), body, plan::PlanDirNaming::Uuid).unwrap();
+            plan.meta.state = state;
+            plan.meta.owners = owners.iter().map(|o| o.to_string()).collect();
+            plan.meta.quarter = Some(quarter);
+            plan::save_plan(&tmp, &plan).unwrap();
+        };
+        let tickets = "## Tickets\n\n### Ticket 1: A\n\n**Status:** done\n\n- [x] a\n\n### Ticket 2: B\n\n- [ ] b\n";
+        add("Idea", "## Overview\n\nx\n", PlanState::Draft, &[], q1);
+        add("Empty", "## Overview\n\nx\n", PlanState::Approved, &["alice"], q1);
+        add("Search", tickets, PlanState::InProgress, &["bob"], q1);
+        add("Later", tickets, PlanState::Draft, &[], q1.next());
+
+        let config = ConfigFile::default();
+        let report = quarter_report(&tmp, q1, &config, NaiveDate::from_ymd_opt(2026, 2, 20).unwrap(), false).unwrap();
+        assert_eq!(report.elapsed_pct, 56);
+        let flags: Vec<(&str, Vec<&str>)> =
+            report.plans.iter().map(|r| (r.title.as_str(), r.flags.iter().map(String::as_str).collect())).collect();
+        assert_eq!(
+            flags,
+            vec![
+                ("Empty", vec!["no tickets"]),
+                ("Idea", vec!["still draft at 56% of quarter", "no owner"]),
+                ("Search", vec![]),
+            ]
+        );
+        let s = &report.summary;
+        assert_eq!((s.plans, s.tickets_done, s.tickets_total, s.flagged), (3, 1, 2, 2));
+        assert_eq!(s.owners, ["alice", "bob"]);
+
+        let md = render_markdown(&report);
+        assert!(md.contains("| State | Priority | Plan | Tickets | Checks | Optimized | Owners | Flags |"));
+        assert!(md.contains("| draft | - | Idea | - | - | 0/4 | - | still draft at 56% of quarter; no owner |"));
+        assert!(md.contains("| in_progress | - | Search | 1/2 | 1/2 | 0/4 | bob |  |"));
+        assert!(md.contains("- Tickets: 1/2 (50% done)"));
+        assert!(render_table(&report).contains("Needs attention: 2"));
+        let json: serde_json::Value = serde_json::from_str(&render(&report, ReportFormat::Json).unwrap()).unwrap();
+        assert_eq!((json["quarter"].as_str(), json["summary"]["plans"].as_u64()), (Some("2026-Q1"), Some(3)));
+
+        let ended = quarter_report(&tmp, q1, &config, NaiveDate::from_ymd_opt(2026, 4, 10).unwrap(), false).unwrap();
+        assert!(ended.plans.iter().all(|r| r.flags.contains(&"quarter ended before completion".to_string())));
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}
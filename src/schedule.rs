@@ -0,0 +1,394 @@
+//! Ticket scheduling: critical path, earliest start/finish per ticket, and a
+//! capacity-constrained schedule for a plan or for every plan in a quarter.
+//!
+//! Times are in working hours from the start of the schedule; reports convert
+//! them to days with `[capacity] hours_per_day`.
+
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::CapacityConfig;
+use crate::graph::{self, DepGraph, Node};
+use crate::plan::{self, Plan};
+use crate::quarter::Quarter;
+use crate::tickets;
+
+/// A ticket to schedule. `key` is unique across all plans in the schedule.
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub key: String,
+    pub plan: String,
+    pub ticket: String,
+    pub title: String,
+    /// None when the ticket has no estimate (scheduled as zero effort).
+    pub hours: Option<f64>,
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledTask {
+    pub key: String,
+    pub plan: String,
+    pub ticket: String,
+    pub title: String,
+    pub hours: Option<f64>,
+    /// Earliest start/finish ignoring capacity (critical path method).
+    pub earliest_start: f64,
+    pub earliest_finish: f64,
+    /// How long the ticket can slip without delaying the end of the schedule.
+    pub slack: f64,
+    pub critical: bool,
+    /// Start/finish in the capacity-constrained schedule.
+    pub start: f64,
+    pub finish: f64,
+    /// 1-based engineer the ticket is assigned to.
+    pub engineer: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Schedule {
+    pub scope: String,
+    pub tasks: Vec<ScheduledTask>,
+    pub critical_path: Vec<String>,
+    /// Length of the critical path in hours (lower bound on duration with unlimited engineers).
+    pub critical_path_hours: f64,
+    pub total_hours: f64,
+    pub engineers: u32,
+    pub hours_per_day: f64,
+    /// engineers × days × hours_per_day
+    pub capacity_hours: f64,
+    /// Duration of the capacity-constrained schedule in hours.
+    pub makespan_hours: f64,
+    pub overcommitted: bool,
+    pub unestimated: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Tasks for one plan's tickets, keyed `<plan slug>#<ticket id>`, plus warnings about
+/// unknown dependencies and unreadable estimates.
+pub fn plan_tasks(plan: &Plan, capacity: &CapacityConfig) -> (Vec<Task>, Vec<String>) {
+    let slug = plan::plan_slug(&plan.meta);
+    let tickets = tickets::parse_tickets(&plan.body);
+    let g = graph::ticket_graph(&tickets);
+    let mut warnings: Vec<String> = g
+        .unresolved
+        .iter()
+        .map(|(t, r)| format!("{}: ticket {} has unknown dependency \"{}\"", plan.meta.title, t, r))
+        .collect();
+    let mut tasks = vec![];
+    for t in &tickets {
+        if t.field("estimate").is_some() && t.estimate().is_none() {
+            warnings.push(format!("{}: ticket {} has an unreadable estimate", plan.meta.title, t.id));
+        }
+        tasks.push(Task {
+            key: format!("{}#{}", slug, t.id),
+            plan: plan.meta.title.clone(),
+            ticket: t.id.clone(),
+            title: t.title.clone(),
+            hours: t.estimate_hours(capacity),
+            depends_on: g.upstream(&t.id).iter().map(|u| format!("{}#{}", slug, u)).collect(),
+        });
+    }
+    (tasks, warnings)
+}
+
+/// Compute the critical path and a capacity-constrained schedule for `tasks`.
+/// Overcommitment is judged against `[capacity]`: total work vs. engineers × days, and
+/// the constrained schedule vs. the `days` window.
+pub fn schedule(scope: &str, tasks: &[Task], capacity: &CapacityConfig, mut warnings: Vec<String>) -> Result<Schedule> {
+    let g = DepGraph {
+        nodes: tasks
+            .iter()
+            .map(|t| Node { id: t.key.clone(), label: t.title.clone(), status: None })
+            .collect(),
+        edges: tasks
+            .iter()
+            .flat_map(|t| t.depends_on.iter().map(move |d| (d.clone(), t.key.clone())))
+            .collect(),
+        unresolved: vec![],
+    };
+    let order = g.topo_order()?;
+    let by_key: HashMap<&str, &Task> = tasks.iter().map(|t| (t.key.as_str(), t)).collect();
+    let dur = |k: &str| by_key.get(k).and_then(|t| t.hours).unwrap_or(0.0);
+
+    // Forward pass: earliest start/finish.
+    let mut es: HashMap<String, f64> = HashMap::new();
+    let mut ef: HashMap<String, f64> = HashMap::new();
+    for k in &order {
+        let start = g.upstream(k).iter().map(|u| ef[*u]).fold(0.0, f64::max);
+        es.insert(k.clone(), start);
+        ef.insert(k.clone(), start + dur(k));
+    }
+    let project_end = ef.values().cloned().fold(0.0, f64::max);
+
+    // Backward pass: latest finish, slack.
+    let mut lf: HashMap<String, f64> = HashMap::new();
+    for k in order.iter().rev() {
+        let finish = g
+            .edges
+            .iter()
+            .filter(|(from, _)| from == k)
+            .map(|(_, to)| lf[to] - dur(to))
+            .fold(project_end, f64::min);
+        lf.insert(k.clone(), finish);
+    }
+    let slack = |k: &str| (lf[k] - ef[k]).max(0.0);
+    let is_critical = |k: &str| slack(k) < 1e-9;
+
+    // Critical path: follow zero-slack tasks from a zero-start root along zero-slack edges.
+    let mut critical_path = vec![];
+    let mut current = order
+        .iter()
+        .filter(|k| is_critical(k) && g.upstream(k).is_empty())
+        .max_by(|a, b| ef[*a].total_cmp(&ef[*b]))
+        .cloned();
+    while let Some(k) = current {
+        critical_path.push(k.clone());
+        current = g
+            .edges
+            .iter()
+            .filter(|(from, to)| from == &k && is_critical(to) && (es[to] - ef[&k]).abs() < 1e-9)
+            .map(|(_, to)| to.clone())
+            .next();
+    }
+
+    // Capacity-constrained list scheduling: among ready tasks pick least slack first.
+    let engineers = capacity.engineers.max(1);
+    let mut free_at = vec![0.0f64; engineers as usize];
+    let mut done: HashMap<String, (f64, f64, u32)> = HashMap::new();
+    let mut pending: Vec<&String> = order.iter().collect();
+    while !pending.is_empty() {
+        let pos = pending
+            .iter()
+            .enumerate()
+            .filter(|(_, k)| g.upstream(k).iter().all(|u| done.contains_key(*u)))
+            .min_by(|(_, a), (_, b)| slack(a).total_cmp(&slack(b)).then(es[**a].total_cmp(&es[**b])))
+            .map(|(i, _)| i)
+            .expect("topological order guarantees a ready task");
+        let k = pending.remove(pos);
+        let ready_at = g.upstream(k).iter().map(|u| done[*u].1).fold(0.0, f64::max);
+        let (eng, free) = free_at
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.max(ready_at).total_cmp(&b.1.max(ready_at)).then(a.0.cmp(&b.0)))
+            .map(|(i, f)| (i, *f))
+            .expect("at least one engineer");
+        let start = free.max(ready_at);
+        let finish = start + dur(k);
+        free_at[eng] = finish;
+        done.insert(k.clone(), (start, finish, eng as u32 + 1));
+    }
+
+    let unestimated: Vec<String> = tasks.iter().filter(|t| t.hours.is_none()).map(|t| t.key.clone()).collect();
+    if !unestimated.is_empty() {
+        warnings.push(format!("{} ticket(s) without an estimate are scheduled as zero effort", unestimated.len()));
+    }
+    let total_hours: f64 = tasks.iter().filter_map(|t| t.hours).sum();
+    let capacity_hours = engineers as f64 * capacity.days as f64 * capacity.hours_per_day;
+    let window_hours = capacity.days as f64 * capacity.hours_per_day;
+    let makespan_hours = done.values().map(|(_, f, _)| *f).fold(0.0, f64::max);
+    let overcommitted = total_hours > capacity_hours || makespan_hours > window_hours;
+
+    let scheduled = order
+        .iter()
+        .map(|k| {
+            let t = by_key[k.as_str()];
+            let (start, finish, engineer) = done[k];
+            ScheduledTask {
+                key: k.clone(),
+                plan: t.plan.clone(),
+                ticket: t.ticket.clone(),
+                title: t.title.clone(),
+                hours: t.hours,
+                earliest_start: es[k],
+                earliest_finish: ef[k],
+                slack: slack(k),
+                critical: is_critical(k),
+                start,
+                finish,
+                engineer,
+            }
+        })
+        .collect();
+    Ok(Schedule {
+        scope: scope.to_string(),
+        tasks: scheduled,
+        critical_path,
+        critical_path_hours: project_end,
+        total_hours,
+        engineers,
+        hours_per_day: capacity.hours_per_day,
+        capacity_hours,
+        makespan_hours,
+        overcommitted,
+        unestimated,
+        warnings,
+    })
+}
+
+/// Schedule the tickets of one plan.
+pub fn schedule_plan(qp_root: &Path, plan_ref: &str, capacity: &CapacityConfig) -> Result<Schedule> {
+    let plan = plan::get_plan(qp_root, plan_ref)?;
+    let (tasks, warnings) = plan_tasks(&plan, capacity);
+    schedule(&plan.meta.title, &tasks, capacity, warnings)
+}
+
+/// Schedule every ticket of every active plan assigned to `quarter`. A plan's tickets
+/// wait for all tickets of the plans it depends on (when those are in the same quarter).
+pub fn schedule_quarter(qp_root: &Path, quarter: Quarter, capacity: &CapacityConfig) -> Result<Schedule> {
+    let metas: Vec<_> = plan::list_plans(qp_root)?
+        .into_iter()
+        .filter(|m| m.quarter == Some(quarter))
+        .collect();
+    let mut tasks: Vec<Task> = vec![];
+    let mut warnings = vec![];
+    let mut keys_by_plan: HashMap<String, Vec<String>> = HashMap::new();
+    for m in &metas {
+        let plan = plan::get_plan(qp_root, &m.id)?;
+        let (t, w) = plan_tasks(&plan, capacity);
+        if t.is_empty() {
+            warnings.push(format!("{}: no tickets to schedule", m.title));
+        }
+        keys_by_plan.insert(m.id.clone(), t.iter().map(|x| x.key.clone()).collect());
+        tasks.extend(t);
+        warnings.extend(w);
+    }
+    let plan_of: HashMap<String, String> = keys_by_plan
+        .iter()
+        .flat_map(|(p, keys)| keys.iter().map(move |k| (k.clone(), p.clone())))
+        .collect();
+    for task in &mut tasks {
+        let Some(meta) = metas.iter().find(|m| plan_of.get(&task.key) == Some(&m.id)) else {
+            continue;
+        };
+        for up in &meta.depends_on {
+            if let Some(keys) = keys_by_plan.get(up) {
+                task.depends_on.extend(keys.iter().cloned());
+            }
+        }
+    }
+    schedule(&quarter.to_string(), &tasks, capacity, warnings)
+}
+
+/// Terminal rendering: per-ticket table, critical path and capacity summary.
+pub fn render_schedule(s: &Schedule, show_plan: bool) -> String {
+    let days = |h: f64| h / s.hours_per_day;
+    let fmt_days = |h: f64| format!("{:.1}", days(h));
+    let mut out = format!("{}\n\n", format!("Schedule: {}", s.scope).bold());
+    if s.tasks.is_empty() {
+        out.push_str("No tickets to schedule.\n");
+        return out;
+    }
+    let mut headers = vec!["", "Ticket", "Title"];
+    if show_plan {
+        headers.insert(1, "Plan");
+    }
+    headers.extend(["Est (d)", "ES", "EF", "Slack", "Start", "Finish", "Eng"]);
+    let rows: Vec<Vec<String>> = s
+        .tasks
+        .iter()
+        .map(|t| {
+            let mut row = vec![if t.critical { "*".to_string() } else { String::new() }];
+            if show_plan {
+                row.push(t.plan.clone());
+            }
+            row.extend([
+                t.ticket.clone(),
+                t.title.clone(),
+                t.hours.map(fmt_days).unwrap_or_else(|| "?".to_string()),
+                fmt_days(t.earliest_start),
+                fmt_days(t.earliest_finish),
+                fmt_days(t.slack),
+                fmt_days(t.start),
+                fmt_days(t.finish),
+                t.engineer.to_string(),
+            ]);
+            row
+        })
+        .collect();
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in &rows {
+        for (w, c) in widths.iter_mut().zip(row) {
+            *w = (*w).max(c.chars().count());
+        }
+    }
+    let line = |cells: Vec<String>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!("{:<width$}", c, width = w))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    out.push_str(&format!("{}\n", line(headers.iter().map(|h| h.to_string()).collect()).bold()));
+    for row in rows {
+        out.push_str(&format!("{}\n", line(row)));
+    }
+    out.push_str("\n* = on the critical path; times are working days from the start of the schedule\n\n");
+    let titles: Vec<String> = s
+        .critical_path
+        .iter()
+        .filter_map(|k| s.tasks.iter().find(|t| &t.key == k))
+        .map(|t| if show_plan { format!("{} #{}", t.plan, t.ticket) } else { format!("#{} {}", t.ticket, t.title) })
+        .collect();
+    out.push_str(&format!("Critical path: {} ({} days)\n", titles.join(" -> "), fmt_days(s.critical_path_hours)));
+    out.push_str(&format!(
+        "Work: {} days across {} engineer(s); capacity {} days\n",
+        fmt_days(s.total_hours),
+        s.engineers,
+        fmt_days(s.capacity_hours)
+    ));
+    out.push_str(&format!("Finishes after {} working days with current capacity\n", fmt_days(s.makespan_hours)));
+    if s.overcommitted {
+        out.push_str(&format!("{}\n", "OVERCOMMITTED: work does not fit in the configured capacity".red().bold()));
+    }
+    for w in &s.warnings {
+        out.push_str(&format!("{} {}\n", "warning:".yellow(), w));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(key: &str, hours: f64, deps: &[&str]) -> Task {
+        Task {
+            key: key.to_string(),
+            plan: "p".to_string(),
+            ticket: key.to_string(),
+            title: key.to_string(),
+            hours: Some(hours),
+            depends_on: deps.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_critical_path_and_capacity() {
+        let tasks = vec![
+            task("a", 6.0, &[]),
+            task("b", 12.0, &["a"]),
+            task("c", 6.0, &["a"]),
+            task("d", 6.0, &["b", "c"]),
+        ];
+        let capacity = CapacityConfig { engineers: 1, days: 4, hours_per_day: 6.0, ..Default::default() };
+        let s = schedule("test", &tasks, &capacity, vec![]).unwrap();
+        assert_eq!(s.critical_path, vec!["a", "b", "d"]);
+        assert_eq!(s.critical_path_hours, 24.0);
+        let c = s.tasks.iter().find(|t| t.key == "c").unwrap();
+        assert_eq!((c.earliest_start, c.slack), (6.0, 6.0));
+        // One engineer must do all 30h of work: more than 4 days × 6h.
+        assert_eq!(s.makespan_hours, 30.0);
+        assert!(s.overcommitted);
+
+        let two = CapacityConfig { engineers: 2, ..capacity };
+        let s = schedule("test", &tasks, &two, vec![]).unwrap();
+        assert_eq!(s.makespan_hours, 24.0);
+        assert!(!s.overcommitted);
+    }
+}
@@ -0,0 +1,255 @@
+//! Aggregate statistics for `qp stats`, written to `.qp/stats.toml`.
+//!
+//! Everything is derived from plan metadata (`state`, `state_history`, `review_steps`),
+//! the per-step changelogs and the first snapshot in `history/`. Archived plans count;
+//! trashed plans do not. Plans saved before qp recorded `state_history` contribute no
+//! time-in-state data unless they are still drafts.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::changelog;
+use crate::config::ConfigFile;
+use crate::plan::{self, PlanLocation, PlanMeta, PlanState};
+
+const STATS_FILE: &str = "stats.toml";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StepStats {
+    pub runs: usize,
+    pub failed: usize,
+    /// Net words added across runs with a recorded changelog.
+    pub words_added: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuarterStats {
+    pub plans: usize,
+    pub completed: usize,
+}
+
+/// Contents of `.qp/stats.toml` and `qp stats --format json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub generated_at: String,
+    /// Only plans updated on or after this date were counted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    pub total: usize,
+    pub completed: usize,
+    pub with_optimization: usize,
+    /// Completed plans ÷ plans that got past draft.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success_rate: Option<f64>,
+    pub avg_optimizations: f64,
+    /// Word counts of the first snapshot and the current body, over plans with history.
+    pub words_draft: usize,
+    pub words_final: usize,
+    pub plans_with_history: usize,
+    pub by_state: BTreeMap<String, usize>,
+    /// Average days a plan spends in each state (current state counted up to now).
+    pub avg_days_in_state: BTreeMap<String, f64>,
+    pub steps: BTreeMap<String, StepStats>,
+    /// Step runs per agent command.
+    pub agents: BTreeMap<String, usize>,
+    pub quarters: BTreeMap<String, QuarterStats>,
+}
+
+fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// `(state, hours)` intervals from `created_at` through `state_history` to `now`.
+/// None when the plan left draft before transitions were recorded.
+pub fn time_in_states(meta: &PlanMeta, now: DateTime<Utc>) -> Option<Vec<(PlanState, f64)>> {
+    if meta.state_history.is_empty() && meta.state != PlanState::Draft {
+        return None;
+    }
+    let mut out = vec![];
+    let mut state = meta.state_history.first().map(|c| c.from).unwrap_or(meta.state);
+    let mut since = parse_time(&meta.created_at)?;
+    for change in &meta.state_history {
+        let at = parse_time(&change.at)?;
+        out.push((state, (at - since).num_seconds().max(0) as f64 / 3600.0));
+        state = change.to;
+        since = at;
+    }
+    if state != PlanState::Completed {
+        out.push((state, (now - since).num_seconds().max(0) as f64 / 3600.0));
+    }
+    Some(out)
+}
+
+fn first_snapshot_words(qp_root: &Path, plan_id: &str) -> Option<usize> {
+    let dir = plan::plan_dir(qp_root, plan_id).join("history");
+    let first = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            let n: u32 = name.strip_prefix('v')?.strip_suffix(".md")?.parse().ok()?;
+            Some((n, e.path()))
+        })
+        .min_by_key(|(n, _)| *n)?;
+    let content = std::fs::read_to_string(first.1).ok()?;
+    let body = plan::parse_plan(&content).map(|p| p.body).unwrap_or(content);
+    Some(changelog::word_count(&body))
+}
+
+/// Compute statistics over active and archived plans, optionally only those updated since `since`.
+pub fn compute(qp_root: &Path, config: &ConfigFile, since: Option<NaiveDate>) -> Result<Stats> {
+    let now = Utc::now();
+    let since_time = since.map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc());
+    let recent = |at: &str| match (since_time, parse_time(at)) {
+        (Some(s), Some(t)) => t >= s,
+        (Some(_), None) => false,
+        (None, _) => true,
+    };
+    let mut metas = plan::list_plans_in(qp_root, PlanLocation::Active)?;
+    metas.extend(plan::list_plans_in(qp_root, PlanLocation::Archived)?);
+    metas.retain(|m| recent(&m.updated_at));
+
+    let mut stats = Stats {
+        generated_at: now.to_rfc3339(),
+        since: since.map(|d| d.to_string()),
+        total: metas.len(),
+        ..Default::default()
+    };
+    let mut state_hours: BTreeMap<String, (f64, usize)> = BTreeMap::new();
+    let mut past_draft = 0;
+    for meta in &metas {
+        let state = meta.state.to_string();
+        *stats.by_state.entry(state).or_default() += 1;
+        let completed = meta.state == PlanState::Completed;
+        stats.completed += completed as usize;
+        stats.with_optimization += (meta.review_cycles > 0) as usize;
+        past_draft += (meta.state != PlanState::Draft) as usize;
+        stats.avg_optimizations += meta.review_cycles as f64;
+        if let Some(q) = &meta.quarter {
+            let entry = stats.quarters.entry(q.to_string()).or_default();
+            entry.plans += 1;
+            entry.completed += completed as usize;
+        }
+        if let Some(intervals) = time_in_states(meta, now) {
+            let mut per_plan: BTreeMap<String, f64> = BTreeMap::new();
+            for (state, hours) in intervals {
+                *per_plan.entry(state.to_string()).or_default() += hours;
+            }
+            for (state, hours) in per_plan {
+                let e = state_hours.entry(state).or_default();
+                e.0 += hours;
+                e.1 += 1;
+            }
+        }
+        if let Some(draft) = first_snapshot_words(qp_root, &meta.id) {
+            let current = plan::get_plan(qp_root, &meta.id).map(|p| changelog::word_count(&p.body))?;
+            stats.words_draft += draft;
+            stats.words_final += current;
+            stats.plans_with_history += 1;
+        }
+
+        // Step runs: one per recorded changelog, plus done steps that predate changelogs.
+        let changes = changelog::load_all(qp_root, &meta.id)?;
+        let default_agent = |step: &str| {
+            meta.review_agents
+                .as_ref()
+                .and_then(|m| m.get(step).cloned())
+                .or_else(|| config.step_agent(step).ok().map(|a| a.command))
+                .unwrap_or_else(|| config.agent.command.clone())
+        };
+        for c in changes.iter().filter(|c| since.is_none() || recent(&c.created_at)) {
+            let Some(step) = &c.step else { continue };
+            let entry = stats.steps.entry(step.clone()).or_default();
+            entry.runs += 1;
+            entry.words_added += c.words_delta();
+            let agent = c.agent.clone().unwrap_or_else(|| default_agent(step));
+            *stats.agents.entry(agent).or_default() += 1;
+        }
+        for rs in &meta.review_steps {
+            if !rs.completed_at.as_deref().is_some_and(&recent) {
+                continue;
+            }
+            let logged = changes.iter().any(|c| c.step.as_deref() == Some(&rs.step));
+            match rs.status.as_str() {
+                "failed" => stats.steps.entry(rs.step.clone()).or_default().failed += 1,
+                "done" if !logged => {
+                    stats.steps.entry(rs.step.clone()).or_default().runs += 1;
+                    *stats.agents.entry(default_agent(&rs.step)).or_default() += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+    if stats.total > 0 {
+        stats.avg_optimizations /= stats.total as f64;
+    }
+    stats.success_rate = (past_draft > 0).then(|| stats.completed as f64 / past_draft as f64);
+    stats.avg_days_in_state = state_hours
+        .into_iter()
+        .map(|(state, (hours, plans))| (state, hours / 24.0 / plans as f64))
+        .collect();
+    Ok(stats)
+}
+
+/// Write `.qp/stats.toml`.
+pub fn save(qp_root: &Path, stats: &Stats) -> Result<()> {
+    let path = qp_root.join(STATS_FILE);
+    std::fs::write(&path, toml::to_string(stats)?).with_context(|| format!("write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::StateChange;
+
+    #[test]
+    fn test_time_in_states() {
+        let tmp = std::env::temp_dir().join("qp_test_stats");
+        let _ = std::fs::remove_dir_all(&tmp);
+        let mut plan = plan::create_plan(&tmp, Some("Stats"), plan::PlanDirNaming::Uuid).unwrap();
+        plan.meta.created_at = "2026-01-01T00:00:00+00:00".to_string();
+        plan.meta.state = PlanState::Approved;
+        plan::save_plan(&tmp, &plan).unwrap();
+        // `plan` still has an empty history; saving keeps the Draft -> Approved entry from disk.
+        plan.meta.state = PlanState::Completed;
+        plan::save_plan(&tmp, &plan).unwrap();
+        let mut meta = plan::get_plan(&tmp, &plan.meta.id).unwrap().meta;
+        assert_eq!(meta.state_history.len(), 2);
+        assert_eq!((meta.state_history[1].from, meta.state_history[1].to), (PlanState::Approved, PlanState::Completed));
+
+        meta.state_history = vec![
+            StateChange { from: PlanState::Draft, to: PlanState::Approved, at: "2026-01-03T00:00:00+00:00".into() },
+            StateChange { from: PlanState::Approved, to: PlanState::Completed, at: "2026-01-04T12:00:00+00:00".into() },
+        ];
+        let intervals = time_in_states(&meta, Utc::now()).unwrap();
+        assert_eq!(intervals, vec![(PlanState::Draft, 48.0), (PlanState::Approved, 36.0)]);
+
+        let stats = compute(&tmp, &ConfigFile::default(), None).unwrap();
+        assert_eq!((stats.total, stats.completed, stats.success_rate), (1, 1, Some(1.0)));
+        assert!(compute(&tmp, &ConfigFile::default(), NaiveDate::from_ymd_opt(2999, 1, 1)).unwrap().total == 0);
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_review_step_is_counted() {
+        let tmp = std::env::temp_dir().join("qp_test_stats_failed");
+        let _ = std::fs::remove_dir_all(&tmp);
+        let mut plan = plan::create_plan(&tmp, Some("Flaky"), plan::PlanDirNaming::Uuid).unwrap();
+        plan.meta.state = PlanState::Approved;
+        plan::save_plan(&tmp, &plan).unwrap();
+        let mut config = ConfigFile::default();
+        let holes = config.review_agents.get_mut("holes").unwrap();
+        (holes.command, holes.args, holes.prompt) = ("false".to_string(), vec![], "Find holes.".to_string());
+
+        assert!(crate::optimize::run_step(&tmp, &plan.meta.id, "holes", &config).is_err());
+        let meta = plan::get_plan(&tmp, &plan.meta.id).unwrap().meta;
+        assert_eq!((meta.state, meta.review_steps[0].status.as_str()), (PlanState::Approved, "failed"));
+        let steps = compute(&tmp, &config, None).unwrap().steps;
+        assert_eq!((steps["holes"].runs, steps["holes"].failed), (0, 1));
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}
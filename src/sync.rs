@@ -0,0 +1,204 @@
+//! Pull issue state back from the tracker into `plan.md`.
+//!
+//! Uses the ticket-to-issue mapping in `tracker.json` (written by `qp export --apply`).
+//! Closed issues mark their ticket `**Status:** done`, reopened ones `open`; when every
+//! ticket is done the plan moves to `completed`. A ticket whose content changed locally
+//! while its issue also changed remotely since the last export/sync is a conflict: its
+//! status is still synced, but it keeps being reported until synced with `--force`.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::config::ConfigFile;
+use crate::plan::{self, PlanState};
+use crate::tickets;
+use crate::tracker::{self, TrackerClient};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketSync {
+    pub ticket: String,
+    pub title: String,
+    pub issue: u64,
+    pub remote_state: String,
+    /// Status written to the ticket, when it changed.
+    pub status_changed_to: Option<String>,
+    pub local_changed: bool,
+    pub remote_changed: bool,
+    pub conflict: bool,
+    /// The mapped ticket no longer exists in plan.md.
+    pub missing_locally: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncReport {
+    pub plan: String,
+    pub tracker: String,
+    pub repo: String,
+    pub tickets: Vec<TicketSync>,
+    /// Tickets in plan.md that were never exported.
+    pub unmapped: Vec<String>,
+    pub completed: bool,
+}
+
+impl SyncReport {
+    pub fn conflicts(&self) -> impl Iterator<Item = &TicketSync> {
+        self.tickets.iter().filter(|t| t.conflict)
+    }
+}
+
+/// Status line value for a remote issue state.
+fn status_for(closed: bool) -> &'static str {
+    if closed {
+        "done"
+    } else {
+        "open"
+    }
+}
+
+/// Sync one plan. `base_url` overrides the URL recorded at export time. With `force`,
+/// conflicting tickets get a new baseline (local and remote versions both accepted).
+pub fn sync_plan(
+    qp_root: &Path,
+    plan_ref: &str,
+    config: &ConfigFile,
+    base_url: Option<&str>,
+    force: bool,
+) -> Result<SyncReport> {
+    let mut plan = plan::get_plan(qp_root, plan_ref)?;
+    let dir = plan::plan_dir(qp_root, &plan.meta.id);
+    let mut map = tracker::load_map(&dir)?.with_context(|| {
+        format!("{} has not been exported; run `qp export <plan> --to github-issues --apply` first", plan.meta.title)
+    })?;
+    let client = TrackerClient::new(
+        map.tracker,
+        &config.tracker,
+        Some(base_url.unwrap_or(&map.base_url)),
+        Some(&map.repo),
+    )?;
+
+    let mut body = plan.body.clone();
+    let mut results = vec![];
+    for link in map.issues.iter_mut() {
+        let issue = client.get_issue(link.number)?;
+        let ticket = tickets::parse_tickets(&body).into_iter().find(|t| t.id == link.ticket);
+        let remote_changed = issue.updated_at != link.remote_updated_at;
+        let Some(ticket) = ticket else {
+            results.push(TicketSync {
+                ticket: link.ticket.clone(),
+                title: link.title.clone(),
+                issue: link.number,
+                remote_state: issue.state.clone(),
+                status_changed_to: None,
+                local_changed: true,
+                remote_changed,
+                conflict: remote_changed,
+                missing_locally: true,
+            });
+            continue;
+        };
+        let local_changed = tracker::ticket_hash(&ticket) != link.ticket_hash;
+        let conflict = local_changed && remote_changed;
+
+        // Closed issues need an explicit done status; open ones only touch tickets that look done.
+        let needs_update = if issue.is_closed() {
+            !(ticket.field("status").is_some() && ticket.is_done())
+        } else {
+            ticket.is_done()
+        };
+        let mut status_changed_to = None;
+        if needs_update {
+            let wanted = status_for(issue.is_closed());
+            body = tickets::set_ticket_field(&body, &ticket.id, "Status", wanted).unwrap_or(body);
+            status_changed_to = Some(wanted.to_string());
+        }
+
+        if !conflict || force {
+            if let Some(t) = tickets::parse_tickets(&body).into_iter().find(|t| t.id == link.ticket) {
+                link.ticket_hash = tracker::ticket_hash(&t);
+            }
+            link.remote_updated_at = issue.updated_at.clone();
+        }
+        link.remote_state = Some(issue.state.clone());
+        results.push(TicketSync {
+            ticket: ticket.id.clone(),
+            title: ticket.title.clone(),
+            issue: link.number,
+            remote_state: issue.state,
+            status_changed_to,
+            local_changed,
+            remote_changed,
+            conflict: conflict && !force,
+            missing_locally: false,
+        });
+    }
+
+    let all = tickets::parse_tickets(&body);
+    let unmapped: Vec<String> = all
+        .iter()
+        .filter(|t| map.issue_for(&t.id).is_none())
+        .map(|t| t.id.clone())
+        .collect();
+    let all_done = !all.is_empty() && all.iter().all(|t| t.is_done());
+    let completed = all_done && plan.meta.state != PlanState::Completed;
+
+    if body != plan.body || completed {
+        plan.body = body;
+        if completed {
+            plan.meta.state = PlanState::Completed;
+        }
+        plan.meta.updated_at = Utc::now().to_rfc3339();
+        plan::save_plan(qp_root, &plan)?;
+    }
+    map.synced_at = Some(Utc::now().to_rfc3339());
+    tracker::save_map(&dir, &map)?;
+
+    Ok(SyncReport {
+        plan: plan.meta.title.clone(),
+        tracker: map.tracker.to_string(),
+        repo: map.repo.clone(),
+        tickets: results,
+        unmapped,
+        completed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracker::mock::MockGithub;
+
+    #[test]
+    fn test_sync_closed_issues_and_conflicts() {
+        let tmp = std::env::temp_dir().join("qp_test_sync");
+        let _ = std::fs::remove_dir_all(&tmp);
+        let body = "## Tickets\n\n### Ticket 1: Setup\n\n**Summary:** Set up.\n\n### Ticket 2: Auth\n\n**Summary:** Log in.\n";
+        let plan = plan::create_plan_with_body(&tmp, Some("Gateway"), body, plan::PlanDirNaming::Uuid).unwrap();
+        let server = MockGithub::start();
+        let mut config = ConfigFile::default();
+        config.tracker.token_env = Some("QP_TEST_NO_TOKEN".to_string());
+        crate::export::apply(&tmp, &plan.meta.id, &server.client(), &config).unwrap();
+
+        server.close(1);
+        let report = sync_plan(&tmp, &plan.meta.id, &config, None, false).unwrap();
+        assert_eq!(report.tickets[0].status_changed_to.as_deref(), Some("done"));
+        assert!(report.tickets[1].status_changed_to.is_none());
+        assert!(!report.completed && report.conflicts().count() == 0);
+        let mut plan = plan::get_plan(&tmp, &plan.meta.id).unwrap();
+        assert!(tickets::parse_tickets(&plan.body)[0].is_done());
+
+        // Ticket 2 edited locally while its issue was closed: status syncs, conflict stays until --force.
+        plan.body = plan.body.replace("Log in.", "Log in with SSO.");
+        plan::save_plan(&tmp, &plan).unwrap();
+        server.close(2);
+        let report = sync_plan(&tmp, &plan.meta.id, &config, None, false).unwrap();
+        assert!(report.completed);
+        assert_eq!(report.conflicts().map(|t| t.ticket.as_str()).collect::<Vec<_>>(), vec!["2"]);
+        assert_eq!(plan::get_plan(&tmp, &plan.meta.id).unwrap().meta.state, PlanState::Completed);
+        assert_eq!(sync_plan(&tmp, &plan.meta.id, &config, None, false).unwrap().conflicts().count(), 1);
+        assert_eq!(sync_plan(&tmp, &plan.meta.id, &config, None, true).unwrap().conflicts().count(), 0);
+        assert_eq!(sync_plan(&tmp, &plan.meta.id, &config, None, false).unwrap().conflicts().count(), 0);
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}
@@ -0,0 +1,558 @@
+//! Parse the `## Tickets` section of a plan body into structured tickets.
+//!
+//! Tickets are the shallowest headings inside `## Tickets` (usually `### Ticket 1: Title`).
+//! Deeper headings (`#### Steps`, `#### Acceptance Criteria`) belong to the ticket above them.
+//! Ticket fields are lines such as `**Summary:** ...` or `Definition of Done: ...`.
+//! Numbered items under a `#### Steps` heading are the ticket's steps; a step may carry
+//! its own estimate (`- Estimate: 1h`).
+
+use serde::Serialize;
+
+use crate::config::CapacityConfig;
+use crate::plan::title_to_slug;
+
+/// Field names recognized without bold markup (`Summary: ...`).
+const PLAIN_FIELDS: &[&str] = &["summary", "definition of done", "status", "depends on", "estimate"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateUnit {
+    Hours,
+    Days,
+    Points,
+}
+
+/// Effort estimate from an `**Estimate:**` line, e.g. `4h`, `1.5 days`, `3pt`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Estimate {
+    pub value: f64,
+    pub unit: EstimateUnit,
+}
+
+impl Estimate {
+    /// Effort in hours, converting days and points with `[capacity]` settings.
+    pub fn hours(&self, capacity: &CapacityConfig) -> f64 {
+        match self.unit {
+            EstimateUnit::Hours => self.value,
+            EstimateUnit::Days => self.value * capacity.hours_per_day,
+            EstimateUnit::Points => self.value * capacity.hours_per_point,
+        }
+    }
+}
+
+impl std::fmt::Display for Estimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = match self.unit {
+            EstimateUnit::Hours => "h",
+            EstimateUnit::Days => "d",
+            EstimateUnit::Points => "pt",
+        };
+        write!(f, "{}{}", self.value, unit)
+    }
+}
+
+impl std::str::FromStr for Estimate {
+    type Err = anyhow::Error;
+    /// Accepts `4`, `4h`, `4 hours`, `1.5d`, `2 days`, `3pt`, `5 points`. A bare number is hours.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let t = s.trim().trim_start_matches('~').to_lowercase();
+        let split = t
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(t.len());
+        let (num, unit) = t.split_at(split);
+        let value: f64 = num
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid estimate: {} (expected e.g. 4h, 2d or 3pt)", s))?;
+        let unit = match unit.trim() {
+            "" | "h" | "hr" | "hrs" | "hour" | "hours" => EstimateUnit::Hours,
+            "d" | "day" | "days" => EstimateUnit::Days,
+            "pt" | "pts" | "sp" | "point" | "points" => EstimateUnit::Points,
+            other => anyhow::bail!("unknown estimate unit: {} (expected h, d or pt)", other),
+        };
+        Ok(Self { value, unit })
+    }
+}
+
+/// Byte offsets of the last estimate marker in `text` and of the value after it. Markers are
+/// `(Estimate:`, `- Estimate:`, `**Estimate:**` or `Estimate:` at the start of the text, so
+/// a title that merely mentions the word is not cut.
+fn estimate_marker(text: &str) -> Option<(usize, usize)> {
+    // ASCII lowercasing keeps byte offsets valid for slicing `text`.
+    let lower = text.to_ascii_lowercase();
+    let mut found = ["(estimate:", "- estimate:", "**estimate:**"]
+        .iter()
+        .filter_map(|m| lower.rfind(m).map(|at| (at, at + m.len())))
+        .collect::<Vec<_>>();
+    let indent = lower.len() - lower.trim_start().len();
+    if lower[indent..].starts_with("estimate:") {
+        found.push((indent, indent + "estimate:".len()));
+    }
+    found.into_iter().max_by_key(|&(at, end)| (at, std::cmp::Reverse(end)))
+}
+
+/// Parse the estimate following the last "Estimate:" marker in `text`,
+/// e.g. `**Create model** (Estimate: 1h)` or `- **Estimate:** 2pt`.
+fn inline_estimate(text: &str) -> Option<Estimate> {
+    let (_, value_at) = estimate_marker(text)?;
+    let rest = text[value_at..].trim_start_matches(['*', ':', ' ']);
+    let value = rest.split(['(', ')', ',', ';', '*']).next()?.trim();
+    value.parse().ok()
+}
+
+/// A numbered item under a ticket's `#### Steps` heading.
+#[derive(Debug, Clone, Serialize)]
+pub struct Step {
+    pub number: u32,
+    pub title: String,
+    pub estimate: Option<Estimate>,
+}
+
+/// Sum of ticket estimates for a plan.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EstimateTotals {
+    /// All estimates converted to hours.
+    pub hours: f64,
+    /// Raw sum of estimates given in points (also included in `hours`).
+    pub points: f64,
+    pub estimated: usize,
+    pub tickets: usize,
+}
+
+impl std::fmt::Display for EstimateTotals {
+    /// e.g. "26h (3/4 tickets estimated)" or "26h incl. 5pt (4/4 tickets estimated)".
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}h", round1(self.hours))?;
+        if self.points > 0.0 {
+            write!(f, " incl. {}pt", round1(self.points))?;
+        }
+        write!(f, " ({}/{} tickets estimated)", self.estimated, self.tickets)
+    }
+}
+
+fn round1(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+/// Estimate totals across tickets (see [`Ticket::estimate_hours`]).
+pub fn estimate_totals(tickets: &[Ticket], capacity: &CapacityConfig) -> EstimateTotals {
+    let mut totals = EstimateTotals { tickets: tickets.len(), ..Default::default() };
+    for t in tickets {
+        if let Some(h) = t.estimate_hours(capacity) {
+            totals.hours += h;
+            totals.estimated += 1;
+            totals.points += t.estimate_points();
+        }
+    }
+    totals
+}
+
+#[derive(Debug, Clone)]
+pub struct Ticket {
+    /// Short reference: the number in "Ticket 3: ..." or the title slug.
+    pub id: String,
+    /// Heading text without the "Ticket N:" prefix.
+    pub title: String,
+    /// Heading depth (number of `#`).
+    pub level: usize,
+    /// `(lowercase key, value)` pairs from `**Key:** value` lines, in order.
+    pub fields: Vec<(String, String)>,
+    /// Markdown under the heading, up to the next ticket.
+    pub body: String,
+    pub checks_done: usize,
+    pub checks_total: usize,
+    /// Numbered items under a `Steps` sub-heading.
+    pub steps: Vec<Step>,
+    /// Line index of the heading in the plan body, and of the first line after the ticket.
+    pub lines: (usize, usize),
+}
+
+impl Ticket {
+    /// First value for a field (case-insensitive key).
+    pub fn field(&self, key: &str) -> Option<&str> {
+        let key = key.to_lowercase();
+        self.fields
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn summary(&self) -> Option<&str> {
+        self.field("summary")
+    }
+
+    pub fn definition_of_done(&self) -> Option<&str> {
+        self.field("definition of done")
+    }
+
+    /// Ticket references from a `**Depends on:**` (or `**Blocked by:**`) line, separated by
+    /// commas, semicolons or list items. "and" is not a separator, since titles contain it.
+    pub fn depends_on(&self) -> Vec<String> {
+        let Some(value) = self.field("depends on").or_else(|| self.field("blocked by")) else {
+            return vec![];
+        };
+        value
+            .split([',', ';', '\n'])
+            .map(|s| s.trim().trim_start_matches("- ").trim().trim_end_matches('.').trim())
+            .filter(|s| !s.is_empty() && !s.eq_ignore_ascii_case("none") && *s != "-")
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Parsed `**Estimate:**` value, if present and valid. Text after the estimate
+    /// (e.g. "4h (includes review)") is ignored.
+    pub fn estimate(&self) -> Option<Estimate> {
+        let value = self.field("estimate")?;
+        let first = value.split(['(', ',']).next()?.trim();
+        first.parse().ok()
+    }
+
+    /// Effort in hours: the ticket's own estimate, or else the sum of its step estimates.
+    pub fn estimate_hours(&self, capacity: &CapacityConfig) -> Option<f64> {
+        if let Some(e) = self.estimate() {
+            return Some(e.hours(capacity));
+        }
+        let steps: Vec<Estimate> = self.steps.iter().filter_map(|s| s.estimate).collect();
+        (!steps.is_empty()).then(|| steps.iter().map(|e| e.hours(capacity)).sum())
+    }
+
+    /// Points counted by [`Ticket::estimate_hours`], for reporting point totals.
+    fn estimate_points(&self) -> f64 {
+        let points = |e: &Estimate| if e.unit == EstimateUnit::Points { e.value } else { 0.0 };
+        match self.estimate() {
+            Some(e) => points(&e),
+            None => self.steps.iter().filter_map(|s| s.estimate.as_ref()).map(points).sum(),
+        }
+    }
+
+    /// Markdown under a sub-heading of this ticket whose text starts with `name`
+    /// (case-insensitive), e.g. `section("acceptance criteria")`.
+    pub fn section(&self, name: &str) -> Option<String> {
+        let name = name.to_lowercase();
+        let mut in_fence = false;
+        let mut level = None;
+        let mut out: Vec<&str> = vec![];
+        for line in self.body.lines() {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+            }
+            let h = if in_fence { None } else { heading(line) };
+            match (level, h) {
+                (None, Some((l, text))) if text.to_lowercase().starts_with(&name) => level = Some(l),
+                (Some(current), Some((l, _))) if l <= current => break,
+                (Some(_), _) => out.push(line),
+                _ => {}
+            }
+        }
+        level.map(|_| out.join("\n").trim().to_string())
+    }
+
+    /// Done when its status says so, or when it has checkboxes and all are ticked.
+    pub fn is_done(&self) -> bool {
+        match self.field("status").map(|s| s.to_lowercase()) {
+            Some(s) => matches!(s.as_str(), "done" | "closed" | "completed" | "complete"),
+            None => self.checks_total > 0 && self.checks_done == self.checks_total,
+        }
+    }
+
+    /// Whether `reference` names this ticket: its id, "Ticket <id>", its title or title slug.
+    pub fn matches_ref(&self, reference: &str) -> bool {
+        let r = reference.trim().trim_start_matches('#');
+        let lower = r.to_lowercase();
+        let without_prefix = lower.strip_prefix("ticket").map(str::trim).unwrap_or(&lower);
+        without_prefix.eq_ignore_ascii_case(&self.id)
+            || lower == self.title.to_lowercase()
+            || title_to_slug(r) == title_to_slug(&self.title)
+    }
+}
+
+/// Body of the `## Tickets` section (without the heading), if present.
+pub fn tickets_section(body: &str) -> Option<&str> {
+    let mut offset = 0;
+    let mut start = None;
+    for line in body.split_inclusive('\n') {
+        let heading = line.trim_end();
+        if let Some(s) = start {
+            if heading.starts_with("## ") {
+                return Some(&body[s..offset]);
+            }
+        } else if heading.trim().eq_ignore_ascii_case("## tickets") {
+            start = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    start.map(|s| &body[s..])
+}
+
+/// Heading depth and text for a Markdown ATX heading line.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.starts_with(' ') {
+        return None;
+    }
+    Some((level, rest.trim()))
+}
+
+/// Split "Ticket 3: Title" into ("3", "Title"); other headings use their slug as id.
+fn split_ticket_heading(text: &str) -> (String, String) {
+    if text.get(..6).is_some_and(|p| p.eq_ignore_ascii_case("ticket")) {
+        let rest_orig = text[6..].trim_start();
+        if let Some(title) = rest_orig.strip_prefix(':') {
+            let title = title.trim().to_string();
+            return (title_to_slug(&title), title);
+        }
+        let end = rest_orig
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '-'))
+            .unwrap_or(rest_orig.len());
+        let (num, tail) = rest_orig.split_at(end);
+        let tail = tail.trim_start();
+        if !num.is_empty() {
+            for sep in [":", "—", "–", "-"] {
+                if let Some(title) = tail.strip_prefix(sep) {
+                    return (num.to_string(), title.trim().to_string());
+                }
+            }
+            if tail.is_empty() {
+                return (num.to_string(), text.to_string());
+            }
+        }
+    }
+    (title_to_slug(text), text.to_string())
+}
+
+/// Parse a `**Key:** value`, `**Key**: value` or known `Key: value` line.
+fn parse_field(line: &str) -> Option<(String, String)> {
+    let t = line.trim().trim_start_matches("- ").trim();
+    if let Some(rest) = t.strip_prefix("**") {
+        let end = rest.find("**")?;
+        let key = rest[..end].trim().trim_end_matches(':').trim();
+        let value = rest[end + 2..].trim().trim_start_matches(':').trim();
+        if key.is_empty() || key.contains('*') {
+            return None;
+        }
+        return Some((key.to_lowercase(), value.to_string()));
+    }
+    let (key, value) = t.split_once(':')?;
+    let key = key.trim().to_lowercase();
+    if PLAIN_FIELDS.contains(&key.as_str()) {
+        return Some((key, value.trim().to_string()));
+    }
+    None
+}
+
+fn checkbox(line: &str) -> Option<bool> {
+    let t = line.trim_start();
+    let t = t
+        .strip_prefix("- ")
+        .or_else(|| t.strip_prefix("* "))
+        .or_else(|| t.strip_prefix("+ "))?;
+    if t.starts_with("[ ]") {
+        Some(false)
+    } else if t.starts_with("[x]") || t.starts_with("[X]") {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Set a `**Key:** value` field on one ticket in a plan body: the ticket's existing field
+/// line is replaced, otherwise the field is inserted under the ticket heading.
+/// Returns None when no ticket has that id.
+pub fn set_ticket_field(body: &str, ticket_id: &str, key: &str, value: &str) -> Option<String> {
+    let ticket = parse_tickets(body).into_iter().find(|t| t.id == ticket_id)?;
+    let mut lines: Vec<String> = body.lines().map(str::to_string).collect();
+    let (start, end) = ticket.lines;
+    let field_line = format!("**{}:** {}", key, value);
+    let existing = (start + 1..end.min(lines.len()))
+        .take_while(|&i| heading(&lines[i]).is_none())
+        .find(|&i| parse_field(&lines[i]).is_some_and(|(k, _)| k.eq_ignore_ascii_case(key)));
+    match existing {
+        Some(i) => lines[i] = field_line,
+        None => {
+            lines.insert(start + 1, String::new());
+            lines.insert(start + 2, field_line);
+        }
+    }
+    let mut out = lines.join("\n");
+    if body.ends_with('\n') {
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// Parse an unindented `1. **Title** (Estimate: 1h)` step line.
+fn parse_step(line: &str) -> Option<Step> {
+    let (num, rest) = line.split_once(". ")?;
+    let number: u32 = num.parse().ok()?;
+    let estimate = inline_estimate(rest);
+    let title = match estimate_marker(rest) {
+        Some((at, _)) => &rest[..at],
+        None => rest,
+    };
+    let title = title.trim().trim_end_matches(['(', '-', '—', ' ']).replace("**", "");
+    Some(Step { number, title: title.trim().to_string(), estimate })
+}
+
+/// Parse all tickets in a plan body. Returns an empty list when there is no `## Tickets` section.
+pub fn parse_tickets(body: &str) -> Vec<Ticket> {
+    let Some(section) = tickets_section(body) else {
+        return vec![];
+    };
+    let mut in_fence = false;
+    let mut headings = vec![];
+    for (i, line) in section.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if !in_fence {
+            if let Some((level, text)) = heading(line) {
+                headings.push((i, level, text.to_string()));
+            }
+        }
+    }
+    let Some(ticket_level) = headings.iter().map(|(_, l, _)| *l).min() else {
+        return vec![];
+    };
+    let lines: Vec<&str> = section.lines().collect();
+    // `section` is a slice of `body`; ticket line numbers are relative to the whole body.
+    let line_offset = body[..section.as_ptr() as usize - body.as_ptr() as usize].lines().count();
+    let starts: Vec<&(usize, usize, String)> = headings.iter().filter(|(_, l, _)| *l == ticket_level).collect();
+    let mut tickets = vec![];
+    for (n, (start, level, text)) in starts.iter().enumerate() {
+        let end = starts.get(n + 1).map(|(i, _, _)| *i).unwrap_or(lines.len());
+        let body_lines = &lines[start + 1..end];
+        let (id, title) = split_ticket_heading(text);
+        let mut fields = vec![];
+        let mut checks_done = 0;
+        let mut checks_total = 0;
+        let mut in_fence = false;
+        let mut list_field: Option<usize> = None;
+        let mut steps: Vec<Step> = vec![];
+        let mut in_steps = false;
+        for line in body_lines {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                continue;
+            }
+            if in_fence {
+                continue;
+            }
+            if let Some(done) = checkbox(line) {
+                checks_total += 1;
+                if done {
+                    checks_done += 1;
+                }
+            }
+            if let Some((_, text)) = heading(line) {
+                list_field = None;
+                in_steps = text.to_lowercase().contains("steps");
+                continue;
+            }
+            if in_steps {
+                if let Some(step) = parse_step(line) {
+                    steps.push(step);
+                } else if let Some(step) = steps.last_mut() {
+                    if step.estimate.is_none() && line.starts_with([' ', '\t']) {
+                        step.estimate = inline_estimate(line);
+                    }
+                }
+                continue;
+            }
+            if let Some((key, value)) = parse_field(line) {
+                list_field = if value.is_empty() { Some(fields.len()) } else { None };
+                fields.push((key, value));
+                continue;
+            }
+            // A field with an empty value collects the list items that follow it.
+            if let Some(idx) = list_field {
+                let t = line.trim();
+                if t.starts_with("- ") || t.starts_with("* ") {
+                    let entry: &mut (String, String) = &mut fields[idx];
+                    if !entry.1.is_empty() {
+                        entry.1.push('\n');
+                    }
+                    entry.1.push_str(t);
+                } else if !t.is_empty() {
+                    list_field = None;
+                }
+            }
+        }
+        tickets.push(Ticket {
+            id,
+            title,
+            level: *level,
+            fields,
+            body: body_lines.join("\n").trim().to_string(),
+            checks_done,
+            checks_total,
+            steps,
+            lines: (line_offset + start, line_offset + end),
+        });
+    }
+    tickets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tickets_fields_and_checkboxes() {
+        let body = "## Overview\n\nx\n\n## Tickets\n\n### Ticket 1: Redis Setup\n\n**Summary:** Connect.\n\n**Definition of Done:** Works.\n\n#### Acceptance Criteria\n\n- [x] a\n- [ ] b\n\n```bash\n# not a heading\n```\n\n### TICKET: Cache Layer\n\nSummary: Cache things\n\nDefinition of Done:\n- one\n- two\n\n## Appendix\n\n- [ ] ignored\n";
+        let tickets = parse_tickets(body);
+        assert_eq!(tickets.len(), 2);
+        assert_eq!(tickets[0].id, "1");
+        assert_eq!(tickets[0].title, "Redis Setup");
+        assert_eq!(tickets[0].summary(), Some("Connect."));
+        assert_eq!((tickets[0].checks_done, tickets[0].checks_total), (1, 2));
+        assert!(!tickets[0].is_done());
+        assert_eq!(tickets[1].id, "cache-layer");
+        assert_eq!(tickets[1].definition_of_done(), Some("- one\n- two"));
+        assert_eq!(tickets[1].checks_total, 0);
+        assert!(tickets[0].matches_ref("Ticket 1"));
+        assert!(tickets[1].matches_ref("Cache Layer"));
+
+        let body = "## Tickets\n\n### Ticket 1: Search and Replace\n\n### Ticket 2: UI\n\n**Depends on:** Search and Replace; Ticket 3.\n";
+        assert_eq!(parse_tickets(body)[1].depends_on(), vec!["Search and Replace", "Ticket 3"]);
+    }
+
+    #[test]
+    fn test_estimates_from_tickets_and_steps() {
+        let body = "## Tickets\n\n### Ticket 1: A\n\n**Estimate:** 1.5d\n\n#### Steps\n\n1. **First** (Estimate: 1h)\n   - Verify: ok\n\n### Ticket 2: B\n\n#### Steps\n\n1. **Model**\n   - Estimate: 2h\n   - Verify: builds\n2. **Wire up** (Estimate: 3pt)\n\n### Ticket 3: C\n";
+        let tickets = parse_tickets(body);
+        let capacity = CapacityConfig { hours_per_day: 6.0, hours_per_point: 4.0, ..Default::default() };
+        assert_eq!(tickets[0].estimate_hours(&capacity), Some(9.0));
+        assert_eq!(tickets[0].steps[0].title, "First");
+        assert_eq!(tickets[1].estimate(), None);
+        assert_eq!(tickets[1].steps.len(), 2);
+        assert_eq!(tickets[1].steps[1].title, "Wire up");
+        assert_eq!(tickets[1].estimate_hours(&capacity), Some(14.0));
+        assert_eq!(tickets[2].estimate_hours(&capacity), None);
+        let totals = estimate_totals(&tickets, &capacity);
+        assert_eq!((totals.hours, totals.points, totals.estimated), (23.0, 3.0, 2));
+        assert_eq!(totals.to_string(), "23h incl. 3pt (2/3 tickets estimated)");
+        assert_eq!("5 points".parse::<Estimate>().unwrap().unit, EstimateUnit::Points);
+
+        let body = set_ticket_field(body, "2", "Status", "done").unwrap();
+        assert!(parse_tickets(&body)[1].is_done());
+        let body = set_ticket_field(&body, "2", "Status", "open").unwrap();
+        assert_eq!(parse_tickets(&body)[1].field("status"), Some("open"));
+        assert_eq!(body.matches("**Status:**").count(), 1);
+
+        // Only the marker cuts a title, never the bare word. Lowercasing `İ` changes its byte
+        // length; offsets must still fall on char boundaries.
+        let body = "## Tickets\n\n### Ticket 1: A\n\n#### Steps\n\n1. **Add İ estimate column to tickets table** (Estimate: 1h)\n2. İİ estimate notes\n   - Estimate: 2h\n3. Estimate review - Estimate: 3h\n";
+        let tickets = parse_tickets(&format!("{}\n### Ticket İ: Dotted\n\n### İ Ticket\n", body));
+        assert_eq!((tickets[1].title.as_str(), tickets[2].title.as_str()), ("Ticket İ: Dotted", "İ Ticket"));
+        let steps: Vec<(&str, Option<f64>)> =
+            tickets[0].steps.iter().map(|s| (s.title.as_str(), s.estimate.map(|e| e.value))).collect();
+        assert_eq!(
+            steps,
+            vec![("Add İ estimate column to tickets table", Some(1.0)), ("İİ estimate notes", Some(2.0)), ("Estimate review", Some(3.0))]
+        );
+    }
+}
@@ -0,0 +1,339 @@
+//! Issue-tracker REST client (GitHub and GitLab shapes) and the ticket-to-issue mapping
+//! that qp records in `tracker.json` inside the plan directory.
+//!
+//! The base URL comes from `[tracker] base_url` (or `--base-url`), so everything here
+//! can be pointed at a local mock server.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::config::TrackerConfig;
+use crate::tickets::Ticket;
+
+const MAP_FILE: &str = "tracker.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrackerKind {
+    Github,
+    Gitlab,
+}
+
+impl TrackerKind {
+    pub fn default_base_url(self) -> &'static str {
+        match self {
+            TrackerKind::Github => "https://api.github.com",
+            TrackerKind::Gitlab => "https://gitlab.com/api/v4",
+        }
+    }
+
+    pub fn default_token_env(self) -> &'static str {
+        match self {
+            TrackerKind::Github => "GITHUB_TOKEN",
+            TrackerKind::Gitlab => "GITLAB_TOKEN",
+        }
+    }
+}
+
+impl std::fmt::Display for TrackerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrackerKind::Github => write!(f, "github"),
+            TrackerKind::Gitlab => write!(f, "gitlab"),
+        }
+    }
+}
+
+/// One exported ticket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueLink {
+    pub ticket: String,
+    pub title: String,
+    /// GitHub issue number or GitLab issue iid.
+    pub number: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub exported_at: String,
+    /// [`ticket_hash`] when the ticket was last exported or synced.
+    pub ticket_hash: String,
+    /// Remote `updated_at` when the issue was last exported or synced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_updated_at: Option<String>,
+    /// Remote state ("open" or "closed") at the last export or sync.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_state: Option<String>,
+}
+
+/// Contents of `tracker.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackerMap {
+    pub tracker: TrackerKind,
+    pub base_url: String,
+    pub repo: String,
+    /// When `qp sync` last ran.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub synced_at: Option<String>,
+    #[serde(default)]
+    pub issues: Vec<IssueLink>,
+}
+
+impl TrackerMap {
+    pub fn issue_for(&self, ticket_id: &str) -> Option<&IssueLink> {
+        self.issues.iter().find(|i| i.ticket == ticket_id)
+    }
+}
+
+pub fn map_path(plan_dir: &Path) -> PathBuf {
+    plan_dir.join(MAP_FILE)
+}
+
+pub fn load_map(plan_dir: &Path) -> Result<Option<TrackerMap>> {
+    let path = map_path(plan_dir);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let s = std::fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+    let map = serde_json::from_str(&s).with_context(|| format!("parse {}", path.display()))?;
+    Ok(Some(map))
+}
+
+pub fn save_map(plan_dir: &Path, map: &TrackerMap) -> Result<()> {
+    let path = map_path(plan_dir);
+    std::fs::write(&path, format!("{}\n", serde_json::to_string_pretty(map)?))
+        .with_context(|| format!("write {}", path.display()))
+}
+
+/// Stable fingerprint of a ticket's content, ignoring its `Status:` line (which
+/// `qp sync` rewrites). FNV-1a, so it does not change between builds.
+pub fn ticket_hash(ticket: &Ticket) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let content = format!("{}\n{}", ticket.title, ticket.body);
+    for line in content.lines() {
+        let t = line.trim().trim_start_matches("- ").to_lowercase();
+        if t.starts_with("**status:**") || t.starts_with("**status**:") || t.starts_with("status:") {
+            continue;
+        }
+        for b in line.trim_end().bytes().chain([b'\n']) {
+            hash ^= b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// An issue as the tracker reports it.
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteIssue {
+    pub number: u64,
+    pub title: String,
+    pub url: Option<String>,
+    /// Normalized to "open" or "closed".
+    pub state: String,
+    pub updated_at: Option<String>,
+}
+
+impl RemoteIssue {
+    pub fn is_closed(&self) -> bool {
+        self.state == "closed"
+    }
+
+    fn from_json(kind: TrackerKind, v: &serde_json::Value) -> Result<Self> {
+        let (number_key, url_key) = match kind {
+            TrackerKind::Github => ("number", "html_url"),
+            TrackerKind::Gitlab => ("iid", "web_url"),
+        };
+        let number = v[number_key]
+            .as_u64()
+            .with_context(|| format!("tracker response has no \"{}\"", number_key))?;
+        let state = match v["state"].as_str().unwrap_or("open") {
+            "closed" => "closed",
+            _ => "open",
+        };
+        Ok(Self {
+            number,
+            title: v["title"].as_str().unwrap_or_default().to_string(),
+            url: v[url_key].as_str().map(str::to_string),
+            state: state.to_string(),
+            updated_at: v["updated_at"].as_str().map(str::to_string),
+        })
+    }
+}
+
+/// Blocking REST client for one repository.
+pub struct TrackerClient {
+    pub kind: TrackerKind,
+    pub base_url: String,
+    pub repo: String,
+    token: Option<String>,
+    http: reqwest::blocking::Client,
+}
+
+impl TrackerClient {
+    /// Build a client from `[tracker]` config; `base_url` and `repo` override it.
+    pub fn new(kind: TrackerKind, config: &TrackerConfig, base_url: Option<&str>, repo: Option<&str>) -> Result<Self> {
+        let base_url = base_url
+            .map(str::to_string)
+            .or_else(|| config.base_url.clone())
+            .unwrap_or_else(|| kind.default_base_url().to_string());
+        let repo = repo
+            .map(str::to_string)
+            .or_else(|| config.repo.clone())
+            .context("no repository: set `repo` under [tracker] in config.toml or pass --repo")?;
+        let token_env = config.token_env.clone().unwrap_or_else(|| kind.default_token_env().to_string());
+        let token = std::env::var(&token_env).ok().filter(|t| !t.is_empty());
+        Ok(Self {
+            kind,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            repo,
+            token,
+            http: reqwest::blocking::Client::new(),
+        })
+    }
+
+    fn issues_url(&self) -> String {
+        match self.kind {
+            TrackerKind::Github => format!("{}/repos/{}/issues", self.base_url, self.repo),
+            TrackerKind::Gitlab => format!("{}/projects/{}/issues", self.base_url, self.repo.replace('/', "%2F")),
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::blocking::RequestBuilder {
+        let mut req = self.http.request(method, url).header("User-Agent", "qp");
+        req = match self.kind {
+            TrackerKind::Github => req.header("Accept", "application/vnd.github+json"),
+            TrackerKind::Gitlab => req,
+        };
+        match (&self.token, self.kind) {
+            (Some(t), TrackerKind::Github) => req.bearer_auth(t),
+            (Some(t), TrackerKind::Gitlab) => req.header("PRIVATE-TOKEN", t),
+            (None, _) => req,
+        }
+    }
+
+    fn send(&self, req: reqwest::blocking::RequestBuilder, what: &str) -> Result<serde_json::Value> {
+        let resp = req.send().with_context(|| format!("{} ({})", what, self.base_url))?;
+        let status = resp.status();
+        let text = resp.text().unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!("{}: tracker returned {}: {}", what, status, text.trim());
+        }
+        serde_json::from_str(&text).with_context(|| format!("{}: invalid JSON from tracker", what))
+    }
+
+    /// Create an issue. `body` is Markdown; `labels` become tracker labels.
+    pub fn create_issue(&self, title: &str, body: &str, labels: &[String]) -> Result<RemoteIssue> {
+        let payload = match self.kind {
+            TrackerKind::Github => serde_json::json!({ "title": title, "body": body, "labels": labels }),
+            TrackerKind::Gitlab => serde_json::json!({ "title": title, "description": body, "labels": labels.join(",") }),
+        };
+        let v = self.send(
+            self.request(reqwest::Method::POST, &self.issues_url()).json(&payload),
+            &format!("create issue \"{}\"", title),
+        )?;
+        RemoteIssue::from_json(self.kind, &v)
+    }
+
+    /// Fetch one issue by number (GitHub) or iid (GitLab).
+    pub fn get_issue(&self, number: u64) -> Result<RemoteIssue> {
+        let url = format!("{}/{}", self.issues_url(), number);
+        let v = self.send(self.request(reqwest::Method::GET, &url), &format!("get issue #{}", number))?;
+        RemoteIssue::from_json(self.kind, &v)
+    }
+}
+
+/// In-process stand-in for the GitHub issues API, served over a `TcpListener` on
+/// localhost: `POST .../issues` creates an issue, `GET .../issues/N` returns it.
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    pub struct MockGithub {
+        pub base_url: String,
+        pub issues: Arc<Mutex<Vec<serde_json::Value>>>,
+        /// "METHOD path" of every request served, in order.
+        pub requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockGithub {
+        pub fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let base_url = format!("http://{}", listener.local_addr().unwrap());
+            let issues: Arc<Mutex<Vec<serde_json::Value>>> = Arc::default();
+            let requests: Arc<Mutex<Vec<String>>> = Arc::default();
+            let (i, r, url) = (issues.clone(), requests.clone(), base_url.clone());
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else { continue };
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut len = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if line.trim().is_empty() {
+                            break;
+                        }
+                        if let Some((k, v)) = line.split_once(':') {
+                            if k.eq_ignore_ascii_case("content-length") {
+                                len = v.trim().parse().unwrap();
+                            }
+                        }
+                    }
+                    let mut body = vec![0; len];
+                    reader.read_exact(&mut body).unwrap();
+                    let mut parts = request_line.split_whitespace();
+                    let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+                    r.lock().unwrap().push(format!("{} {}", method, path));
+                    let mut issues = i.lock().unwrap();
+                    let number = path.rsplit('/').next().and_then(|n| n.parse::<usize>().ok());
+                    let (status, reply) = match (method, number) {
+                        ("POST", None) => {
+                            let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                            let n = issues.len() + 1;
+                            issues.push(serde_json::json!({
+                                "number": n,
+                                "title": payload["title"],
+                                "body": payload["body"],
+                                "html_url": format!("{}/issues/{}", url, n),
+                                "state": "open",
+                                "updated_at": "2026-01-01T00:00:00Z",
+                            }));
+                            ("201 Created", issues[n - 1].to_string())
+                        }
+                        ("GET", Some(n)) if n >= 1 && n <= issues.len() => ("200 OK", issues[n - 1].to_string()),
+                        _ => ("404 Not Found", r#"{"message":"Not Found"}"#.to_string()),
+                    };
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        reply.len(),
+                        reply
+                    );
+                }
+            });
+            Self { base_url, issues, requests }
+        }
+
+        /// Close issue `number` remotely, bumping its `updated_at`.
+        pub fn close(&self, number: u64) {
+            let mut issues = self.issues.lock().unwrap();
+            let issue = &mut issues[number as usize - 1];
+            issue["state"] = "closed".into();
+            issue["updated_at"] = "2026-01-02T00:00:00Z".into();
+        }
+
+        pub fn client(&self) -> super::TrackerClient {
+            let config = crate::config::TrackerConfig {
+                base_url: Some(self.base_url.clone()),
+                repo: Some("acme/api".to_string()),
+                token_env: Some("QP_TEST_NO_TOKEN".to_string()),
+            };
+            super::TrackerClient::new(super::TrackerKind::Github, &config, None, None).unwrap()
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! `qp init --upgrade`: bring a `.qp` directory written by an older qp up to date.
+//!
+//! The layout version is stored in `.qp/version`; directories without one are version 1.
+//! An upgrade is computed as a list of file rewrites first, so the caller can show a diff
+//! and ask before [`apply`] writes anything. Every migration is idempotent: upgrading an
+//! up-to-date directory yields no changes.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+use std::path::{Path, PathBuf};
+
+use crate::plan::{self, PlanLocation};
+use crate::prompts;
+
+/// Current `.qp` layout version.
+/// - 1: no version file; config.toml may carry the default prompts inline
+/// - 2: `.qp/version`; built-in prompts come from the prompt library
+pub const LAYOUT_VERSION: u32 = 2;
+
+const VERSION_FILE: &str = "version";
+
+/// Prompts that `qp init` used to write into config.toml, before the prompt library.
+const LEGACY_PROMPTS: &[(&str, &str)] = &[
+    ("holes", "Review this plan and identify weaknesses, missing considerations, edge cases, potential failures, and assumptions that need validation."),
+    ("details", "Expand this plan with implementation details. Add specifics about technologies, APIs, data structures, and algorithms."),
+    ("breakdown", "Break this plan into precise, atomic steps. Each step should be independently implementable and testable."),
+    ("deliverables", "Define clear acceptance criteria for each component. What tests must pass? What can be demonstrated? How do we know it's done?"),
+    ("estimate", "Estimate every ticket and step. Add a `**Estimate:** 4h` line under each ticket heading and a `- Estimate: 1h` bullet under each numbered step (units: h, d or pt)."),
+];
+
+/// Layout version of an existing `.qp` directory.
+pub fn layout_version(qp_root: &Path) -> Result<u32> {
+    let path = qp_root.join(VERSION_FILE);
+    if !path.exists() {
+        return Ok(1);
+    }
+    let text = std::fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+    text.trim().parse().with_context(|| format!("{}: invalid layout version \"{}\"", path.display(), text.trim()))
+}
+
+/// Mark a freshly created `.qp` directory as current.
+pub fn write_layout_version(qp_root: &Path) -> Result<()> {
+    let path = qp_root.join(VERSION_FILE);
+    std::fs::write(&path, format!("{}\n", LAYOUT_VERSION)).with_context(|| format!("write {}", path.display()))
+}
+
+/// One file an upgrade creates or rewrites.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub reasons: Vec<String>,
+    #[serde(skip)]
+    pub before: Option<String>,
+    #[serde(skip)]
+    pub after: String,
+}
+
+impl FileChange {
+    fn new(path: PathBuf, before: Option<String>, after: String, reasons: Vec<String>) -> Option<Self> {
+        (before.as_deref() != Some(after.as_str())).then_some(Self { path, reasons, before, after })
+    }
+
+    /// Unified diff of the change, with paths relative to `base`.
+    pub fn diff(&self, base: &Path) -> String {
+        let name = self.path.strip_prefix(base).unwrap_or(&self.path).display().to_string();
+        let before = self.before.as_deref().unwrap_or("");
+        let diff = TextDiff::from_lines(before, &self.after);
+        let mut out = match self.before {
+            Some(_) => format!("--- a/{}\n+++ b/{}\n", name, name),
+            None => format!("--- /dev/null\n+++ b/{}\n", name),
+        };
+        for group in diff.grouped_ops(3) {
+            let (first, last) = (&group[0], &group[group.len() - 1]);
+            let old = first.old_range().start..last.old_range().end;
+            let new = first.new_range().start..last.new_range().end;
+            out.push_str(&format!("@@ -{},{} +{},{} @@\n", old.start + 1, old.len(), new.start + 1, new.len()));
+            for op in &group {
+                for change in diff.iter_changes(op) {
+                    let sign = match change.tag() {
+                        ChangeTag::Insert => '+',
+                        ChangeTag::Delete => '-',
+                        ChangeTag::Equal => ' ',
+                    };
+                    out.push(sign);
+                    out.push_str(change.value().trim_end_matches('\n'));
+                    out.push('\n');
+                }
+            }
+        }
+        out
+    }
+}
+
+/// What [`plan_upgrade`] found.
+#[derive(Debug, Clone, Serialize)]
+pub struct Upgrade {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub changes: Vec<FileChange>,
+}
+
+/// Compute the upgrade of `qp_root` without writing anything.
+pub fn plan_upgrade(qp_root: &Path) -> Result<Upgrade> {
+    let from_version = layout_version(qp_root)?;
+    if from_version > LAYOUT_VERSION {
+        anyhow::bail!(
+            "{} has layout version {}, newer than this qp supports ({}); upgrade qp instead",
+            qp_root.display(),
+            from_version,
+            LAYOUT_VERSION
+        );
+    }
+    let read = |path: &Path| -> Result<Option<String>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        std::fs::read_to_string(path).map(Some).with_context(|| format!("read {}", path.display()))
+    };
+    let mut changes = vec![];
+
+    let config_path = qp_root.join("config.toml");
+    let config_before = read(&config_path)?;
+    let (config_after, reasons) = match &config_before {
+        Some(text) => upgrade_config(text).with_context(|| format!("upgrade {}", config_path.display()))?,
+        None => (plan::default_config_toml().to_string(), vec!["create the default config".to_string()]),
+    };
+    changes.extend(FileChange::new(config_path, config_before, config_after, reasons));
+
+    let format_path = qp_root.join("plan-format.md");
+    let reason = vec!["refresh the plan format reference".to_string()];
+    changes.extend(FileChange::new(format_path.clone(), read(&format_path)?, plan::plan_format_md_content().to_string(), reason));
+
+    for location in [PlanLocation::Active, PlanLocation::Archived, PlanLocation::Trashed] {
+        let dir = location.dir(qp_root);
+        if !dir.exists() {
+            continue;
+        }
+        let mut entries: Vec<PathBuf> = std::fs::read_dir(&dir)?.filter_map(|e| e.ok()).map(|e| e.path().join("plan.md")).collect();
+        entries.sort();
+        for path in entries.into_iter().filter(|p| p.exists()) {
+            let text = std::fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+            let (after, reasons) = upgrade_plan(&text).with_context(|| format!("upgrade {}", path.display()))?;
+            changes.extend(FileChange::new(path, Some(text), after, reasons));
+        }
+    }
+
+    let version_path = qp_root.join(VERSION_FILE);
+    let reason = vec![format!("record layout version {}", LAYOUT_VERSION)];
+    changes.extend(FileChange::new(version_path.clone(), read(&version_path)?, format!("{}\n", LAYOUT_VERSION), reason));
+    Ok(Upgrade { from_version, to_version: LAYOUT_VERSION, changes })
+}
+
+/// Write every change of `upgrade`.
+pub fn apply(upgrade: &Upgrade) -> Result<()> {
+    for change in &upgrade.changes {
+        if let Some(parent) = change.path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+        }
+        std::fs::write(&change.path, &change.after).with_context(|| format!("write {}", change.path.display()))?;
+    }
+    Ok(())
+}
+
+/// Migrate one config.toml, keeping comments and layout:
+/// - drop review-agent prompts that are a (legacy) built-in default, so the step follows
+///   the prompt library
+/// - when the file sets its own `[agent]`, add built-in review agents it does not define
+///   yet, running that agent instead of the default command
+fn upgrade_config(text: &str) -> Result<(String, Vec<String>)> {
+    let mut doc: toml_edit::DocumentMut = text.parse()?;
+    let mut reasons = vec![];
+
+    if let Some(agents) = doc.get_mut("review_agents").and_then(|i| i.as_table_like_mut()) {
+        for (name, agent) in agents.iter_mut() {
+            let Some(agent) = agent.as_table_like_mut() else { continue };
+            let Some(prompt) = agent.get("prompt").and_then(|p| p.as_str()).map(str::trim) else { continue };
+            let legacy = LEGACY_PROMPTS.iter().any(|(n, p)| *n == name.get() && *p == prompt);
+            let current = prompts::builtin(name.get()).is_some_and(|p| p.text().trim() == prompt);
+            if legacy || current {
+                agent.remove("prompt");
+                reasons.push(format!("review_agents.{}.prompt: default prompt, now taken from the prompt library", name.get()));
+            }
+        }
+    }
+
+    let replace = doc.get("merge").and_then(|m| m.get("review_agents")).and_then(|v| v.as_str()) == Some("replace");
+    let agent = doc.get("agent").and_then(|a| a.as_table_like()).map(|a| {
+        ["command", "args", "profile"].iter().filter_map(|k| a.get(k).and_then(|v| v.as_value()).map(|v| (*k, v.clone()))).collect::<Vec<_>>()
+    });
+    if let Some(agent) = agent.filter(|a| !a.is_empty() && !replace) {
+        let missing: Vec<&str> = prompts::BUILTIN
+            .iter()
+            .map(|p| p.name)
+            .filter(|name| doc.get("review_agents").and_then(|t| t.get(name)).is_none())
+            .collect();
+        if !missing.is_empty() {
+            let agents = doc.entry("review_agents").or_insert_with(|| {
+                let mut t = toml_edit::Table::new();
+                t.set_implicit(true);
+                toml_edit::Item::Table(t)
+            });
+            let agents = agents.as_table_like_mut().context("review_agents is not a table")?;
+            for name in missing {
+                let mut table = toml_edit::Table::new();
+                for (key, value) in &agent {
+                    table.insert(key, toml_edit::value(value.clone()));
+                }
+                agents.insert(name, toml_edit::Item::Table(table));
+                reasons.push(format!("review_agents.{}: new built-in step, set to run the configured [agent]", name));
+            }
+        }
+    }
+    Ok((doc.to_string(), reasons))
+}
+
+/// Migrate one plan.md: fill in fields newer qp versions record (the persisted slug) and
+/// rewrite the frontmatter in the current format (e.g. `priority: p1` becomes `high`).
+fn upgrade_plan(text: &str) -> Result<(String, Vec<String>)> {
+    let mut plan = plan::parse_plan(text)?;
+    let mut reasons = vec![];
+    if plan.meta.slug.is_none() {
+        plan.meta.slug = Some(plan::title_to_slug(&plan.meta.title));
+        reasons.push("add slug".to_string());
+    }
+    let after = plan::serialize_plan(&plan)?;
+    let (front_before, _) = text.split_once("\n---\n").unwrap_or((text, ""));
+    let (front_after, _) = after.split_once("\n---\n").unwrap_or((&after, ""));
+    if front_before.trim() != front_after.trim() && reasons.is_empty() {
+        reasons.push("rewrite frontmatter in the current format".to_string());
+    }
+    if reasons.is_empty() {
+        return Ok((text.to_string(), reasons));
+    }
+    Ok((after, reasons))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_legacy_qp_dir() {
+        let tmp = std::env::temp_dir().join("qp_test_upgrade");
+        let _ = std::fs::remove_dir_all(&tmp);
+        let plan_dir = tmp.join("plans/p1");
+        std::fs::create_dir_all(&plan_dir).unwrap();
+        let config = format!(
+            "# mine\n[agent]\ncommand = \"agent\"\n\n[review_agents.holes]\ncommand = \"agent\"\nprompt = \"{}\"\n\n\
+[review_agents.details]\ncommand = \"agent\"\nprompt = \"Be brief.\"\n",
+            LEGACY_PROMPTS[0].1
+        );
+        std::fs::write(tmp.join("config.toml"), &config).unwrap();
+        std::fs::write(
+            plan_dir.join("plan.md"),
+            "---\nid: p1\ntitle: My Plan\nstate: draft\ncreated_at: x\nupdated_at: x\npriority: p1\n---\n\n## Overview\n",
+        )
+        .unwrap();
+
+        let upgrade = plan_upgrade(&tmp).unwrap();
+        assert_eq!((upgrade.from_version, upgrade.to_version), (1, LAYOUT_VERSION));
+        let config_change = &upgrade.changes[0];
+        assert!(config_change.diff(&tmp).contains(&format!("-prompt = \"{}\"", LEGACY_PROMPTS[0].1)));
+        apply(&upgrade).unwrap();
+
+        let text = std::fs::read_to_string(tmp.join("config.toml")).unwrap();
+        assert!(text.starts_with("# mine\n") && text.contains("prompt = \"Be brief.\""));
+        let loaded = crate::config::load_config(Some(&tmp)).unwrap();
+        assert_eq!(loaded.review_agents["holes"].prompt, prompts::builtin("holes").unwrap().text());
+        assert_eq!(loaded.review_agents["estimate"].command, "agent");
+        let plan = plan::parse_plan(&std::fs::read_to_string(plan_dir.join("plan.md")).unwrap()).unwrap();
+        assert_eq!(plan.meta.slug.as_deref(), Some("my-plan"));
+        assert!(tmp.join("plan-format.md").exists());
+        assert_eq!(layout_version(&tmp).unwrap(), LAYOUT_VERSION);
+        assert!(plan_upgrade(&tmp).unwrap().changes.is_empty());
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}